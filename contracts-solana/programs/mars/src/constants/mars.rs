@@ -14,6 +14,14 @@ pub const FEE_TIERS_SEED: &[u8] = b"fee-tiers-seed";
 pub const TARGET_CHAIN_MIN_FEE_SEED: &[u8] = b"mars-target-chain-min-fee";
 pub const PROTOCOL_FEE_FRACTION_SEED: &[u8] = b"protocol-fee-fraction-seed";
 pub const INSURANCE_FEE_TIERS_SEED: &[u8] = b"insurance-fee-tiers-seed";
+pub const REBALANCE_CONFIG_SEED: &[u8] = b"rebalance-config";
+pub const PENDING_CHANGE_SEED: &[u8] = b"pending-change";
+pub const ROLE_REGISTRY_SEED: &[u8] = b"mars-role-registry";
+pub const WITHDRAWAL_REQUEST_SEED: &[u8] = b"withdrawal-request";
+pub const WITHDRAWAL_ESCROW_SEED: &[u8] = b"withdrawal-escrow";
+pub const WITHDRAWAL_RESERVE_SEED: &[u8] = b"withdrawal-reserve";
+pub const INSURANCE_TREASURY_SEED: &[u8] = b"insurance-treasury";
+pub const SHORTFALL_SEED: &[u8] = b"shortfall-record";
 
 // === Array Limits ===
 pub const MAX_FREEZE_AUTHORITY_LENGTH: usize = 10;
@@ -22,6 +30,9 @@ pub const MAX_FEE_TIERS_LENGTH: usize = 10;
 pub const MAX_USERS_PER_VAULT: usize = 1000;
 pub const MAX_PROTOCOLS_PER_VAULT: usize = 10;
 pub const MAX_REBALANCE_HISTORY: usize = 100;
+pub const MAX_STATUS_REASON_LENGTH: usize = 200;
+pub const MAX_ROLE_MEMBERS: usize = 10;
+pub const MAX_MIN_AMOUNT_OUT_LENGTH: usize = 78; // digits of u256::MAX
 
 // === Fee Configurations (Basis Points) ===
 pub const BASE_PERCENTAGE: u64 = 10_000;
@@ -31,6 +42,14 @@ pub const DEFAULT_DEPOSIT_FEE_BPS: u16 = 0; // 0%
 pub const DEFAULT_WITHDRAW_FEE_BPS: u16 = 50; // 0.5%
 pub const DEFAULT_MANAGEMENT_FEE_BPS: u16 = 200; // 2% annual
 pub const DEFAULT_PERFORMANCE_FEE_BPS: u16 = 1000; // 10%
+pub const MAX_DEPOSIT_FEE_BPS: u16 = 200; // 2%
+pub const MAX_WITHDRAW_FEE_BPS: u16 = 200; // 2%
+pub const MAX_MANAGEMENT_FEE_BPS: u16 = 500; // 5% annual
+pub const MAX_PERFORMANCE_FEE_BPS: u16 = 3000; // 30%
+
+// === Time ===
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 3600;
+pub const MAX_TIMELOCK_DELAY_SECONDS: u64 = 30 * 24 * 3600; // 30 days
 
 // === Risk Management ===
 pub const MAX_SLIPPAGE_BPS: u16 = 500; // 5%
@@ -199,6 +199,81 @@ pub enum CustomError {
 
     #[msg("Platform fee account owner does not match configured platform fee wallet")]
     InvalidPlatformFeeAccount,
+
+    #[msg("Rebalance is in cooldown")]
+    RebalanceCooldownActive,
+
+    #[msg("Invalid rebalance configuration")]
+    InvalidRebalanceConfig,
+
+    #[msg("Invalid vault status transition")]
+    InvalidStatusTransition,
+
+    #[msg("Vault still has outstanding shares")]
+    VaultNotEmpty,
+
+    #[msg("Protocol is already registered for this vault")]
+    ProtocolAlreadyRegistered,
+
+    #[msg("Protocol still holds vault assets")]
+    ProtocolNotEmpty,
+
+    #[msg("Target allocations of enabled protocols must sum to 10000 bps")]
+    InvalidAllocationSum,
+
+    #[msg("This change must be queued through the timelock first")]
+    TimelockRequired,
+
+    #[msg("Timelock delay has not elapsed yet")]
+    TimelockNotExpired,
+
+    #[msg("Queued change does not match the requested change")]
+    PendingChangeMismatch,
+
+    #[msg("Role members limit exceeded")]
+    RoleMembersLimitExceeded,
+
+    #[msg("No pending admin nomination")]
+    NoPendingAdmin,
+
+    #[msg("Deposit amount is below the vault minimum")]
+    DepositBelowMinimum,
+
+    #[msg("Deposit amount exceeds the per-transaction maximum")]
+    DepositAboveMaximum,
+
+    #[msg("Deposit would exceed the vault TVL cap")]
+    VaultDepositCapExceeded,
+
+    #[msg("Deposit would exceed the per-user position cap")]
+    UserDepositCapExceeded,
+
+    #[msg("Withdrawal request is not in the expected status")]
+    InvalidWithdrawalRequestStatus,
+
+    #[msg("Not enough liquidity in the treasury to process the withdrawal")]
+    InsufficientLiquidity,
+
+    #[msg("Fee tier accounts are required when tiered deposit fees are enabled")]
+    FeeTierAccountsRequired,
+
+    #[msg("Protocol allocation changed since the shortfall was detected")]
+    StaleShortfall,
+
+    #[msg("Cover amount exceeds the outstanding shortfall")]
+    CoverExceedsShortfall,
+
+    #[msg("Not enough funds in the insurance treasury")]
+    InsufficientInsuranceFunds,
+
+    #[msg("Protocol rate source account is invalid")]
+    InvalidRateSource,
+
+    #[msg("Position account does not match the protocol registry")]
+    PositionAccountMismatch,
+
+    #[msg("Position accounts are required to value protocol allocations")]
+    PositionAccountsRequired,
 }
 
 pub type MarsError = CustomError;
@@ -34,6 +34,18 @@ pub struct RebalanceEvent {
     pub timestamp: i64,
 }
 
+/// 再平衡配置更新事件
+#[event]
+pub struct RebalanceConfigUpdated {
+    pub vault_id: [u8; 32],
+    pub deviation_threshold_bps: u16,
+    pub min_rebalance_amount: u64,
+    pub max_rebalance_amount: u64,
+    pub cooldown_seconds: i64,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
 /// 兑换事件
 #[event]
 pub struct SwapEvent {
@@ -67,6 +79,17 @@ pub struct ProtocolConfigUpdated {
     pub timestamp: i64,
 }
 
+/// 协议 APY 更新事件
+#[event]
+pub struct ProtocolApyUpdated {
+    pub vault_id: [u8; 32],
+    pub protocol_id: u8,
+    pub price: u64,
+    pub apy_bps: u64,
+    pub updater: Pubkey,
+    pub timestamp: i64,
+}
+
 /// 费用配置更新事件
 #[event]
 pub struct FeeConfigUpdated {
@@ -75,6 +98,41 @@ pub struct FeeConfigUpdated {
     pub withdraw_fee_bps: u16,
     pub management_fee_bps: u16,
     pub performance_fee_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 存款限额更新事件
+#[event]
+pub struct DepositLimitsUpdated {
+    pub vault_id: [u8; 32],
+    pub max_total_deposits: u64,
+    pub max_user_deposit: u64,
+    pub min_deposit_amount: u64,
+    pub max_deposit_amount: u64,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 管理费计提事件（以增发份额的形式收取）
+#[event]
+pub struct ManagementFeeAccrued {
+    pub vault_id: [u8; 32],
+    pub fee_recipient: Pubkey,
+    pub fee_shares: u64,
+    pub fee_amount: u64,
+    pub elapsed_seconds: i64,
+    pub timestamp: i64,
+}
+
+/// 性能费结算事件（仅对超过高水位的收益收取）
+#[event]
+pub struct PerformanceFeeCrystallized {
+    pub vault_id: [u8; 32],
+    pub fee_amount: u64,
+    pub share_price: u64,
+    pub previous_high_water_mark: u64,
+    pub new_high_water_mark: u64,
     pub timestamp: i64,
 }
 
@@ -94,6 +152,9 @@ pub enum EmergencyEventType {
     Resume,
     EmergencyWithdraw,
     ForceRebalance,
+    EnterEmergency,
+    Close,
+    UnwindAll,
 }
 
 /// Farm 奖励领取事件
@@ -127,3 +188,205 @@ pub struct PlatformFeeWalletUpdatedEvent {
     pub updated_by: Pubkey,
     pub timestamp: i64,
 }
+
+/// Timelock 变更排队事件
+#[event]
+pub struct PendingChangeQueued {
+    pub pending_change: Pubkey,
+    pub target: Pubkey,
+    pub kind: u8,
+    pub change: crate::state::PendingChangeData,
+    pub proposer: Pubkey,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+/// Timelock 变更执行事件
+#[event]
+pub struct PendingChangeExecuted {
+    pub pending_change: Pubkey,
+    pub target: Pubkey,
+    pub kind: u8,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Timelock 变更取消事件
+#[event]
+pub struct PendingChangeCancelled {
+    pub pending_change: Pubkey,
+    pub target: Pubkey,
+    pub kind: u8,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 角色授予事件
+#[event]
+pub struct RoleGranted {
+    pub role: crate::state::Role,
+    pub account: Pubkey,
+    pub granted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 角色撤销事件
+#[event]
+pub struct RoleRevoked {
+    pub role: crate::state::Role,
+    pub account: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Vault 管理员提名事件
+#[event]
+pub struct VaultAdminNominated {
+    pub vault_id: [u8; 32],
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Vault 管理员转移完成事件
+#[event]
+pub struct VaultAdminTransferred {
+    pub vault_id: [u8; 32],
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Vault 管理员提名取消事件
+#[event]
+pub struct VaultAdminNominationCancelled {
+    pub vault_id: [u8; 32],
+    pub admin: Pubkey,
+    pub cancelled_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 排队提款请求事件
+#[event]
+pub struct WithdrawalRequested {
+    pub vault_id: [u8; 32],
+    pub user: Pubkey,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+/// 排队提款处理事件（份额按此时价格结算并销毁）
+#[event]
+pub struct WithdrawalProcessed {
+    pub vault_id: [u8; 32],
+    pub user: Pubkey,
+    pub shares_burned: u64,
+    pub claimable_amount: u64,
+    pub withdraw_fee: u64,
+    pub processed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 排队提款领取事件
+#[event]
+pub struct WithdrawalClaimed {
+    pub vault_id: [u8; 32],
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 排队提款取消事件（份额退回用户）
+#[event]
+pub struct WithdrawalCancelled {
+    pub vault_id: [u8; 32],
+    pub user: Pubkey,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+/// 排队提款流动性释放事件（keeper 从协议撤回资金到 Treasury）
+#[event]
+pub struct WithdrawalLiquidityReleased {
+    pub vault_id: [u8; 32],
+    pub protocol_id: u8,
+    pub amount_requested: u64,
+    pub amount_received: u64,
+    pub keeper: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 跨链订单创建事件（USDC 已托管到 ata_vault）
+#[event]
+pub struct OrderCreated {
+    pub order: Pubkey,
+    pub order_hash: [u8; 32],
+    pub trader: Pubkey,
+    pub receiver: [u8; 32],
+    pub src_chain_id: u32,
+    pub dest_chain_id: u32,
+    pub token_in: Pubkey,
+    pub token_out: [u8; 32],
+    pub amount_in: u64,
+    pub fee: u64,
+    pub min_amount_out: String,
+    pub fill_deadline: i64,
+    pub timestamp: i64,
+}
+
+/// 跨链订单成交事件（orchestrator 确认目标链已交付）
+#[event]
+pub struct OrderFilled {
+    pub order: Pubkey,
+    pub order_hash: [u8; 32],
+    pub orchestrator: Pubkey,
+    pub amount_in: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+/// 跨链订单撤销事件（超过截止时间后退款给 trader）
+#[event]
+pub struct OrderReverted {
+    pub order: Pubkey,
+    pub order_hash: [u8; 32],
+    pub trader: Pubkey,
+    pub refund_amount: u64,
+    pub protocol_fee: u64,
+    pub timestamp: i64,
+}
+
+/// 全局 Asset 费用桶领取事件
+#[event]
+pub struct AssetFeeClaimed {
+    pub bucket: crate::state::AssetFeeBucket,
+    pub claimer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub remaining_unclaimed: u64,
+    pub timestamp: i64,
+}
+
+/// 协议亏损检测事件（测得价值低于账面持仓）
+#[event]
+pub struct ShortfallDetected {
+    pub vault_id: [u8; 32],
+    pub protocol_id: u8,
+    pub recorded_allocation: u64,
+    pub measured_value: u64,
+    pub shortfall: u64,
+    pub detector: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 保险金库补偿协议亏损事件
+#[event]
+pub struct ShortfallCovered {
+    pub vault_id: [u8; 32],
+    pub protocol_id: u8,
+    pub covered_amount: u64,
+    pub remaining_shortfall: u64,
+    pub insurance_balance: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,155 @@
+use crate::events::AssetFeeClaimed;
+use crate::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+// ============================================================================
+// Claim Asset Fee - 从全局 Asset 费用桶领取费用
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct ClaimAssetFee<'info> {
+    //  Global admin, fee manager (base / lp / protocol) or guardian (insurance)
+    pub claimer: Signer<'info>,
+
+    //  Global state
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Fee buckets
+    #[account(
+        mut,
+        seeds = [ASSET_SEED],
+        bump
+    )]
+    pub asset: Box<Account<'info, Asset>>,
+
+    //  Store deposited assets
+    #[account(
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault: AccountInfo<'info>,
+
+    //  USDC ata of vault, holds the collected fees
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    //  Receives the claimed fee, must be the insurance treasury for the insurance bucket
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == usdc_mint.key() @MarsError::InvalidMint
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    //  Insurance treasury, required when claiming the insurance bucket
+    #[account(
+        seeds = [INSURANCE_TREASURY_SEED],
+        bump
+    )]
+    pub insurance_treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        address = global_state.base_mint @MarsError::InvalidMint
+    )]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    //  Role registry (optional, only admin is accepted when absent)
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl ClaimAssetFee<'_> {
+    //  role that may claim each bucket, insurance is reserved to cover losses
+    pub fn required_role(bucket: AssetFeeBucket) -> Role {
+        match bucket {
+            AssetFeeBucket::Insurance => Role::Guardian,
+            AssetFeeBucket::Base | AssetFeeBucket::Lp | AssetFeeBucket::Protocol => {
+                Role::FeeManager
+            }
+        }
+    }
+
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        bucket: AssetFeeBucket,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, MarsError::ZeroAmount);
+        require!(
+            RoleRegistry::authorizes(
+                &ctx.accounts.role_registry,
+                Self::required_role(bucket),
+                &ctx.accounts.global_state.admin,
+                &ctx.accounts.claimer.key(),
+            ),
+            MarsError::UnauthorizedSigner
+        );
+
+        if bucket == AssetFeeBucket::Insurance {
+            let insurance_treasury =
+                ctx.accounts.insurance_treasury.as_ref().ok_or(MarsError::InvalidOwner)?;
+            require_keys_eq!(
+                ctx.accounts.recipient_token_account.key(),
+                insurance_treasury.key(),
+                MarsError::InvalidOwner
+            );
+        }
+
+        ctx.accounts.asset.claim(bucket, amount)?;
+
+        let vault_bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &vault_bump]];
+        //  transfer_checked so that a Token-2022 base mint (and insurance treasury) is supported
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        let remaining_unclaimed = ctx.accounts.asset.unclaimed(bucket);
+
+        msg!(
+            "ClaimAssetFee: {{\
+            \"claimer\":\"{:?}\",\
+            \"bucket\":\"{:?}\",\
+            \"amount\":\"{:?}\",\
+            \"remaining_unclaimed\":\"{:?}\"\
+            }}",
+            ctx.accounts.claimer.key(),
+            bucket,
+            amount,
+            remaining_unclaimed
+        );
+
+        emit!(AssetFeeClaimed {
+            bucket,
+            claimer: ctx.accounts.claimer.key(),
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            remaining_unclaimed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
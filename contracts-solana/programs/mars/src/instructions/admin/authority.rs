@@ -116,10 +116,36 @@ pub struct NominateAuthority<'info> {
         constraint = global_state.frozen == false @MarsError::GlobalStateFrozen
     )]
     pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Matured queued nomination, required when the timelock is enabled
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            global_state.key().as_ref(),
+            &[PendingChangeData::KIND_GLOBAL_ADMIN]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    //  Receives the pending change rent, checked against pending_change.proposer
+    /// CHECK: address checked in consume_pending_change
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
 }
 
 impl NominateAuthority<'_> {
     pub fn process_instruction(ctx: Context<Self>, new_admin: Pubkey) -> Result<()> {
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.global_state.key(),
+            &PendingChangeData::GlobalAdmin { new_admin },
+            &ctx.accounts.admin.key(),
+        )?;
+
         let global_state = &mut ctx.accounts.global_state;
 
         global_state.pending_admin = Some(new_admin);
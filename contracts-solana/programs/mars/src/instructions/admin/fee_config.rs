@@ -33,6 +33,23 @@ pub struct SetFeeTiers<'info> {
     )]
     pub fee_tiers: Box<Account<'info, FeeTiers>>,
 
+    //  Matured queued change, required when the timelock is enabled
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            global_state.key().as_ref(),
+            &[PendingChangeData::KIND_FEE_TIERS]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    //  Receives the pending change rent, checked against pending_change.proposer
+    /// CHECK: address checked in consume_pending_change
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+
     //  Needed to init new account
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -45,6 +62,18 @@ impl SetFeeTiers<'_> {
         threshold_amounts: &Vec<u64>,
         bps_fees: &Vec<u64>,
     ) -> Result<()> {
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.global_state.key(),
+            &PendingChangeData::FeeTiers {
+                threshold_amounts: threshold_amounts.clone(),
+                bps_fees: bps_fees.clone(),
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
         let threshold_length = threshold_amounts.len();
 
         // Check for empty arrays
@@ -132,6 +161,23 @@ pub struct SetProtocolFeeFraction<'info> {
     )]
     pub protocol_fee_fraction: Box<Account<'info, ProtocolFeeFraction>>,
 
+    //  Matured queued change, required when the timelock is enabled
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            global_state.key().as_ref(),
+            &[PendingChangeData::KIND_PROTOCOL_FEE_FRACTION]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    //  Receives the pending change rent, checked against pending_change.proposer
+    /// CHECK: address checked in consume_pending_change
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+
     //  Needed to init new account
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -145,6 +191,19 @@ impl SetProtocolFeeFraction<'_> {
         protocol_fee_denominator: u64,
     ) -> Result<()> {
         require!(protocol_fee_denominator > 0, MarsError::ZeroDenominator);
+
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.global_state.key(),
+            &PendingChangeData::ProtocolFeeFraction {
+                protocol_fee_numerator,
+                protocol_fee_denominator,
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
         let protocol_fee_fraction = &mut ctx.accounts.protocol_fee_fraction;
         protocol_fee_fraction.numerator = protocol_fee_numerator;
         protocol_fee_fraction.denominator = protocol_fee_denominator;
@@ -196,6 +255,23 @@ pub struct SetInsuranceFeeTiers<'info> {
     )]
     pub insurance_fee_tiers: Box<Account<'info, InsuranceFeeTiers>>,
 
+    //  Matured queued change, required when the timelock is enabled
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            global_state.key().as_ref(),
+            &[PendingChangeData::KIND_INSURANCE_FEE_TIERS]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    //  Receives the pending change rent, checked against pending_change.proposer
+    /// CHECK: address checked in consume_pending_change
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+
     //  Needed to init new account
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -208,6 +284,18 @@ impl SetInsuranceFeeTiers<'_> {
         threshold_amounts: &Vec<u64>,
         insurance_fees: &Vec<u64>,
     ) -> Result<()> {
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.global_state.key(),
+            &PendingChangeData::InsuranceFeeTiers {
+                threshold_amounts: threshold_amounts.clone(),
+                insurance_fees: insurance_fees.clone(),
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
         let threshold_length = threshold_amounts.len();
 
         // Check for empty arrays
@@ -268,3 +356,68 @@ impl SetInsuranceFeeTiers<'_> {
         Ok(())
     }
 }
+
+// ============================================================================
+// Set Target Chain Min Fee - 设置目标链最低费用
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(dest_chain_id: u32, token_in: Pubkey)]
+pub struct SetTargetChainMinFee<'info> {
+    // only admin can add target_chain_min_fees
+    #[account(
+        mut,
+        constraint = global_state.admin == *admin.key @MarsError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    //  Global state
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump,
+        constraint = !global_state.frozen @MarsError::GlobalStateFrozen
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Stores target_chain_min_fee info
+    #[account(
+        init_if_needed,
+        space = TargetChainMinFee::space(),
+        seeds = [TARGET_CHAIN_MIN_FEE_SEED, &dest_chain_id.to_le_bytes(), token_in.as_ref()],
+        bump,
+        payer = admin
+    )]
+    pub target_chain_min_fee: Box<Account<'info, TargetChainMinFee>>,
+
+    //  Needed to init new account
+    pub system_program: Program<'info, System>,
+}
+
+impl SetTargetChainMinFee<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        dest_chain_id: u32,
+        token_in: Pubkey,
+        min_fee: u64,
+    ) -> Result<()> {
+        let target_chain_min_fee = &mut ctx.accounts.target_chain_min_fee;
+        target_chain_min_fee.dest_chain_id = dest_chain_id;
+        target_chain_min_fee.token_in = token_in.to_bytes();
+        target_chain_min_fee.min_fee = min_fee;
+
+        msg!(
+            "SetTargetChainMinFee: {{\
+            \"admin\":\"{:?}\",\
+            \"dest_chain_id\":\"{:?}\",\
+            \"token_in\":\"{:?}\",\
+            \"min_fee\":\"{:?}\"\
+            }}",
+            ctx.accounts.admin.key(),
+            dest_chain_id,
+            token_in,
+            min_fee
+        );
+
+        Ok(())
+    }
+}
@@ -92,6 +92,24 @@ pub struct UpdateGlobalStateParams<'info> {
         constraint = global_state.frozen == false @MarsError::GlobalStateFrozen
     )]
     pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Matured queued fee param change, required when the timelock is enabled
+    //  and cross_chain_fee_bps or deposit_fee_tiers_enabled is updated
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            global_state.key().as_ref(),
+            &[PendingChangeData::KIND_GLOBAL_STATE_PARAMS]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    //  Receives the pending change rent, checked against pending_change.proposer
+    /// CHECK: address checked in consume_pending_change
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
 }
 
 impl UpdateGlobalStateParams<'_> {
@@ -100,7 +118,24 @@ impl UpdateGlobalStateParams<'_> {
         rebalance_threshold: Option<u16>,
         cross_chain_fee_bps: Option<u16>,
         max_order_amount: Option<u64>,
+        deposit_fee_tiers_enabled: Option<bool>,
     ) -> Result<()> {
+        // fee params go through the timelock, the other params apply immediately
+        let fee_change = cross_chain_fee_bps.is_some() || deposit_fee_tiers_enabled.is_some();
+        if fee_change || ctx.accounts.pending_change.is_some() {
+            consume_pending_change(
+                ctx.accounts.global_state.timelock_delay_seconds,
+                ctx.accounts.pending_change.as_deref(),
+                ctx.accounts.proposer.as_deref(),
+                &ctx.accounts.global_state.key(),
+                &PendingChangeData::GlobalStateParams {
+                    cross_chain_fee_bps,
+                    deposit_fee_tiers_enabled,
+                },
+                &ctx.accounts.admin.key(),
+            )?;
+        }
+
         let global_state = &mut ctx.accounts.global_state;
 
         global_state.rebalance_threshold =
@@ -108,12 +143,15 @@ impl UpdateGlobalStateParams<'_> {
         global_state.cross_chain_fee_bps =
             cross_chain_fee_bps.unwrap_or(global_state.cross_chain_fee_bps);
         global_state.max_order_amount = max_order_amount.unwrap_or(global_state.max_order_amount);
+        global_state.deposit_fee_tiers_enabled =
+            deposit_fee_tiers_enabled.unwrap_or(global_state.deposit_fee_tiers_enabled);
 
         msg!(
-            "Updated global state params, new rebalance_threshold: {:?}, new cross_chain_fee_bps: {:?}, new max_order_amount: {:?}",
+            "Updated global state params, new rebalance_threshold: {:?}, new cross_chain_fee_bps: {:?}, new max_order_amount: {:?}, new deposit_fee_tiers_enabled: {:?}",
             global_state.rebalance_threshold,
             global_state.cross_chain_fee_bps,
             global_state.max_order_amount,
+            global_state.deposit_fee_tiers_enabled,
         );
 
         msg!(
@@ -121,12 +159,14 @@ impl UpdateGlobalStateParams<'_> {
             \"admin\":\"{:?}\",\
             \"rebalance_threshold\":\"{:?}\",\
             \"cross_chain_fee_bps\":\"{:?}\",\
-            \"max_order_amount\":\"{:?}\"\
+            \"max_order_amount\":\"{:?}\",\
+            \"deposit_fee_tiers_enabled\":\"{:?}\"\
             }}",
             ctx.accounts.admin.key(),
             global_state.rebalance_threshold,
             global_state.cross_chain_fee_bps,
             global_state.max_order_amount,
+            global_state.deposit_fee_tiers_enabled,
         );
         Ok(())
     }
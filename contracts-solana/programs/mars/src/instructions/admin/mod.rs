@@ -1,7 +1,13 @@
 pub mod authority;
 pub mod global_state;
 pub mod fee_config;
+pub mod timelock;
+pub mod roles;
+pub mod asset_fees;
 
 pub use authority::*;
 pub use global_state::*;
 pub use fee_config::*;
+pub use timelock::*;
+pub use roles::*;
+pub use asset_fees::*;
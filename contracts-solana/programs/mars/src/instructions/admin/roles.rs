@@ -0,0 +1,114 @@
+use crate::events::{RoleGranted, RoleRevoked};
+use crate::*;
+
+// ============================================================================
+// Grant/Revoke Role - 管理 keeper / fee manager / guardian / curator 角色
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    // only admin can grant roles
+    #[account(
+        mut,
+        constraint = global_state.admin == *admin.key @MarsError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    //  Global state
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Stores role members
+    #[account(
+        init_if_needed,
+        space = RoleRegistry::space(),
+        seeds = [ROLE_REGISTRY_SEED],
+        bump,
+        payer = admin
+    )]
+    pub role_registry: Box<Account<'info, RoleRegistry>>,
+
+    //  Needed to init new account
+    pub system_program: Program<'info, System>,
+}
+
+impl GrantRole<'_> {
+    pub fn process_instruction(ctx: Context<Self>, role: Role, account: Pubkey) -> Result<()> {
+        let role_registry = &mut ctx.accounts.role_registry;
+        role_registry.bump = ctx.bumps.role_registry;
+        role_registry.grant(role, account)?;
+
+        msg!(
+            "GrantRole: {{\
+            \"admin\":\"{:?}\",\
+            \"role\":\"{:?}\",\
+            \"account\":\"{:?}\"\
+            }}",
+            ctx.accounts.admin.key(),
+            role,
+            account
+        );
+
+        emit!(RoleGranted {
+            role,
+            account,
+            granted_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    // only admin can revoke roles
+    #[account(
+        constraint = global_state.admin == *admin.key @MarsError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    //  Global state
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Stores role members
+    #[account(
+        mut,
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump,
+    )]
+    pub role_registry: Box<Account<'info, RoleRegistry>>,
+}
+
+impl RevokeRole<'_> {
+    pub fn process_instruction(ctx: Context<Self>, role: Role, account: Pubkey) -> Result<()> {
+        ctx.accounts.role_registry.revoke(role, &account)?;
+
+        msg!(
+            "RevokeRole: {{\
+            \"admin\":\"{:?}\",\
+            \"role\":\"{:?}\",\
+            \"account\":\"{:?}\"\
+            }}",
+            ctx.accounts.admin.key(),
+            role,
+            account
+        );
+
+        emit!(RoleRevoked {
+            role,
+            account,
+            revoked_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
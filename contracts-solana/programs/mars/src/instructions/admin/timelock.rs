@@ -0,0 +1,345 @@
+use crate::events::{PendingChangeCancelled, PendingChangeExecuted, PendingChangeQueued};
+use crate::*;
+
+// ============================================================================
+// Queue Change - 排队 timelock 变更
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(target: Pubkey, change: PendingChangeData)]
+pub struct QueueChange<'info> {
+    //  Vault admin (or fee manager for fee changes) for vault changes, global admin for global changes
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    //  Stores timelock delay
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Required for vault changes
+    pub vault_state: Option<Box<Account<'info, VaultState>>>,
+
+    //  One pending change per target and kind
+    #[account(
+        init,
+        space = PendingChange::space(),
+        seeds = [PENDING_CHANGE_SEED, target.as_ref(), &[change.kind()]],
+        bump,
+        payer = proposer
+    )]
+    pub pending_change: Box<Account<'info, PendingChange>>,
+
+    //  Needed to init new account
+    pub system_program: Program<'info, System>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl QueueChange<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        target: Pubkey,
+        change: PendingChangeData,
+    ) -> Result<()> {
+        let proposer = ctx.accounts.proposer.key();
+
+        if change.targets_vault() {
+            let vault_state =
+                ctx.accounts.vault_state.as_ref().ok_or(MarsError::InvalidVaultState)?;
+            require_keys_eq!(vault_state.key(), target, MarsError::PendingChangeMismatch);
+            let fee_change = matches!(
+                change,
+                PendingChangeData::VaultFeeConfig { .. }
+                    | PendingChangeData::VaultPlatformFee { .. }
+            );
+            let fee_manager = fee_change
+                && ctx
+                    .accounts
+                    .role_registry
+                    .as_ref()
+                    .is_some_and(|r| r.has_role(Role::FeeManager, &proposer));
+            require!(vault_state.admin == proposer || fee_manager, MarsError::InvalidAdmin);
+            // fee manager can only queue fee rates, fee recipient changes need the vault admin
+            if let PendingChangeData::VaultFeeConfig {
+                fee_recipient: Some(_),
+                ..
+            } = change
+            {
+                require_keys_eq!(vault_state.admin, proposer, MarsError::OnlyAdmin);
+            }
+        } else {
+            require_keys_eq!(
+                ctx.accounts.global_state.key(),
+                target,
+                MarsError::PendingChangeMismatch
+            );
+            require_keys_eq!(ctx.accounts.global_state.admin, proposer, MarsError::InvalidAdmin);
+        }
+
+        match &change {
+            PendingChangeData::VaultPlatformFee {
+                platform_fee_bps,
+            } => {
+                require!(*platform_fee_bps <= 10_000, MarsError::InvalidParameter)
+            }
+            PendingChangeData::AddProtocol {
+                params,
+                ..
+            } => {
+                require!(
+                    params.len() <= ProtocolConfig::MAX_PARAMS_LEN,
+                    MarsError::InvalidParameter
+                )
+            }
+            PendingChangeData::TimelockDelay {
+                delay_seconds,
+            } => {
+                require!(*delay_seconds <= MAX_TIMELOCK_DELAY_SECONDS, MarsError::InvalidParameter)
+            }
+            PendingChangeData::FeeTiers {
+                threshold_amounts,
+                bps_fees,
+            } => {
+                require!(
+                    threshold_amounts.len() <= MAX_FEE_TIERS_LENGTH
+                        && bps_fees.len() <= MAX_FEE_TIERS_LENGTH,
+                    MarsError::FeeTiersLengthExceeded
+                )
+            }
+            PendingChangeData::InsuranceFeeTiers {
+                threshold_amounts,
+                insurance_fees,
+            } => {
+                require!(
+                    threshold_amounts.len() <= MAX_FEE_TIERS_LENGTH
+                        && insurance_fees.len() <= MAX_FEE_TIERS_LENGTH,
+                    MarsError::InsuranceFeeTiersLengthExceeded
+                )
+            }
+            PendingChangeData::TargetAllocations {
+                allocations,
+            } => {
+                require!(
+                    allocations.len() <= MAX_PROTOCOLS_PER_VAULT,
+                    MarsError::InvalidParameter
+                )
+            }
+            _ => {}
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let delay = i64::try_from(ctx.accounts.global_state.timelock_delay_seconds)
+            .map_err(|_| MarsError::MathOverflow)?;
+        let eta = now.checked_add(delay).ok_or(MarsError::MathOverflow)?;
+        let kind = change.kind();
+
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.target = target;
+        pending_change.kind = kind;
+        pending_change.change = change.clone();
+        pending_change.proposer = proposer;
+        pending_change.queued_at = now;
+        pending_change.eta = eta;
+        pending_change.bump = ctx.bumps.pending_change;
+
+        msg!("⏳ Change {} queued for {}, executable at {}", kind, target, eta);
+
+        emit!(PendingChangeQueued {
+            pending_change: pending_change.key(),
+            target,
+            kind,
+            change,
+            proposer,
+            eta,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Cancel Change - 取消 timelock 变更
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    //  Guardian (freeze authority or guardian role) or the proposer
+    pub signer: Signer<'info>,
+
+    //  Stores GlobalStateAuthority info
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED],
+        bump,
+    )]
+    pub global_state_authority: Box<Account<'info, GlobalStateAuthority>>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_CHANGE_SEED, pending_change.target.as_ref(), &[pending_change.kind]],
+        bump = pending_change.bump,
+        close = proposer
+    )]
+    pub pending_change: Box<Account<'info, PendingChange>>,
+
+    //  Receives the rent back
+    /// CHECK: address checked against pending_change.proposer
+    #[account(mut, address = pending_change.proposer @ MarsError::InvalidOwner)]
+    pub proposer: AccountInfo<'info>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl CancelChange<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        let pending_change = &ctx.accounts.pending_change;
+        require!(
+            signer == pending_change.proposer
+                || ctx.accounts.global_state_authority.freeze_authority.contains(&signer)
+                || ctx
+                    .accounts
+                    .role_registry
+                    .as_ref()
+                    .is_some_and(|r| r.has_role(Role::Guardian, &signer)),
+            MarsError::InvalidAuthority
+        );
+
+        msg!(
+            "🛑 Change {} for {} cancelled by {}",
+            pending_change.kind,
+            pending_change.target,
+            signer
+        );
+
+        emit!(PendingChangeCancelled {
+            pending_change: pending_change.key(),
+            target: pending_change.target,
+            kind: pending_change.kind,
+            cancelled_by: signer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Set Timelock Delay - 设置 timelock 延迟
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct SetTimelockDelay<'info> {
+    #[account(
+        mut,
+        constraint = global_state.admin == *admin.key @MarsError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    //  Stores timelock delay
+    #[account(
+        mut,
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Required when shortening the delay
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            global_state.key().as_ref(),
+            &[PendingChangeData::KIND_TIMELOCK_DELAY]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    //  Receives the pending change rent, checked against pending_change.proposer
+    /// CHECK: address checked in consume_pending_change
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+}
+
+impl SetTimelockDelay<'_> {
+    /// 延长延迟立即生效；缩短延迟需经过当前延迟的 timelock
+    pub fn process_instruction(ctx: Context<Self>, delay_seconds: u64) -> Result<()> {
+        require!(delay_seconds <= MAX_TIMELOCK_DELAY_SECONDS, MarsError::InvalidParameter);
+
+        let old_delay = ctx.accounts.global_state.timelock_delay_seconds;
+        if delay_seconds < old_delay || ctx.accounts.pending_change.is_some() {
+            consume_pending_change(
+                old_delay,
+                ctx.accounts.pending_change.as_deref(),
+                ctx.accounts.proposer.as_deref(),
+                &ctx.accounts.global_state.key(),
+                &PendingChangeData::TimelockDelay {
+                    delay_seconds,
+                },
+                &ctx.accounts.admin.key(),
+            )?;
+        }
+
+        ctx.accounts.global_state.timelock_delay_seconds = delay_seconds;
+
+        msg!(
+            "SetTimelockDelay: {{\
+            \"admin\":\"{:?}\",\
+            \"old_delay\":\"{:?}\",\
+            \"new_delay\":\"{:?}\"\
+            }}",
+            ctx.accounts.admin.key(),
+            old_delay,
+            delay_seconds
+        );
+
+        Ok(())
+    }
+}
+
+/// 执行受 timelock 保护的变更前调用：校验待执行项并发出执行事件
+///
+/// 校验通过后关闭待执行项，租金退还给排队者（proposer 账户必须与记录一致）
+pub fn consume_pending_change<'info>(
+    delay_seconds: u64,
+    pending_change: Option<&Account<'info, PendingChange>>,
+    proposer: Option<&AccountInfo<'info>>,
+    target: &Pubkey,
+    expected: &PendingChangeData,
+    executor: &Pubkey,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    PendingChange::authorize(delay_seconds, pending_change.map(|p| &**p), target, expected, now)?;
+
+    if let Some(pending_change) = pending_change {
+        let proposer = proposer.ok_or(MarsError::InvalidOwner)?;
+        require_keys_eq!(proposer.key(), pending_change.proposer, MarsError::InvalidOwner);
+
+        msg!("✅ Executing queued change {} for {}", pending_change.kind, target);
+        emit!(PendingChangeExecuted {
+            pending_change: pending_change.key(),
+            target: *target,
+            kind: pending_change.kind,
+            executor: *executor,
+            timestamp: now,
+        });
+
+        pending_change.close(proposer.clone())?;
+    }
+
+    Ok(())
+}
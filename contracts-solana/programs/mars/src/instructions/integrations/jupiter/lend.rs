@@ -56,6 +56,13 @@ pub struct JupiterLendDepositCPI<'info> {
     /// 9. jupiterLendProgram - Jupiter Lend 程序
     /// CHECK: Jupiter Lend 程序 ID
     pub jupiter_lend_program: AccountInfo<'info>,
+
+    /// 全局状态（单笔存款不能超过 max_order_amount）
+    #[account(
+        seeds = [crate::constants::GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, crate::state::GlobalState>>,
 }
 
 /// Jupiter Lend 取款 CPI 调用所需的账户
@@ -112,23 +119,71 @@ pub struct JupiterLendWithdrawCPI<'info> {
     pub jupiter_lend_program: AccountInfo<'info>,
 }
 
+/// Jupiter Lend 存款 CPI 所需的账户（可由 PDA 作为 signer 签名）
+pub struct JupiterLendDepositAccounts<'info> {
+    pub signer: AccountInfo<'info>,
+    pub depositor_token_account: AccountInfo<'info>,
+    pub recipient_token_account: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub lending_admin: AccountInfo<'info>,
+    pub lending: AccountInfo<'info>,
+    pub f_token_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub jupiter_lend_program: AccountInfo<'info>,
+}
+
+impl<'info> JupiterLendDepositCPI<'info> {
+    pub fn to_cpi_accounts(&self) -> JupiterLendDepositAccounts<'info> {
+        JupiterLendDepositAccounts {
+            signer: self.signer.to_account_info(),
+            depositor_token_account: self.depositor_token_account.to_account_info(),
+            recipient_token_account: self.recipient_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            lending_admin: self.lending_admin.to_account_info(),
+            lending: self.lending.to_account_info(),
+            f_token_mint: self.f_token_mint.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            jupiter_lend_program: self.jupiter_lend_program.to_account_info(),
+        }
+    }
+}
+
 /// CPI 调用 Jupiter Lend 进行存款
 ///
 /// 参数:
 /// - amount: 存款金额（基础单位）
 ///
 /// 示例:
-/// ```rust
+/// ```ignore
 /// // 存入 1 USDC (1_000_000 基础单位)
 /// jupiter_lend_deposit_cpi(ctx, 1_000_000)?;
 /// ```
 pub fn jupiter_lend_deposit_cpi<'info>(
     ctx: Context<'_, '_, '_, 'info, JupiterLendDepositCPI<'info>>,
     amount: u64,
+) -> Result<()> {
+    require!(
+        amount <= ctx.accounts.global_state.max_order_amount,
+        crate::error::CustomError::MaxOrderAmountExceeded
+    );
+    invoke_jupiter_lend_deposit(
+        &ctx.accounts.to_cpi_accounts(),
+        ctx.remaining_accounts,
+        amount,
+        &[],
+    )
+}
+
+/// 执行 Jupiter Lend 存款 CPI，signer_seeds 为空时由 signer 直接签名
+pub fn invoke_jupiter_lend_deposit<'info>(
+    accounts: &JupiterLendDepositAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     // 验证 Jupiter Lend 程序 ID
     require_eq!(
-        ctx.accounts.jupiter_lend_program.key(),
+        accounts.jupiter_lend_program.key(),
         JUPITER_LEND_PROGRAM_ID,
         JupiterLendCPIError::InvalidJupiterLendProgram
     );
@@ -139,23 +194,23 @@ pub fn jupiter_lend_deposit_cpi<'info>(
     // 构建账户数组（严格按照 Jupiter Lend 指令顺序）
     let mut account_metas = vec![
         // 1. signer
-        AccountMeta::new(ctx.accounts.signer.key(), true),
+        AccountMeta::new(accounts.signer.key(), true),
         // 2. depositorTokenAccount
-        AccountMeta::new(ctx.accounts.depositor_token_account.key(), false),
+        AccountMeta::new(accounts.depositor_token_account.key(), false),
         // 3. recipientTokenAccount
-        AccountMeta::new(ctx.accounts.recipient_token_account.key(), false),
+        AccountMeta::new(accounts.recipient_token_account.key(), false),
         // 4. mint
-        AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+        AccountMeta::new_readonly(accounts.mint.key(), false),
         // 5. lendingAdmin
-        AccountMeta::new_readonly(ctx.accounts.lending_admin.key(), false),
+        AccountMeta::new_readonly(accounts.lending_admin.key(), false),
         // 6. lending
-        AccountMeta::new(ctx.accounts.lending.key(), false),
+        AccountMeta::new(accounts.lending.key(), false),
         // 7. fTokenMint
-        AccountMeta::new(ctx.accounts.f_token_mint.key(), false),
+        AccountMeta::new(accounts.f_token_mint.key(), false),
     ];
     
     // 8-17. 添加 remaining_accounts (从 SDK 获取的其他必需账户)
-    for acc in ctx.remaining_accounts.iter() {
+    for acc in remaining_accounts.iter() {
         account_metas.push(AccountMeta {
             pubkey: *acc.key,
             is_signer: acc.is_signer,
@@ -179,42 +234,108 @@ pub fn jupiter_lend_deposit_cpi<'info>(
 
     // 构建 account_infos
     let mut account_infos = vec![
-        ctx.accounts.signer.to_account_info(),
-        ctx.accounts.depositor_token_account.to_account_info(),
-        ctx.accounts.recipient_token_account.to_account_info(),
-        ctx.accounts.mint.to_account_info(),
-        ctx.accounts.lending_admin.to_account_info(),
-        ctx.accounts.lending.to_account_info(),
-        ctx.accounts.f_token_mint.to_account_info(),
+        accounts.signer.clone(),
+        accounts.depositor_token_account.clone(),
+        accounts.recipient_token_account.clone(),
+        accounts.mint.clone(),
+        accounts.lending_admin.clone(),
+        accounts.lending.clone(),
+        accounts.f_token_mint.clone(),
     ];
     
     // 添加 remaining_accounts
-    for acc in ctx.remaining_accounts.iter() {
-        account_infos.push(acc.to_account_info());
-    }
+    account_infos.extend_from_slice(remaining_accounts);
 
     // 执行 CPI 调用
-    anchor_lang::solana_program::program::invoke(&ix, &account_infos)?;
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, signer_seeds)?;
     Ok(())
 }
 
+/// Jupiter Lend 取款 CPI 所需的账户（可由 PDA 作为 signer 签名）
+pub struct JupiterLendWithdrawAccounts<'info> {
+    pub signer: AccountInfo<'info>,
+    pub recipient_token_account: AccountInfo<'info>,
+    pub depositor_token_account: AccountInfo<'info>,
+    pub lending_admin: AccountInfo<'info>,
+    pub lending: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub f_token_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub jupiter_lend_program: AccountInfo<'info>,
+}
+
+impl<'info> JupiterLendWithdrawCPI<'info> {
+    pub fn to_cpi_accounts(&self) -> JupiterLendWithdrawAccounts<'info> {
+        JupiterLendWithdrawAccounts {
+            signer: self.signer.to_account_info(),
+            recipient_token_account: self.recipient_token_account.to_account_info(),
+            depositor_token_account: self.depositor_token_account.to_account_info(),
+            lending_admin: self.lending_admin.to_account_info(),
+            lending: self.lending.to_account_info(),
+            mint: self.mint.to_account_info(),
+            f_token_mint: self.f_token_mint.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            jupiter_lend_program: self.jupiter_lend_program.to_account_info(),
+        }
+    }
+}
+
 /// CPI 调用 Jupiter Lend 进行取款
 ///
 /// 参数:
 /// - amount: 取款金额（基础单位）
 ///
 /// 示例:
-/// ```rust
+/// ```ignore
 /// // 取出 1 USDC (1_000_000 基础单位)
 /// jupiter_lend_withdraw_cpi(ctx, 1_000_000)?;
 /// ```
 pub fn jupiter_lend_withdraw_cpi<'info>(
     ctx: Context<'_, '_, '_, 'info, JupiterLendWithdrawCPI<'info>>,
     amount: u64,
+) -> Result<()> {
+    invoke_jupiter_lend_withdraw(
+        &ctx.accounts.to_cpi_accounts(),
+        ctx.remaining_accounts,
+        amount,
+        &[],
+    )
+}
+
+/// 执行 Jupiter Lend 取款 CPI，signer_seeds 为空时由 signer 直接签名
+pub fn invoke_jupiter_lend_withdraw<'info>(
+    accounts: &JupiterLendWithdrawAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // Jupiter Lend withdraw 指令的 discriminator（按底层代币数量取款）
+    let discriminator = [0xb7, 0x12, 0x46, 0x9c, 0x94, 0x6d, 0xa1, 0x22];
+    invoke_jupiter_lend_withdraw_ix(accounts, remaining_accounts, discriminator, amount, signer_seeds)
+}
+
+/// 执行 Jupiter Lend 赎回 CPI：按 jlToken 数量赎回，用于全部撤出（包括账面之外的收益）
+pub fn invoke_jupiter_lend_redeem<'info>(
+    accounts: &JupiterLendWithdrawAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    f_token_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // Jupiter Lend redeem 指令的 discriminator（与 withdraw 使用相同的账户顺序）
+    let discriminator = [0xb8, 0x0c, 0x56, 0x95, 0x46, 0xc4, 0x61, 0xe1];
+    invoke_jupiter_lend_withdraw_ix(accounts, remaining_accounts, discriminator, f_token_amount, signer_seeds)
+}
+
+fn invoke_jupiter_lend_withdraw_ix<'info>(
+    accounts: &JupiterLendWithdrawAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    discriminator: [u8; 8],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     // 验证 Jupiter Lend 程序 ID
     require_eq!(
-        ctx.accounts.jupiter_lend_program.key(),
+        accounts.jupiter_lend_program.key(),
         JUPITER_LEND_PROGRAM_ID,
         JupiterLendCPIError::InvalidJupiterLendProgram
     );
@@ -226,23 +347,23 @@ pub fn jupiter_lend_withdraw_cpi<'info>(
     // 注意：取款的顺序与存款不同
     let mut account_metas = vec![
         // 1. signer
-        AccountMeta::new(ctx.accounts.signer.key(), true),
+        AccountMeta::new(accounts.signer.key(), true),
         // 2. recipientTokenAccount (销毁 jlToken)
-        AccountMeta::new(ctx.accounts.recipient_token_account.key(), false),
+        AccountMeta::new(accounts.recipient_token_account.key(), false),
         // 3. depositorTokenAccount (接收代币)
-        AccountMeta::new(ctx.accounts.depositor_token_account.key(), false),
+        AccountMeta::new(accounts.depositor_token_account.key(), false),
         // 4. lendingAdmin
-        AccountMeta::new_readonly(ctx.accounts.lending_admin.key(), false),
+        AccountMeta::new_readonly(accounts.lending_admin.key(), false),
         // 5. lending
-        AccountMeta::new(ctx.accounts.lending.key(), false),
+        AccountMeta::new(accounts.lending.key(), false),
         // 6. mint
-        AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+        AccountMeta::new_readonly(accounts.mint.key(), false),
         // 7. fTokenMint
-        AccountMeta::new(ctx.accounts.f_token_mint.key(), false),
+        AccountMeta::new(accounts.f_token_mint.key(), false),
     ];
     
     // 8-18. 添加 remaining_accounts
-    for acc in ctx.remaining_accounts.iter() {
+    for acc in remaining_accounts.iter() {
         account_metas.push(AccountMeta {
             pubkey: *acc.key,
             is_signer: acc.is_signer,
@@ -250,10 +371,9 @@ pub fn jupiter_lend_withdraw_cpi<'info>(
         });
     }
 
-    // 构建指令数据
+    // 构建指令数据：discriminator (8 bytes) + amount (8 bytes)
     let mut data = Vec::with_capacity(16);
-    // Jupiter Lend withdraw 指令的 discriminator
-    data.extend_from_slice(&[0xb7, 0x12, 0x46, 0x9c, 0x94, 0x6d, 0xa1, 0x22]); // withdraw discriminator
+    data.extend_from_slice(&discriminator);
     data.extend_from_slice(&amount.to_le_bytes());
 
     let ix = anchor_lang::solana_program::instruction::Instruction {
@@ -264,21 +384,19 @@ pub fn jupiter_lend_withdraw_cpi<'info>(
 
     // 构建 account_infos
     let mut account_infos = vec![
-        ctx.accounts.signer.to_account_info(),
-        ctx.accounts.recipient_token_account.to_account_info(),
-        ctx.accounts.depositor_token_account.to_account_info(),
-        ctx.accounts.lending_admin.to_account_info(),
-        ctx.accounts.lending.to_account_info(),
-        ctx.accounts.mint.to_account_info(),
-        ctx.accounts.f_token_mint.to_account_info(),
+        accounts.signer.clone(),
+        accounts.recipient_token_account.clone(),
+        accounts.depositor_token_account.clone(),
+        accounts.lending_admin.clone(),
+        accounts.lending.clone(),
+        accounts.mint.clone(),
+        accounts.f_token_mint.clone(),
     ];
     
     // 添加 remaining_accounts
-    for acc in ctx.remaining_accounts.iter() {
-        account_infos.push(acc.to_account_info());
-    }
+    account_infos.extend_from_slice(remaining_accounts);
 
-    anchor_lang::solana_program::program::invoke(&ix, &account_infos)?;
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, signer_seeds)?;
     Ok(())
 }
 
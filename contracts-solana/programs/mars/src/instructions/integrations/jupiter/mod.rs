@@ -1,2 +1,4 @@
 pub mod lend;
 pub use lend::*;
+pub mod swap;
+pub use swap::*;
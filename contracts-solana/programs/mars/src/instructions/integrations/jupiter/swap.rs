@@ -0,0 +1,62 @@
+use crate::constants::protocols::jupiter::DEX_PROGRAM_ID as JUPITER_DEX_PROGRAM_ID;
+use crate::error::CustomError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+/// 执行 Jupiter 聚合器兑换 CPI
+///
+/// route_data 和 route_accounts 直接来自 Jupiter Swap API（`route` / `shared_accounts_route`），
+/// 本程序不解析路由，只透传。兑换结果由调用方通过 CPI 前后的余额差验证。
+/// 不附带 PDA 签名，路由只能动用外层交易签名者授权的资金。
+pub fn invoke_jupiter_swap<'info>(
+    jupiter_program: &AccountInfo<'info>,
+    route_accounts: &[AccountInfo<'info>],
+    route_data: Vec<u8>,
+) -> Result<()> {
+    require_keys_eq!(jupiter_program.key(), JUPITER_DEX_PROGRAM_ID, CustomError::ProgramMismatch);
+    require!(!route_data.is_empty(), CustomError::InvalidSwapRoute);
+
+    let account_metas = route_accounts
+        .iter()
+        .map(|acc| AccountMeta {
+            pubkey: *acc.key,
+            is_signer: acc.is_signer,
+            is_writable: acc.is_writable,
+        })
+        .collect();
+
+    let ix = Instruction {
+        program_id: JUPITER_DEX_PROGRAM_ID,
+        accounts: account_metas,
+        data: route_data,
+    };
+
+    let mut account_infos = route_accounts.to_vec();
+    account_infos.push(jupiter_program.clone());
+
+    anchor_lang::solana_program::program::invoke(&ix, &account_infos)?;
+    Ok(())
+}
+
+/// 实际到账相对报价的差额（bps），到账不低于报价时为 0
+pub fn price_impact_bps(quoted_out: u64, amount_out: u64) -> u16 {
+    if quoted_out == 0 || amount_out >= quoted_out {
+        return 0;
+    }
+    let impact = (quoted_out - amount_out) as u128 * 10_000 / quoted_out as u128;
+    impact.min(10_000) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_impact_bps() {
+        assert_eq!(price_impact_bps(1_000_000, 1_000_000), 0);
+        assert_eq!(price_impact_bps(1_000_000, 1_050_000), 0);
+        assert_eq!(price_impact_bps(1_000_000, 990_000), 100);
+        assert_eq!(price_impact_bps(1_000_000, 0), 10_000);
+        assert_eq!(price_impact_bps(0, 500), 0);
+    }
+}
@@ -241,6 +241,13 @@ pub struct KaminoDepositAndStake<'info> {
     /// 18. farmTokenProgram - Farm 使用的 Token 程序（通常是普通 Token Program）
     /// CHECK: Token Program for Farm
     pub farm_token_program: AccountInfo<'info>,
+
+    /// 全局状态（单笔存款不能超过 max_order_amount）
+    #[account(
+        seeds = [crate::constants::GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, crate::state::GlobalState>>,
 }
 
 /// 存款并自动质押到 Farm（组合操作）
@@ -252,6 +259,10 @@ pub fn handler_kamino_deposit_and_stake<'info>(
 ) -> Result<()> {
     use crate::constants::protocols::kamino::PROGRAM_ID as KAMINO_PROGRAM_ID;
     use anchor_lang::solana_program::instruction::AccountMeta;
+    require!(
+        max_amount <= ctx.accounts.global_state.max_order_amount,
+        crate::error::CustomError::MaxOrderAmountExceeded
+    );
     // ===== 第一步：存款到 Kamino Vault =====
     // 构建存款账户数组
     let mut deposit_accounts = vec![
@@ -64,6 +64,13 @@ pub struct KaminoDepositCPI<'info> {
     /// 13. program - Kamino Vault程序自身
     /// CHECK: 这是Kamino Vault程序ID
     pub kamino_vault_program: AccountInfo<'info>,
+
+    /// 全局状态（单笔存款不能超过 max_order_amount）
+    #[account(
+        seeds = [crate::constants::GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, crate::state::GlobalState>>,
 }
 
 /// Kamino提取CPI调用所需的账户（简化版本 - withdrawFromAvailable）
@@ -129,6 +136,43 @@ pub struct KaminoWithdrawCPI<'info> {
     pub kamino_vault_program: AccountInfo<'info>,
 }
 
+/// Kamino 存款 CPI 所需的账户（可由 PDA 作为 user 签名）
+pub struct KaminoDepositAccounts<'info> {
+    pub user: AccountInfo<'info>,
+    pub vault_state: AccountInfo<'info>,
+    pub token_vault: AccountInfo<'info>,
+    pub token_mint: AccountInfo<'info>,
+    pub base_vault_authority: AccountInfo<'info>,
+    pub shares_mint: AccountInfo<'info>,
+    pub user_token_ata: AccountInfo<'info>,
+    pub user_shares_ata: AccountInfo<'info>,
+    pub klend_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub shares_token_program: AccountInfo<'info>,
+    pub event_authority: AccountInfo<'info>,
+    pub kamino_vault_program: AccountInfo<'info>,
+}
+
+impl<'info> KaminoDepositCPI<'info> {
+    pub fn to_cpi_accounts(&self) -> KaminoDepositAccounts<'info> {
+        KaminoDepositAccounts {
+            user: self.user.to_account_info(),
+            vault_state: self.vault_state.to_account_info(),
+            token_vault: self.token_vault.to_account_info(),
+            token_mint: self.token_mint.to_account_info(),
+            base_vault_authority: self.base_vault_authority.to_account_info(),
+            shares_mint: self.shares_mint.to_account_info(),
+            user_token_ata: self.user_token_ata.to_account_info(),
+            user_shares_ata: self.user_shares_ata.to_account_info(),
+            klend_program: self.klend_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            shares_token_program: self.shares_token_program.to_account_info(),
+            event_authority: self.event_authority.to_account_info(),
+            kamino_vault_program: self.kamino_vault_program.to_account_info(),
+        }
+    }
+}
+
 /// CPI调用Kamino进行存款（完整实现）
 ///
 /// remaining_accounts 应该包含 vault 的 reserves 和对应的 lending markets:
@@ -140,10 +184,24 @@ pub struct KaminoWithdrawCPI<'info> {
 pub fn kamino_deposit_cpi<'info>(
     ctx: Context<'_, '_, '_, 'info, KaminoDepositCPI<'info>>,
     max_amount: u64,
+) -> Result<()> {
+    require!(
+        max_amount <= ctx.accounts.global_state.max_order_amount,
+        crate::error::CustomError::MaxOrderAmountExceeded
+    );
+    invoke_kamino_deposit(&ctx.accounts.to_cpi_accounts(), ctx.remaining_accounts, max_amount, &[])
+}
+
+/// 执行 Kamino 存款 CPI，signer_seeds 为空时由 user 直接签名
+pub fn invoke_kamino_deposit<'info>(
+    accounts: &KaminoDepositAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    max_amount: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     // 验证Kamino程序ID
     require_eq!(
-        ctx.accounts.kamino_vault_program.key(),
+        accounts.kamino_vault_program.key(),
         KAMINO_PROGRAM_ID,
         KaminoCPIError::InvalidKaminoProgram
     );
@@ -151,37 +209,37 @@ pub fn kamino_deposit_cpi<'info>(
     // 构建账户数组（严格按照Kamino IDL顺序）
     let mut account_metas = vec![
         // 1. user
-        AccountMeta::new(ctx.accounts.user.key(), true),
+        AccountMeta::new(accounts.user.key(), true),
         // 2. vaultState
-        AccountMeta::new(ctx.accounts.vault_state.key(), false),
+        AccountMeta::new(accounts.vault_state.key(), false),
         // 3. tokenVault
-        AccountMeta::new(ctx.accounts.token_vault.key(), false),
+        AccountMeta::new(accounts.token_vault.key(), false),
         // 4. tokenMint
-        AccountMeta::new_readonly(ctx.accounts.token_mint.key(), false),
+        AccountMeta::new_readonly(accounts.token_mint.key(), false),
         // 5. baseVaultAuthority
-        AccountMeta::new_readonly(ctx.accounts.base_vault_authority.key(), false),
+        AccountMeta::new_readonly(accounts.base_vault_authority.key(), false),
         // 6. sharesMint
-        AccountMeta::new(ctx.accounts.shares_mint.key(), false),
+        AccountMeta::new(accounts.shares_mint.key(), false),
         // 7. userTokenAta
-        AccountMeta::new(ctx.accounts.user_token_ata.key(), false),
+        AccountMeta::new(accounts.user_token_ata.key(), false),
         // 8. userSharesAta
-        AccountMeta::new(ctx.accounts.user_shares_ata.key(), false),
+        AccountMeta::new(accounts.user_shares_ata.key(), false),
         // 9. klendProgram
-        AccountMeta::new_readonly(ctx.accounts.klend_program.key(), false),
+        AccountMeta::new_readonly(accounts.klend_program.key(), false),
         // 10. tokenProgram
-        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
         // 11. sharesTokenProgram
-        AccountMeta::new_readonly(ctx.accounts.shares_token_program.key(), false),
+        AccountMeta::new_readonly(accounts.shares_token_program.key(), false),
         // 12. eventAuthority
-        AccountMeta::new_readonly(ctx.accounts.event_authority.key(), false),
+        AccountMeta::new_readonly(accounts.event_authority.key(), false),
         // 13. program
-        AccountMeta::new_readonly(ctx.accounts.kamino_vault_program.key(), false),
+        AccountMeta::new_readonly(accounts.kamino_vault_program.key(), false),
     ];
 
     // Add remaining_accounts (reserves + lending markets)
     // Format: [reserve (writable), lending_market (readonly), ...]
-    msg!("📋 Adding {} remaining accounts", ctx.remaining_accounts.len());
-    for (i, account) in ctx.remaining_accounts.iter().enumerate() {
+    msg!("📋 Adding {} remaining accounts", remaining_accounts.len());
+    for (i, account) in remaining_accounts.iter().enumerate() {
         // Even indices are reserves (writable), odd indices are lending markets (readonly)
         let is_writable = i % 2 == 0;
         if is_writable {
@@ -210,31 +268,66 @@ pub fn kamino_deposit_cpi<'info>(
 
     // 构建account_infos (包含 remaining_accounts)
     let mut account_infos = vec![
-        ctx.accounts.user.to_account_info(),
-        ctx.accounts.vault_state.to_account_info(),
-        ctx.accounts.token_vault.to_account_info(),
-        ctx.accounts.token_mint.to_account_info(),
-        ctx.accounts.base_vault_authority.to_account_info(),
-        ctx.accounts.shares_mint.to_account_info(),
-        ctx.accounts.user_token_ata.to_account_info(),
-        ctx.accounts.user_shares_ata.to_account_info(),
-        ctx.accounts.klend_program.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.shares_token_program.to_account_info(),
-        ctx.accounts.event_authority.to_account_info(),
-        ctx.accounts.kamino_vault_program.to_account_info(),
+        accounts.user.clone(),
+        accounts.vault_state.clone(),
+        accounts.token_vault.clone(),
+        accounts.token_mint.clone(),
+        accounts.base_vault_authority.clone(),
+        accounts.shares_mint.clone(),
+        accounts.user_token_ata.clone(),
+        accounts.user_shares_ata.clone(),
+        accounts.klend_program.clone(),
+        accounts.token_program.clone(),
+        accounts.shares_token_program.clone(),
+        accounts.event_authority.clone(),
+        accounts.kamino_vault_program.clone(),
     ];
 
     // 添加 remaining_accounts 到 account_infos
-    for account in ctx.remaining_accounts.iter() {
-        account_infos.push(account.to_account_info());
-    }
+    account_infos.extend_from_slice(remaining_accounts);
 
     // 执行CPI调用
-    anchor_lang::solana_program::program::invoke(&ix, &account_infos)?;
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, signer_seeds)?;
     Ok(())
 }
 
+/// Kamino 提取 CPI 所需的账户（可由 PDA 作为 user 签名）
+pub struct KaminoWithdrawAccounts<'info> {
+    pub user: AccountInfo<'info>,
+    pub vault_state: AccountInfo<'info>,
+    pub token_vault: AccountInfo<'info>,
+    pub base_vault_authority: AccountInfo<'info>,
+    pub user_token_ata: AccountInfo<'info>,
+    pub token_mint: AccountInfo<'info>,
+    pub user_shares_ata: AccountInfo<'info>,
+    pub shares_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub shares_token_program: AccountInfo<'info>,
+    pub klend_program: AccountInfo<'info>,
+    pub event_authority: AccountInfo<'info>,
+    pub kamino_vault_program: AccountInfo<'info>,
+}
+
+impl<'info> KaminoWithdrawCPI<'info> {
+    pub fn to_cpi_accounts(&self) -> KaminoWithdrawAccounts<'info> {
+        KaminoWithdrawAccounts {
+            user: self.user.to_account_info(),
+            vault_state: self.vault_state.to_account_info(),
+            token_vault: self.token_vault.to_account_info(),
+            base_vault_authority: self.base_vault_authority.to_account_info(),
+            user_token_ata: self.user_token_ata.to_account_info(),
+            token_mint: self.token_mint.to_account_info(),
+            user_shares_ata: self.user_shares_ata.to_account_info(),
+            shares_mint: self.shares_mint.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            shares_token_program: self.shares_token_program.to_account_info(),
+            klend_program: self.klend_program.to_account_info(),
+            event_authority: self.event_authority.to_account_info(),
+            kamino_vault_program: self.kamino_vault_program.to_account_info(),
+        }
+    }
+}
+
 /// CPI调用Kamino进行提取（完整实现）
 ///
 /// remaining_accounts 应该包含复杂的 vault 相关账户:
@@ -254,10 +347,20 @@ pub fn kamino_deposit_cpi<'info>(
 pub fn kamino_withdraw_cpi<'info>(
     ctx: Context<'_, '_, '_, 'info, KaminoWithdrawCPI<'info>>,
     max_amount: u64,
+) -> Result<()> {
+    invoke_kamino_withdraw(&ctx.accounts.to_cpi_accounts(), ctx.remaining_accounts, max_amount, &[])
+}
+
+/// 执行 Kamino 提取 CPI，signer_seeds 为空时由 user 直接签名
+pub fn invoke_kamino_withdraw<'info>(
+    accounts: &KaminoWithdrawAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    max_amount: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     // 验证Kamino程序ID
     require_eq!(
-        ctx.accounts.kamino_vault_program.key(),
+        accounts.kamino_vault_program.key(),
         KAMINO_PROGRAM_ID,
         KaminoCPIError::InvalidKaminoProgram
     );
@@ -265,37 +368,37 @@ pub fn kamino_withdraw_cpi<'info>(
     // withdrawFromAvailable 账户
     let mut account_metas = vec![
         // 1. user
-        AccountMeta::new(ctx.accounts.user.key(), true),
+        AccountMeta::new(accounts.user.key(), true),
         // 2. vaultState
-        AccountMeta::new(ctx.accounts.vault_state.key(), false),
+        AccountMeta::new(accounts.vault_state.key(), false),
         // 3. tokenVault
-        AccountMeta::new(ctx.accounts.token_vault.key(), false),
+        AccountMeta::new(accounts.token_vault.key(), false),
         // 4. baseVaultAuthority
-        AccountMeta::new_readonly(ctx.accounts.base_vault_authority.key(), false),
+        AccountMeta::new_readonly(accounts.base_vault_authority.key(), false),
         // 5. userTokenAta
-        AccountMeta::new(ctx.accounts.user_token_ata.key(), false),
+        AccountMeta::new(accounts.user_token_ata.key(), false),
         // 6. tokenMint
-        AccountMeta::new(ctx.accounts.token_mint.key(), false),
+        AccountMeta::new(accounts.token_mint.key(), false),
         // 7. userSharesAta
-        AccountMeta::new(ctx.accounts.user_shares_ata.key(), false),
+        AccountMeta::new(accounts.user_shares_ata.key(), false),
         // 8. sharesMint
-        AccountMeta::new(ctx.accounts.shares_mint.key(), false),
+        AccountMeta::new(accounts.shares_mint.key(), false),
         // 9. tokenProgram
-        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
         // 10. sharesTokenProgram
-        AccountMeta::new_readonly(ctx.accounts.shares_token_program.key(), false),
+        AccountMeta::new_readonly(accounts.shares_token_program.key(), false),
         // 11. klendProgram
-        AccountMeta::new_readonly(ctx.accounts.klend_program.key(), false),
+        AccountMeta::new_readonly(accounts.klend_program.key(), false),
         // 12. eventAuthority
-        AccountMeta::new_readonly(ctx.accounts.event_authority.key(), false),
+        AccountMeta::new_readonly(accounts.event_authority.key(), false),
         // 13. program
-        AccountMeta::new_readonly(ctx.accounts.kamino_vault_program.key(), false),
+        AccountMeta::new_readonly(accounts.kamino_vault_program.key(), false),
     ];
 
     // Add remaining_accounts
     // Use account.is_writable to determine permissions (like in deposit)
-    msg!("📋 Adding {} remaining accounts", ctx.remaining_accounts.len());
-    for account in ctx.remaining_accounts.iter() {
+    msg!("📋 Adding {} remaining accounts", remaining_accounts.len());
+    for account in remaining_accounts.iter() {
         if account.is_writable {
             account_metas.push(AccountMeta::new(account.key(), false));
         } else {
@@ -317,27 +420,25 @@ pub fn kamino_withdraw_cpi<'info>(
 
     // 构建 account_infos (包含 remaining_accounts)
     let mut account_infos = vec![
-        ctx.accounts.user.to_account_info(),
-        ctx.accounts.vault_state.to_account_info(),
-        ctx.accounts.token_vault.to_account_info(),
-        ctx.accounts.base_vault_authority.to_account_info(),
-        ctx.accounts.user_token_ata.to_account_info(),
-        ctx.accounts.token_mint.to_account_info(),
-        ctx.accounts.user_shares_ata.to_account_info(),
-        ctx.accounts.shares_mint.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.shares_token_program.to_account_info(),
-        ctx.accounts.klend_program.to_account_info(),
-        ctx.accounts.event_authority.to_account_info(),
-        ctx.accounts.kamino_vault_program.to_account_info(),
+        accounts.user.clone(),
+        accounts.vault_state.clone(),
+        accounts.token_vault.clone(),
+        accounts.base_vault_authority.clone(),
+        accounts.user_token_ata.clone(),
+        accounts.token_mint.clone(),
+        accounts.user_shares_ata.clone(),
+        accounts.shares_mint.clone(),
+        accounts.token_program.clone(),
+        accounts.shares_token_program.clone(),
+        accounts.klend_program.clone(),
+        accounts.event_authority.clone(),
+        accounts.kamino_vault_program.clone(),
     ];
 
     // 添加 remaining_accounts 到 account_infos
-    for account in ctx.remaining_accounts.iter() {
-        account_infos.push(account.to_account_info());
-    }
+    account_infos.extend_from_slice(remaining_accounts);
 
-    anchor_lang::solana_program::program::invoke(&ix, &account_infos)?;
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, signer_seeds)?;
     Ok(())
 }
 
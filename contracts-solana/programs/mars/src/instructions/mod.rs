@@ -9,3 +9,7 @@ pub use vault::*;
 // === 协议集成模块 ===
 pub mod integrations;
 pub use integrations::*;
+
+// === 跨链订单模块 ===
+pub mod order;
+pub use order::*;
@@ -0,0 +1,200 @@
+use crate::events::OrderCreated;
+use crate::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+// ============================================================================
+// Create Order - 创建跨链订单，USDC 托管到 ata_vault
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(seed: [u8; 32], amount_in: u64, fee: u64, src_chain_id: u32, dest_chain_id: u32)]
+pub struct CreateOrder<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+
+    //  Global state
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump,
+        constraint = !global_state.frozen @MarsError::GlobalStateFrozen
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Stores order info, one PDA per order seed
+    #[account(
+        init_if_needed,
+        space = Order::space(),
+        seeds = [ORDER_SEED, seed.as_ref()],
+        bump,
+        payer = trader
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    //  Minimum fee for the destination chain, must be configured by admin
+    #[account(
+        seeds = [
+            TARGET_CHAIN_MIN_FEE_SEED,
+            &dest_chain_id.to_le_bytes(),
+            usdc_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub target_chain_min_fee: Box<Account<'info, TargetChainMinFee>>,
+
+    //  Size based swap fee tiers
+    #[account(
+        seeds = [FEE_TIERS_SEED],
+        bump
+    )]
+    pub fee_tiers: Box<Account<'info, FeeTiers>>,
+
+    //  Size based insurance fee tiers
+    #[account(
+        seeds = [INSURANCE_FEE_TIERS_SEED],
+        bump
+    )]
+    pub insurance_fee_tiers: Box<Account<'info, InsuranceFeeTiers>>,
+
+    //  Store deposited assets
+    #[account(
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault: AccountInfo<'info>,
+
+    //  USDC ata of vault
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault,
+    )]
+    pub ata_vault: Box<Account<'info, TokenAccount>>,
+
+    //  USDC ata of trader
+    #[account(
+        mut,
+        constraint = trader_token_account.owner == trader.key() @MarsError::InvalidOwner,
+        constraint = trader_token_account.mint == usdc_mint.key() @MarsError::InvalidMint,
+        constraint = trader_token_account.amount >= amount_in @MarsError::InsufficientFunds
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = usdc_mint.key() == global_state.base_mint @MarsError::InvalidTokenIn
+    )]
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    //  Needed to init new account
+    pub system_program: Program<'info, System>,
+}
+
+impl CreateOrder<'_> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        seed: [u8; 32],
+        amount_in: u64,
+        fee: u64,
+        src_chain_id: u32,
+        dest_chain_id: u32,
+        receiver: [u8; 32],
+        token_out: [u8; 32],
+        min_amount_out: String,
+        fill_deadline: i64,
+        order_hash: [u8; 32],
+    ) -> Result<()> {
+        let global_state = &ctx.accounts.global_state;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            ctx.accounts.order.status == OrderStatus::Unexistant,
+            MarsError::OrderAlreadyExists
+        );
+        require!(amount_in > 0, MarsError::ZeroAmount);
+        require!(amount_in <= global_state.max_order_amount, MarsError::MaxOrderAmountExceeded);
+        require!(src_chain_id != dest_chain_id, MarsError::SameSourceAndDestinationChainIds);
+        require!(fill_deadline > now, MarsError::InvalidOrderFillDeadline);
+        require!(token_out != [0u8; 32], MarsError::InvalidTokenOut);
+        require!(Order::is_valid_min_amount_out(&min_amount_out), MarsError::InvalidMinAmountOut);
+
+        //  fee = swap fee and insurance fee by size tier + minimum fee of the destination chain
+        let tiered_fee = TieredFee::compute(
+            &ctx.accounts.fee_tiers,
+            &ctx.accounts.insurance_fee_tiers,
+            amount_in,
+        )
+        .ok_or(MarsError::MathOverflow)?;
+        let required_fee =
+            Order::required_fee(&tiered_fee, ctx.accounts.target_chain_min_fee.min_fee)
+                .ok_or(MarsError::MathOverflow)?;
+        require!(fee >= required_fee, MarsError::InsufficientFees);
+        require!(fee < amount_in, MarsError::ExcessFee);
+
+        //  escrow the full amount (fee included) in ata_vault
+        token_transfer_user(
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.trader.to_account_info(),
+            ctx.accounts.ata_vault.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount_in,
+        )?;
+
+        let order = &mut ctx.accounts.order;
+        order.seed = seed;
+        order.amount_in = amount_in;
+        order.trader = ctx.accounts.trader.key().to_bytes();
+        order.receiver = receiver;
+        order.src_chain_id = src_chain_id;
+        order.dest_chain_id = dest_chain_id;
+        order.fill_deadline = fill_deadline;
+        order.token_in = ctx.accounts.usdc_mint.key().to_bytes();
+        order.status = OrderStatus::Created;
+        order.fee = fee;
+        order.min_amount_out = min_amount_out;
+        order.token_out = token_out;
+        order.order_hash = order_hash;
+        order.swap_fee_bps = tiered_fee.fee_bps as u16;
+        order.insurance_fee = tiered_fee.insurance_fee;
+        order.bump = ctx.bumps.order;
+
+        msg!(
+            "CreateOrder: {{\
+            \"trader\":\"{:?}\",\
+            \"order\":\"{:?}\",\
+            \"amount_in\":\"{:?}\",\
+            \"fee\":\"{:?}\",\
+            \"dest_chain_id\":\"{:?}\"\
+            }}",
+            ctx.accounts.trader.key(),
+            order.key(),
+            amount_in,
+            fee,
+            dest_chain_id
+        );
+
+        emit!(OrderCreated {
+            order: order.key(),
+            order_hash,
+            trader: ctx.accounts.trader.key(),
+            receiver,
+            src_chain_id,
+            dest_chain_id,
+            token_in: ctx.accounts.usdc_mint.key(),
+            token_out,
+            amount_in,
+            fee,
+            min_amount_out: order.min_amount_out.clone(),
+            fill_deadline,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
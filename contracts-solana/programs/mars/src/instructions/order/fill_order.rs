@@ -0,0 +1,96 @@
+use crate::events::OrderFilled;
+use crate::*;
+
+// ============================================================================
+// Fill Order - orchestrator 确认订单已在目标链成交
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct FillOrder<'info> {
+    //  Global admin or orchestrator
+    #[account(
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Orchestrator, &global_state.admin, &orchestrator.key())
+            @MarsError::UnauthorizedSigner
+    )]
+    pub orchestrator: Signer<'info>,
+
+    //  Global state
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump,
+        constraint = !global_state.frozen @MarsError::GlobalStateFrozen
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    //  Order to fill
+    #[account(
+        mut,
+        seeds = [ORDER_SEED, order.seed.as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    //  Fee buckets, the order fee is earned once filled
+    #[account(
+        mut,
+        seeds = [ASSET_SEED],
+        bump
+    )]
+    pub asset: Box<Account<'info, Asset>>,
+
+    //  Splits the earned fee between the protocol and base buckets
+    #[account(
+        seeds = [PROTOCOL_FEE_FRACTION_SEED],
+        bump
+    )]
+    pub protocol_fee_fraction: Box<Account<'info, ProtocolFeeFraction>>,
+
+    //  Role registry (optional, only admin is accepted when absent)
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl FillOrder<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let order = &mut ctx.accounts.order;
+
+        require!(order.status == OrderStatus::Created, MarsError::InvalidOrderStatus);
+        require!(now <= order.fill_deadline, MarsError::DeadlinePassed);
+
+        //  escrowed USDC stays in ata_vault as the protocol paid out on the destination chain
+        order.status = OrderStatus::Filled;
+        ctx.accounts.asset.record_fee(
+            order.fee,
+            order.insurance_fee,
+            &ctx.accounts.protocol_fee_fraction,
+        )?;
+
+        msg!(
+            "FillOrder: {{\
+            \"orchestrator\":\"{:?}\",\
+            \"order\":\"{:?}\",\
+            \"amount_in\":\"{:?}\",\
+            \"fee\":\"{:?}\"\
+            }}",
+            ctx.accounts.orchestrator.key(),
+            order.key(),
+            order.amount_in,
+            order.fee
+        );
+
+        emit!(OrderFilled {
+            order: order.key(),
+            order_hash: order.order_hash,
+            orchestrator: ctx.accounts.orchestrator.key(),
+            amount_in: order.amount_in,
+            fee: order.fee,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
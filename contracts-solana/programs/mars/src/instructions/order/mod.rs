@@ -0,0 +1,7 @@
+pub mod create_order;
+pub mod fill_order;
+pub mod revert_order;
+
+pub use create_order::*;
+pub use fill_order::*;
+pub use revert_order::*;
@@ -0,0 +1,128 @@
+use crate::events::OrderReverted;
+use crate::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+// ============================================================================
+// Revert Order - 超过截止时间未成交的订单由 trader 撤销并退款
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct RevertOrder<'info> {
+    #[account(mut)]
+    pub trader: Signer<'info>,
+
+    //  Order to revert
+    #[account(
+        mut,
+        seeds = [ORDER_SEED, order.seed.as_ref()],
+        bump = order.bump,
+        constraint = order.trader == trader.key().to_bytes() @MarsError::InvalidTrader
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    //  Fee buckets, the part of the fee that is not refunded is earned
+    #[account(
+        mut,
+        seeds = [ASSET_SEED],
+        bump
+    )]
+    pub asset: Box<Account<'info, Asset>>,
+
+    //  Splits the earned fee between the protocol and base buckets
+    #[account(
+        seeds = [PROTOCOL_FEE_FRACTION_SEED],
+        bump
+    )]
+    pub protocol_fee_fraction: Box<Account<'info, ProtocolFeeFraction>>,
+
+    //  Store deposited assets
+    #[account(
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault: AccountInfo<'info>,
+
+    //  USDC ata of vault
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault,
+    )]
+    pub ata_vault: Box<Account<'info, TokenAccount>>,
+
+    //  USDC ata of trader, receives the refund
+    #[account(
+        mut,
+        constraint = trader_token_account.owner == trader.key() @MarsError::InvalidOwner,
+        constraint = trader_token_account.mint == usdc_mint.key() @MarsError::InvalidMint
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = usdc_mint.key().to_bytes() == order.token_in @MarsError::InvalidTokenIn
+    )]
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RevertOrder<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let order = &ctx.accounts.order;
+
+        require!(order.status == OrderStatus::Created, MarsError::InvalidOrderStatus);
+        require!(now > order.fill_deadline, MarsError::DeadlineNotPassed);
+
+        //  swap fee is refunded, the target chain minimum fee is kept by the protocol
+        let refund = calculate_refund_amount(order.amount_in, order.fee, order.swap_fee_bps);
+
+        let vault_bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &vault_bump]];
+        token_transfer_with_signer(
+            ctx.accounts.ata_vault.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+            refund.refund_amount,
+        )?;
+
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Reverted;
+        ctx.accounts.asset.record_fee(
+            refund.protocol_fee,
+            order.insurance_fee,
+            &ctx.accounts.protocol_fee_fraction,
+        )?;
+
+        msg!(
+            "RevertOrder: {{\
+            \"trader\":\"{:?}\",\
+            \"order\":\"{:?}\",\
+            \"refund_amount\":\"{:?}\",\
+            \"protocol_fee\":\"{:?}\"\
+            }}",
+            ctx.accounts.trader.key(),
+            order.key(),
+            refund.refund_amount,
+            refund.protocol_fee
+        );
+
+        emit!(OrderReverted {
+            order: order.key(),
+            order_hash: order.order_hash,
+            trader: ctx.accounts.trader.key(),
+            refund_amount: refund.refund_amount,
+            protocol_fee: refund.protocol_fee,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
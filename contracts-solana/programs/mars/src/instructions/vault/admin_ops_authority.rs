@@ -0,0 +1,157 @@
+use crate::constants::{GLOBAL_SEED, PENDING_CHANGE_SEED};
+use crate::error::*;
+use crate::events::{VaultAdminNominated, VaultAdminNominationCancelled, VaultAdminTransferred};
+use crate::instructions::consume_pending_change;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// 提名新的 Vault 管理员（两步转移的第一步）
+/// 只有当前 Vault admin 可以调用；启用 timelock 时需先排队
+#[derive(Accounts)]
+pub struct NominateVaultAdmin<'info> {
+    #[account(
+        mut,
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 全局状态（timelock 延迟）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 已到期的待执行变更（未启用 timelock 时可省略），执行后关闭，租金退还给排队者
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            vault_state.key().as_ref(),
+            &[PendingChangeData::KIND_VAULT_ADMIN]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// 排队者账户（待执行项关闭后租金退还至此）
+    /// CHECK: 在 consume_pending_change 中与 pending_change.proposer 比对
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+}
+
+impl NominateVaultAdmin<'_> {
+    pub fn process_instruction(ctx: Context<Self>, new_admin: Pubkey) -> Result<()> {
+        require!(new_admin != Pubkey::default(), CustomError::InvalidParameter);
+
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.vault_state.key(),
+            &PendingChangeData::VaultAdmin {
+                new_admin,
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.pending_admin = Some(new_admin);
+        vault_state.last_updated = now;
+
+        msg!("👤 Vault admin nominated: {} -> {}", vault_state.admin, new_admin);
+
+        emit!(VaultAdminNominated {
+            vault_id: vault_state.vault_id,
+            admin: vault_state.admin,
+            pending_admin: new_admin,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// 被提名者接受 Vault 管理员角色（两步转移的第二步）
+#[derive(Accounts)]
+pub struct AcceptVaultAdmin<'info> {
+    #[account(
+        constraint = vault_state.pending_admin == Some(new_admin.key()) @ CustomError::InvalidAdmin
+    )]
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+}
+
+impl AcceptVaultAdmin<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        let old_admin = vault_state.admin;
+        let now = Clock::get()?.unix_timestamp;
+
+        vault_state.admin = ctx.accounts.new_admin.key();
+        vault_state.pending_admin = None;
+        vault_state.last_updated = now;
+
+        msg!("✅ Vault admin transferred: {} -> {}", old_admin, vault_state.admin);
+
+        emit!(VaultAdminTransferred {
+            vault_id: vault_state.vault_id,
+            old_admin,
+            new_admin: vault_state.admin,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// 取消尚未接受的 Vault 管理员提名
+#[derive(Accounts)]
+pub struct CancelVaultAdminNomination<'info> {
+    #[account(
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+}
+
+impl CancelVaultAdminNomination<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        let cancelled_admin =
+            vault_state.pending_admin.take().ok_or(CustomError::NoPendingAdmin)?;
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.last_updated = now;
+
+        msg!("🛑 Vault admin nomination cancelled: {}", cancelled_admin);
+
+        emit!(VaultAdminNominationCancelled {
+            vault_id: vault_state.vault_id,
+            admin: vault_state.admin,
+            cancelled_admin,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::constants::{GLOBAL_SEED, PENDING_CHANGE_SEED};
+use crate::error::*;
+use crate::events::DepositLimitsUpdated;
+use crate::instructions::consume_pending_change;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// 更新 Vault 的存款限额（TVL 上限、用户持仓上限、单笔最小 / 最大金额）
+/// 只有 Vault admin 可以调用（创建 Vault 的 curator 即为其 admin），未传入的参数保持不变，0 表示不限制；启用 timelock 时需先排队
+#[derive(Accounts)]
+pub struct UpdateVaultDepositLimits<'info> {
+    #[account(
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 全局状态（timelock 延迟）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 已到期的待执行变更（未启用 timelock 时可省略），执行后关闭，租金退还给排队者
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            vault_state.key().as_ref(),
+            &[PendingChangeData::KIND_VAULT_DEPOSIT_LIMITS]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// 排队者账户（待执行项关闭后租金退还至此）
+    /// CHECK: 在 consume_pending_change 中与 pending_change.proposer 比对
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+}
+
+impl UpdateVaultDepositLimits<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        max_total_deposits: Option<u64>,
+        max_user_deposit: Option<u64>,
+        min_deposit_amount: Option<u64>,
+        max_deposit_amount: Option<u64>,
+    ) -> Result<()> {
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.vault_state.key(),
+            &PendingChangeData::VaultDepositLimits {
+                max_total_deposits,
+                max_user_deposit,
+                min_deposit_amount,
+                max_deposit_amount,
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        let max_total_deposits = max_total_deposits.unwrap_or(vault_state.max_total_deposits);
+        let max_user_deposit = max_user_deposit.unwrap_or(vault_state.max_user_deposit);
+        let min_deposit_amount = min_deposit_amount.unwrap_or(vault_state.min_deposit_amount);
+        let max_deposit_amount = max_deposit_amount.unwrap_or(vault_state.max_deposit_amount);
+
+        require!(
+            max_deposit_amount == 0 || min_deposit_amount <= max_deposit_amount,
+            CustomError::InvalidParameter
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.max_total_deposits = max_total_deposits;
+        vault_state.max_user_deposit = max_user_deposit;
+        vault_state.min_deposit_amount = min_deposit_amount;
+        vault_state.max_deposit_amount = max_deposit_amount;
+        vault_state.last_updated = now;
+
+        msg!("✅ Deposit limits updated");
+        msg!("  TVL cap: {}, user cap: {}", max_total_deposits, max_user_deposit);
+        msg!("  Per-transaction: min {}, max {}", min_deposit_amount, max_deposit_amount);
+
+        emit!(DepositLimitsUpdated {
+            vault_id: vault_state.vault_id,
+            max_total_deposits,
+            max_user_deposit,
+            min_deposit_amount,
+            max_deposit_amount,
+            updated_by: ctx.accounts.admin.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use crate::constants::{GLOBAL_SEED, PENDING_CHANGE_SEED, ROLE_REGISTRY_SEED};
+use crate::error::*;
+use crate::events::FeeConfigUpdated;
+use crate::instructions::consume_pending_change;
+use crate::instructions::vault::position_valuation::*;
+use crate::state::*;
+use crate::utils::FeeEngine;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::TokenAccount as BaseTokenAccount;
+
+/// 更新 Vault 的费用配置（存款 / 提款 / 管理 / 性能费率和费用接收地址）
+/// Vault admin 或 fee manager 可以调用，未传入的参数保持不变；启用 timelock 时需先排队
+/// fee manager 只能调整费率，修改费用接收地址需要 Vault admin
+#[derive(Accounts)]
+pub struct UpdateVaultFeeConfig<'info> {
+    #[account(
+        mut,
+        constraint = RoleRegistry::authorizes(&role_registry, Role::FeeManager, &vault_state.admin, &admin.key())
+            @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// 全局状态（timelock 延迟）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 已到期的待执行变更（未启用 timelock 时可省略），执行后关闭，租金退还给排队者
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            vault_state.key().as_ref(),
+            &[PendingChangeData::KIND_VAULT_FEE_CONFIG]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// 排队者账户（待执行项关闭后租金退还至此）
+    /// CHECK: 在 consume_pending_change 中与 pending_change.proposer 比对
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 的代币金库（用于按 NAV 结算费用）
+    #[account(
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 当前费用接收方的份额账户（按旧费率结算的管理费份额发往此处）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Account<'info, TokenAccount>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl UpdateVaultFeeConfig<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        deposit_fee_bps: Option<u16>,
+        withdraw_fee_bps: Option<u16>,
+        management_fee_bps: Option<u16>,
+        performance_fee_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        // 费用接收地址决定管理费份额的去向，只有 Vault admin 可以修改
+        if fee_recipient.is_some() {
+            require_keys_eq!(
+                ctx.accounts.admin.key(),
+                ctx.accounts.vault_state.admin,
+                CustomError::OnlyAdmin
+            );
+        }
+
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.vault_state.key(),
+            &PendingChangeData::VaultFeeConfig {
+                deposit_fee_bps,
+                withdraw_fee_bps,
+                management_fee_bps,
+                performance_fee_bps,
+                fee_recipient,
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = ctx.accounts.vault_state.key();
+        ctx.accounts
+            .position_valuation
+            .mark_to_market(&mut ctx.accounts.vault_state, &vault_key)?;
+
+        // 1. 按旧费率结算到当前时刻，新费率只对之后的时间和收益生效
+        FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        // 2. 合并并校验新配置
+        let vault_state = &mut ctx.accounts.vault_state;
+        let current = &vault_state.fee_config;
+        let fee_config = FeeConfig {
+            deposit_fee_bps: deposit_fee_bps.unwrap_or(current.deposit_fee_bps),
+            withdraw_fee_bps: withdraw_fee_bps.unwrap_or(current.withdraw_fee_bps),
+            management_fee_bps: management_fee_bps.unwrap_or(current.management_fee_bps),
+            performance_fee_bps: performance_fee_bps.unwrap_or(current.performance_fee_bps),
+            fee_recipient: fee_recipient.unwrap_or(current.fee_recipient),
+        };
+        fee_config.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.fee_config = fee_config.clone();
+        vault_state.last_updated = now;
+
+        msg!("✅ Fee config updated");
+        msg!(
+            "  Deposit: {} bps, Withdraw: {} bps, Management: {} bps, Performance: {} bps",
+            fee_config.deposit_fee_bps,
+            fee_config.withdraw_fee_bps,
+            fee_config.management_fee_bps,
+            fee_config.performance_fee_bps
+        );
+        msg!("  Fee recipient: {}", fee_config.fee_recipient);
+
+        emit!(FeeConfigUpdated {
+            vault_id: vault_state.vault_id,
+            deposit_fee_bps: fee_config.deposit_fee_bps,
+            withdraw_fee_bps: fee_config.withdraw_fee_bps,
+            management_fee_bps: fee_config.management_fee_bps,
+            performance_fee_bps: fee_config.performance_fee_bps,
+            fee_recipient: fee_config.fee_recipient,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -1,10 +1,10 @@
 use crate::error::CustomError;
 use crate::*;
-use anchor_spl::token::{Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 
 #[derive(Accounts)]
 pub struct ClaimFees<'info> {
-    /// 管理员（必须是 vault 的 admin）
+    /// Vault admin 或 fee manager
     #[account(mut)]
     pub admin: Signer<'info>,
 
@@ -13,7 +13,8 @@ pub struct ClaimFees<'info> {
         mut,
         seeds = [b"vault-state", vault_state.vault_id.as_ref()],
         bump = vault_state.bump,
-        constraint = vault_state.admin == admin.key() @ CustomError::OnlyAdmin
+        constraint = RoleRegistry::authorizes(&role_registry, Role::FeeManager, &vault_state.admin, &admin.key())
+            @ CustomError::OnlyAdmin
     )]
     pub vault_state: Account<'info, VaultState>,
 
@@ -23,13 +24,34 @@ pub struct ClaimFees<'info> {
         seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
         bump
     )]
-    pub vault_treasury: Account<'info, TokenAccount>,
+    pub vault_treasury: InterfaceAccount<'info, TokenAccount>,
 
-    /// 管理员的代币账户（接收费用）
-    #[account(mut)]
-    pub admin_token_account: Account<'info, TokenAccount>,
+    /// 接收费用的代币账户（fee manager 领取时必须属于 fee_recipient）
+    #[account(
+        mut,
+        constraint = admin_token_account.mint == vault_state.base_token_mint @ CustomError::InvalidMint,
+        constraint = admin.key() == vault_state.admin
+            || admin_token_account.owner == vault_state.fee_config.fee_recipient
+            @ CustomError::InvalidOwner
+    )]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    /// Vault 基础代币 Mint（transfer_checked 需要 decimals）
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub base_token_mint: InterfaceAccount<'info, Mint>,
+
+    /// 基础代币所属的 Token Program（SPL Token 或 Token-2022）
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
 }
 
 impl ClaimFees<'_> {
@@ -80,14 +102,19 @@ impl ClaimFees<'_> {
 
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.vault_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
                 to: ctx.accounts.admin_token_account.to_account_info(),
                 authority: ctx.accounts.vault_state.to_account_info(),
             },
             signer_seeds,
         );
-        anchor_spl::token::transfer(transfer_ctx, amount)?;
+        anchor_spl::token_interface::transfer_checked(
+            transfer_ctx,
+            amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
 
         msg!(
             "✅ ClaimedFee: admin={}, amount={}, fee_type={:?}",
@@ -126,14 +153,19 @@ impl ClaimFees<'_> {
 
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.vault_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
                 to: ctx.accounts.admin_token_account.to_account_info(),
                 authority: ctx.accounts.vault_state.to_account_info(),
             },
             signer_seeds,
         );
-        anchor_spl::token::transfer(transfer_ctx, total_unclaimed)?;
+        anchor_spl::token_interface::transfer_checked(
+            transfer_ctx,
+            total_unclaimed,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
 
         // 清零所有未认领费用
         let vault_state = &mut ctx.accounts.vault_state;
@@ -1,13 +1,14 @@
 use crate::*;
 
 /// 更新 Vault 的平台费率配置
-/// 只有 admin 可以调用
+/// Vault admin 或 fee manager 可以调用；启用 timelock 时需先排队
 #[derive(Accounts)]
 pub struct UpdateVaultPlatformFee<'info> {
-    /// Admin 账户
+    /// Vault admin 或 fee manager
     #[account(
         mut,
-        constraint = vault_state.admin == admin.key() @ MarsError::InvalidAdmin
+        constraint = RoleRegistry::authorizes(&role_registry, Role::FeeManager, &vault_state.admin, &admin.key())
+            @ MarsError::InvalidAdmin
     )]
     pub admin: Signer<'info>,
 
@@ -18,6 +19,37 @@ pub struct UpdateVaultPlatformFee<'info> {
         bump = vault_state.bump,
     )]
     pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 全局状态（timelock 延迟）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 已到期的待执行变更（未启用 timelock 时可省略），执行后关闭，租金退还给排队者
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            vault_state.key().as_ref(),
+            &[PendingChangeData::KIND_VAULT_PLATFORM_FEE]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// 排队者账户（待执行项关闭后租金退还至此）
+    /// CHECK: 在 consume_pending_change 中与 pending_change.proposer 比对
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
 }
 
 impl UpdateVaultPlatformFee<'_> {
@@ -25,6 +57,17 @@ impl UpdateVaultPlatformFee<'_> {
         // 验证费率在合理范围内（0-10000，即 0%-100%）
         require!(new_platform_fee_bps <= 10_000, MarsError::InvalidParameter);
 
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.vault_state.key(),
+            &PendingChangeData::VaultPlatformFee {
+                platform_fee_bps: new_platform_fee_bps,
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
         let vault_state = &mut ctx.accounts.vault_state;
         let old_fee = vault_state.platform_fee_bps;
 
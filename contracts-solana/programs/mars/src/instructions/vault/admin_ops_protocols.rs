@@ -0,0 +1,305 @@
+use crate::constants::{GLOBAL_SEED, PENDING_CHANGE_SEED};
+use crate::error::*;
+use crate::events::ProtocolConfigUpdated;
+use crate::instructions::consume_pending_change;
+use crate::instructions::vault::position_valuation::required_position_account;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// 为 Vault 注册新的收益协议（扩容 vault_state）
+/// 只有 Vault admin 可以调用；启用 timelock 时需先排队
+///
+/// receipt_account / rate_source 绑定 Vault 的收益凭证账户及其汇率来源，持仓按此计价
+#[derive(Accounts)]
+pub struct AddProtocol<'info> {
+    #[account(
+        mut,
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+        realloc = vault_state.current_space() + ProtocolConfig::space(),
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 全局状态（timelock 延迟）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 已到期的待执行变更（未启用 timelock 时可省略），执行后关闭，租金退还给排队者
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CHANGE_SEED,
+            vault_state.key().as_ref(),
+            &[PendingChangeData::KIND_ADD_PROTOCOL]
+        ],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// 排队者账户（待执行项关闭后租金退还至此）
+    /// CHECK: 在 consume_pending_change 中与 pending_change.proposer 比对
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl AddProtocol<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        protocol_id: u8,
+        program_id: Pubkey,
+        target_allocation_bps: u16,
+        params: Vec<u8>,
+        receipt_account: Pubkey,
+        rate_source: Pubkey,
+    ) -> Result<()> {
+        consume_pending_change(
+            ctx.accounts.global_state.timelock_delay_seconds,
+            ctx.accounts.pending_change.as_deref(),
+            ctx.accounts.proposer.as_deref(),
+            &ctx.accounts.vault_state.key(),
+            &PendingChangeData::AddProtocol {
+                protocol_id,
+                program_id,
+                target_allocation_bps,
+                params: params.clone(),
+                receipt_account,
+                rate_source,
+            },
+            &ctx.accounts.admin.key(),
+        )?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.add_protocol(ProtocolConfig::new(
+            protocol_id,
+            program_id,
+            target_allocation_bps,
+            params,
+            receipt_account,
+            rate_source,
+        ))?;
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.last_updated = now;
+
+        msg!("✅ Protocol {} registered: program={}", protocol_id, program_id);
+        msg!("  Target allocation: {} bps", target_allocation_bps);
+        msg!("  Receipt account: {}, rate source: {}", receipt_account, rate_source);
+
+        emit!(ProtocolConfigUpdated {
+            vault_id: vault_state.vault_id,
+            protocol_id,
+            enabled: true,
+            allocation_weight_bps: target_allocation_bps,
+            target_allocation_bps,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// 从 Vault 移除协议（缩容 vault_state，租金退还给 admin）
+/// 协议中仍有资产时不允许移除：账面分配和绑定的收益凭证账户余额都必须为 0
+#[derive(Accounts)]
+pub struct RemoveProtocol<'info> {
+    #[account(
+        mut,
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+        realloc = vault_state.current_space().saturating_sub(ProtocolConfig::space()),
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 协议绑定的收益凭证账户（已集成协议必须传入，未绑定时可省略）
+    pub receipt_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl RemoveProtocol<'_> {
+    pub fn process_instruction(ctx: Context<Self>, protocol_id: u8) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        // 账面分配可能滞后于实际持仓，以收益凭证账户余额为准
+        let receipt_key = vault_state
+            .find_protocol(protocol_id)
+            .ok_or(CustomError::UnsupportedProtocol)?
+            .receipt_account;
+        if receipt_key != Pubkey::default() {
+            let receipt = required_position_account(&ctx.accounts.receipt_account)?;
+            vault_state.require_receipt_account(protocol_id, &receipt.key())?;
+            require!(receipt.amount == 0, CustomError::ProtocolNotEmpty);
+        }
+
+        vault_state.remove_protocol(protocol_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.last_updated = now;
+
+        msg!("🗑️ Protocol {} removed", protocol_id);
+
+        emit!(ProtocolConfigUpdated {
+            vault_id: vault_state.vault_id,
+            protocol_id,
+            enabled: false,
+            allocation_weight_bps: 0,
+            target_allocation_bps: 0,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// 启用 / 禁用协议，或批量设置目标分配
+/// 只有 Vault admin 可以调用（创建 Vault 的 curator 即为其 admin）；启用 timelock 时需先排队
+#[derive(Accounts)]
+pub struct UpdateProtocolRegistry<'info> {
+    #[account(
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 全局状态（timelock 延迟）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 已到期的待执行变更（未启用 timelock 时可省略），执行后关闭，租金退还给排队者
+    /// 协议启停和目标分配各自排队，执行时校验变更内容
+    #[account(
+        mut,
+        seeds = [PENDING_CHANGE_SEED, vault_state.key().as_ref(), &[pending_change.kind]],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// 排队者账户（待执行项关闭后租金退还至此）
+    /// CHECK: 在 consume_pending_change 中与 pending_change.proposer 比对
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+}
+
+impl UpdateProtocolRegistry<'_> {
+    /// 启用协议（启用后目标分配之和不能超过 10000）
+    pub fn enable_protocol(ctx: Context<Self>, protocol_id: u8) -> Result<()> {
+        Self::set_enabled(ctx, protocol_id, true)
+    }
+
+    /// 禁用协议（再平衡时跳过，目标分配需另行调整）
+    pub fn disable_protocol(ctx: Context<Self>, protocol_id: u8) -> Result<()> {
+        Self::set_enabled(ctx, protocol_id, false)
+    }
+
+    /// 批量设置目标分配，启用协议的目标之和必须等于 10000
+    pub fn set_target_allocations(
+        ctx: Context<Self>,
+        allocations: Vec<ProtocolTargetAllocation>,
+    ) -> Result<()> {
+        ctx.accounts.consume_queued_change(PendingChangeData::TargetAllocations {
+            allocations: allocations.clone(),
+        })?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.set_target_allocations(&allocations)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.last_updated = now;
+
+        for protocol in vault_state.supported_protocols.iter() {
+            msg!(
+                "🎯 Protocol {} target allocation: {} bps",
+                protocol.protocol_id,
+                protocol.target_allocation_bps
+            );
+            emit!(ProtocolConfigUpdated {
+                vault_id: vault_state.vault_id,
+                protocol_id: protocol.protocol_id,
+                enabled: protocol.enabled,
+                allocation_weight_bps: protocol.allocation_weight_bps,
+                target_allocation_bps: protocol.target_allocation_bps,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn set_enabled(ctx: Context<Self>, protocol_id: u8, enabled: bool) -> Result<()> {
+        ctx.accounts.consume_queued_change(PendingChangeData::ProtocolEnabled {
+            protocol_id,
+            enabled,
+        })?;
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        let protocol =
+            vault_state.find_protocol_mut(protocol_id).ok_or(CustomError::UnsupportedProtocol)?;
+        protocol.enabled = enabled;
+        let allocation_weight_bps = protocol.allocation_weight_bps;
+        let target_allocation_bps = protocol.target_allocation_bps;
+
+        require!(
+            vault_state.enabled_target_allocation_bps() <= 10_000,
+            CustomError::AllocationLimitExceeded
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.last_updated = now;
+
+        msg!("🔧 Protocol {} enabled: {}", protocol_id, enabled);
+
+        emit!(ProtocolConfigUpdated {
+            vault_id: vault_state.vault_id,
+            protocol_id,
+            enabled,
+            allocation_weight_bps,
+            target_allocation_bps,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// 校验并关闭已到期的待执行变更
+    fn consume_queued_change(&self, change: PendingChangeData) -> Result<()> {
+        consume_pending_change(
+            self.global_state.timelock_delay_seconds,
+            self.pending_change.as_deref(),
+            self.proposer.as_deref(),
+            &self.vault_state.key(),
+            &change,
+            &self.admin.key(),
+        )
+    }
+}
@@ -0,0 +1,146 @@
+use crate::constants::REBALANCE_CONFIG_SEED;
+use crate::error::*;
+use crate::events::RebalanceConfigUpdated;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// 创建 Vault 的再平衡配置
+/// 只有 Vault admin 可以调用
+#[derive(Accounts)]
+pub struct InitializeRebalanceConfig<'info> {
+    #[account(
+        mut,
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = RebalanceConfig::space(),
+        seeds = [REBALANCE_CONFIG_SEED, vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub rebalance_config: Account<'info, RebalanceConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeRebalanceConfig<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        deviation_threshold_bps: u16,
+        min_rebalance_amount: u64,
+        max_rebalance_amount: u64,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        RebalanceConfig::validate_params(
+            deviation_threshold_bps,
+            min_rebalance_amount,
+            max_rebalance_amount,
+            cooldown_seconds,
+        )?;
+
+        let config = &mut ctx.accounts.rebalance_config;
+        config.vault_id = ctx.accounts.vault_state.vault_id;
+        config.deviation_threshold_bps = deviation_threshold_bps;
+        config.min_rebalance_amount = min_rebalance_amount;
+        config.max_rebalance_amount = max_rebalance_amount;
+        config.cooldown_seconds = cooldown_seconds;
+        config.last_rebalance_time = 0;
+        config.bump = ctx.bumps.rebalance_config;
+
+        msg!("✅ Rebalance config created");
+        msg!("  Deviation threshold: {} bps", deviation_threshold_bps);
+        msg!("  Amount range: {} - {}", min_rebalance_amount, max_rebalance_amount);
+        msg!("  Cooldown: {}s", cooldown_seconds);
+
+        emit!(RebalanceConfigUpdated {
+            vault_id: config.vault_id,
+            deviation_threshold_bps,
+            min_rebalance_amount,
+            max_rebalance_amount,
+            cooldown_seconds,
+            updated_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// 更新 Vault 的再平衡配置
+/// 只有 Vault admin 可以调用（创建 Vault 的 curator 即为其 admin），未传入的参数保持不变
+#[derive(Accounts)]
+pub struct UpdateRebalanceConfig<'info> {
+    #[account(
+        constraint = vault_state.admin == admin.key() @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [REBALANCE_CONFIG_SEED, vault_state.vault_id.as_ref()],
+        bump = rebalance_config.bump
+    )]
+    pub rebalance_config: Account<'info, RebalanceConfig>,
+}
+
+impl UpdateRebalanceConfig<'_> {
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        deviation_threshold_bps: Option<u16>,
+        min_rebalance_amount: Option<u64>,
+        max_rebalance_amount: Option<u64>,
+        cooldown_seconds: Option<i64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.rebalance_config;
+
+        let deviation_threshold_bps =
+            deviation_threshold_bps.unwrap_or(config.deviation_threshold_bps);
+        let min_rebalance_amount = min_rebalance_amount.unwrap_or(config.min_rebalance_amount);
+        let max_rebalance_amount = max_rebalance_amount.unwrap_or(config.max_rebalance_amount);
+        let cooldown_seconds = cooldown_seconds.unwrap_or(config.cooldown_seconds);
+
+        RebalanceConfig::validate_params(
+            deviation_threshold_bps,
+            min_rebalance_amount,
+            max_rebalance_amount,
+            cooldown_seconds,
+        )?;
+
+        config.deviation_threshold_bps = deviation_threshold_bps;
+        config.min_rebalance_amount = min_rebalance_amount;
+        config.max_rebalance_amount = max_rebalance_amount;
+        config.cooldown_seconds = cooldown_seconds;
+
+        msg!("✅ Rebalance config updated");
+        msg!("  Deviation threshold: {} bps", deviation_threshold_bps);
+        msg!("  Amount range: {} - {}", min_rebalance_amount, max_rebalance_amount);
+        msg!("  Cooldown: {}s", cooldown_seconds);
+
+        emit!(RebalanceConfigUpdated {
+            vault_id: config.vault_id,
+            deviation_threshold_bps,
+            min_rebalance_amount,
+            max_rebalance_amount,
+            cooldown_seconds,
+            updated_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::constants::{GLOBAL_AUTHORITY_SEED, MAX_STATUS_REASON_LENGTH, ROLE_REGISTRY_SEED};
+use crate::error::*;
+use crate::events::{EmergencyEvent, EmergencyEventType};
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// 更新 Vault 状态（暂停 / 恢复 / 紧急模式 / 关闭）
+///
+/// - 管理员可以执行所有状态转换
+/// - Guardian（全局 freeze authority 或 guardian 角色）只能暂停或进入紧急模式
+#[derive(Accounts)]
+pub struct UpdateVaultStatus<'info> {
+    /// 管理员或 guardian
+    pub authority: Signer<'info>,
+
+    /// 全局 freeze / thaw authority 列表
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED],
+        bump,
+    )]
+    pub global_state_authority: Box<Account<'info, GlobalStateAuthority>>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl UpdateVaultStatus<'_> {
+    /// 暂停：停止存款和再平衡，仍允许提款
+    pub fn pause_vault(ctx: Context<Self>, reason: String) -> Result<()> {
+        Self::transition(ctx, VaultStatus::Paused, EmergencyEventType::Pause, reason, true)
+    }
+
+    /// 恢复为 Active（仅管理员）
+    pub fn resume_vault(ctx: Context<Self>, reason: String) -> Result<()> {
+        Self::transition(ctx, VaultStatus::Active, EmergencyEventType::Resume, reason, false)
+    }
+
+    /// 进入紧急模式：停止所有常规用户操作
+    pub fn enter_emergency_mode(ctx: Context<Self>, reason: String) -> Result<()> {
+        Self::transition(
+            ctx,
+            VaultStatus::Emergency,
+            EmergencyEventType::EnterEmergency,
+            reason,
+            true,
+        )
+    }
+
+    /// 关闭金库（仅管理员，所有份额必须已赎回）
+    pub fn close_vault(ctx: Context<Self>, reason: String) -> Result<()> {
+        require!(ctx.accounts.vault_state.total_shares == 0, CustomError::VaultNotEmpty);
+        Self::transition(ctx, VaultStatus::Closed, EmergencyEventType::Close, reason, false)
+    }
+
+    fn transition(
+        ctx: Context<Self>,
+        next: VaultStatus,
+        event_type: EmergencyEventType,
+        reason: String,
+        guardian_allowed: bool,
+    ) -> Result<()> {
+        require!(reason.len() <= MAX_STATUS_REASON_LENGTH, CustomError::InvalidParameter);
+
+        let authority = ctx.accounts.authority.key();
+        let is_admin = authority == ctx.accounts.vault_state.admin;
+        let is_guardian = ctx.accounts.global_state_authority.freeze_authority.contains(&authority)
+            || ctx
+                .accounts
+                .role_registry
+                .as_ref()
+                .is_some_and(|r| r.has_role(Role::Guardian, &authority));
+        require!(is_admin || (guardian_allowed && is_guardian), CustomError::InvalidAuthority);
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        require!(vault_state.status.can_transition_to(&next), CustomError::InvalidStatusTransition);
+
+        let now = Clock::get()?.unix_timestamp;
+        vault_state.status = next;
+        vault_state.last_updated = now;
+
+        msg!("🚦 Vault status updated by {}: {}", authority, reason);
+
+        emit!(EmergencyEvent {
+            vault_id: vault_state.vault_id,
+            event_type,
+            reason,
+            executor: authority,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use crate::constants::{MAX_STATUS_REASON_LENGTH, PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO};
+use crate::error::*;
+use crate::events::{EmergencyEvent, EmergencyEventType};
+use crate::instructions::vault::position_valuation::*;
+use crate::instructions::{
+    invoke_jupiter_lend_redeem, invoke_kamino_withdraw, JupiterLendWithdrawAccounts,
+    KaminoWithdrawAccounts,
+};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::token_interface::{
+    Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface,
+};
+
+/// 将所有协议持仓撤回 Treasury（仅管理员，Vault 需处于 Paused 或 Emergency 状态）
+///
+/// 撤回后用户可以通过 `emergency_withdraw` 按比例赎回全部资产
+/// 没有持仓（current_allocation 为 0）的协议会被跳过，可以省略对应账户；
+/// 收益凭证账户必须是注册表绑定的账户，全部赎回后才清空该协议的账面持仓
+#[derive(Accounts)]
+pub struct UnwindAllPositions<'info> {
+    /// Vault 管理员
+    #[account(
+        mut,
+        constraint = admin.key() == vault_state.admin @ CustomError::OnlyAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    /// Vault 状态
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+        constraint = matches!(vault_state.status, VaultStatus::Paused | VaultStatus::Emergency)
+            @ CustomError::InvalidVaultState
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// Mars Vault 的代币金库（接收撤回的资金）
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022）
+    #[account(
+        mut,
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, BaseMint>>,
+
+    // === Kamino（没有持仓时可省略） ===
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_vault_state: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_token_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_base_vault_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证，并与 vault_kamino_shares.mint 比对
+    #[account(mut)]
+    pub kamino_shares_mint: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 持有的 Kamino 份额账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_kamino_shares.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_KAMINO, &vault_kamino_shares.key())
+            @ CustomError::PositionAccountMismatch
+    )]
+    pub vault_kamino_shares: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: Klend 程序
+    #[account(address = crate::constants::protocols::kamino::LEND_PROGRAM_ID)]
+    pub klend_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_event_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 硬编码程序 ID
+    #[account(address = crate::constants::protocols::kamino::PROGRAM_ID)]
+    pub kamino_program: Option<UncheckedAccount<'info>>,
+
+    // === Jupiter Lend（没有持仓时可省略） ===
+    /// CHECK: 由 Jupiter Lend 程序验证
+    pub jupiter_lending_admin: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Jupiter Lend 程序验证
+    #[account(mut)]
+    pub jupiter_lending: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Jupiter Lend 程序验证，并与 vault_jupiter_f_token.mint 比对
+    #[account(mut)]
+    pub jupiter_f_token_mint: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 持有的 jlToken 账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_jupiter_f_token.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_JUPITER_LEND, &vault_jupiter_f_token.key())
+            @ CustomError::PositionAccountMismatch
+    )]
+    pub vault_jupiter_f_token: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: 硬编码程序 ID
+    #[account(address = crate::constants::protocols::jupiter::LEND_PROGRAM_ID)]
+    pub jupiter_lend_program: Option<UncheckedAccount<'info>>,
+
+    /// 协议份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> UnwindAllPositions<'info> {
+    /// remaining_accounts: 前 kamino_accounts_len 个为 Kamino 的 reserves / lending markets，
+    /// 其余为 Jupiter Lend 所需的账户
+    pub fn process_instruction(
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        kamino_accounts_len: u8,
+        reason: String,
+    ) -> Result<()> {
+        require!(reason.len() <= MAX_STATUS_REASON_LENGTH, CustomError::InvalidParameter);
+        require!(
+            (kamino_accounts_len as usize) <= ctx.remaining_accounts.len(),
+            CustomError::InvalidParameter
+        );
+        let (kamino_remaining, jupiter_remaining) =
+            ctx.remaining_accounts.split_at(kamino_accounts_len as usize);
+
+        // 只能撤回 Kamino / Jupiter Lend，其他协议仍有持仓时拒绝，避免账面持仓被直接清空
+        require!(
+            ctx.accounts.vault_state.supported_protocols.iter().all(|p| {
+                matches!(p.protocol_id, PROTOCOL_KAMINO | PROTOCOL_JUPITER_LEND)
+                    || p.current_allocation == 0
+            }),
+            CustomError::UnsupportedProtocol
+        );
+
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        let bump_seed = [ctx.accounts.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        // 1. 赎回全部 Kamino 份额（没有持仓时跳过）
+        let kamino_allocation = ctx.accounts.allocation_of(PROTOCOL_KAMINO);
+        let mut kamino_received = 0;
+        if kamino_allocation > 0 {
+            let kamino_accounts = ctx.accounts.kamino_withdraw_accounts()?;
+            let kamino_shares =
+                required_position_account(&ctx.accounts.vault_kamino_shares)?.amount;
+            if kamino_shares > 0 {
+                let treasury_before = ctx.accounts.vault_treasury.amount;
+                invoke_kamino_withdraw(
+                    &kamino_accounts,
+                    kamino_remaining,
+                    kamino_shares,
+                    signer_seeds,
+                )?;
+                ctx.accounts.vault_treasury.reload()?;
+                kamino_received = ctx
+                    .accounts
+                    .vault_treasury
+                    .amount
+                    .checked_sub(treasury_before)
+                    .ok_or(CustomError::CpiCallFailed)?;
+            }
+            ctx.accounts.clear_redeemed_position(PROTOCOL_KAMINO)?;
+        }
+
+        // 2. 按 jlToken 余额全部赎回 Jupiter Lend 持仓（包括账面之外的收益，没有持仓时跳过）
+        let jupiter_allocation = ctx.accounts.allocation_of(PROTOCOL_JUPITER_LEND);
+        let mut jupiter_received = 0;
+        if jupiter_allocation > 0 {
+            let jupiter_accounts = ctx.accounts.jupiter_withdraw_accounts()?;
+            let f_token_balance =
+                required_position_account(&ctx.accounts.vault_jupiter_f_token)?.amount;
+            if f_token_balance > 0 {
+                let treasury_before = ctx.accounts.vault_treasury.amount;
+                invoke_jupiter_lend_redeem(
+                    &jupiter_accounts,
+                    jupiter_remaining,
+                    f_token_balance,
+                    signer_seeds,
+                )?;
+                ctx.accounts.vault_treasury.reload()?;
+                jupiter_received = ctx
+                    .accounts
+                    .vault_treasury
+                    .amount
+                    .checked_sub(treasury_before)
+                    .ok_or(CustomError::CpiCallFailed)?;
+            }
+            ctx.accounts.clear_redeemed_position(PROTOCOL_JUPITER_LEND)?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.last_updated = now;
+
+        msg!(
+            "🚨 Unwound all positions: kamino {} -> {}, jupiter {} -> {}",
+            kamino_allocation,
+            kamino_received,
+            jupiter_allocation,
+            jupiter_received
+        );
+
+        emit!(EmergencyEvent {
+            vault_id,
+            event_type: EmergencyEventType::UnwindAll,
+            reason,
+            executor: ctx.accounts.admin.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    fn allocation_of(&self, protocol_id: u8) -> u64 {
+        self.vault_state.find_protocol(protocol_id).map(|p| p.current_allocation).unwrap_or(0)
+    }
+
+    /// 确认收益凭证已全部赎回后清空该协议的账面持仓
+    fn clear_redeemed_position(&mut self, protocol_id: u8) -> Result<()> {
+        let receipt = match protocol_id {
+            PROTOCOL_KAMINO => self.vault_kamino_shares.as_mut(),
+            _ => self.vault_jupiter_f_token.as_mut(),
+        }
+        .ok_or(CustomError::PositionAccountsRequired)?;
+        receipt.reload()?;
+        require!(receipt.amount == 0, CustomError::InsufficientLiquidity);
+
+        if let Some(protocol) = self.vault_state.find_protocol_mut(protocol_id) {
+            protocol.current_allocation = 0;
+        }
+        Ok(())
+    }
+
+    fn kamino_withdraw_accounts(&self) -> Result<KaminoWithdrawAccounts<'info>> {
+        let vault_kamino_shares = required_position_account(&self.vault_kamino_shares)?;
+        self.vault_state.require_receipt_account(PROTOCOL_KAMINO, &vault_kamino_shares.key())?;
+        let shares_mint = required_position_account(&self.kamino_shares_mint)?;
+        require_keys_eq!(vault_kamino_shares.mint, shares_mint.key(), CustomError::InvalidMint);
+
+        Ok(KaminoWithdrawAccounts {
+            user: self.vault_state.to_account_info(),
+            vault_state: required_position_account(&self.kamino_vault_state)?.to_account_info(),
+            token_vault: required_position_account(&self.kamino_token_vault)?.to_account_info(),
+            base_vault_authority: required_position_account(&self.kamino_base_vault_authority)?
+                .to_account_info(),
+            user_token_ata: self.vault_treasury.to_account_info(),
+            token_mint: self.base_token_mint.to_account_info(),
+            user_shares_ata: vault_kamino_shares.to_account_info(),
+            shares_mint: shares_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            shares_token_program: self.token_program.to_account_info(),
+            klend_program: required_position_account(&self.klend_program)?.to_account_info(),
+            event_authority: required_position_account(&self.kamino_event_authority)?
+                .to_account_info(),
+            kamino_vault_program: required_position_account(&self.kamino_program)?
+                .to_account_info(),
+        })
+    }
+
+    fn jupiter_withdraw_accounts(&self) -> Result<JupiterLendWithdrawAccounts<'info>> {
+        let vault_f_token = required_position_account(&self.vault_jupiter_f_token)?;
+        self.vault_state.require_receipt_account(PROTOCOL_JUPITER_LEND, &vault_f_token.key())?;
+        let f_token_mint = required_position_account(&self.jupiter_f_token_mint)?;
+        require_keys_eq!(vault_f_token.mint, f_token_mint.key(), CustomError::InvalidMint);
+
+        Ok(JupiterLendWithdrawAccounts {
+            signer: self.vault_state.to_account_info(),
+            recipient_token_account: vault_f_token.to_account_info(),
+            depositor_token_account: self.vault_treasury.to_account_info(),
+            lending_admin: required_position_account(&self.jupiter_lending_admin)?
+                .to_account_info(),
+            lending: required_position_account(&self.jupiter_lending)?.to_account_info(),
+            mint: self.base_token_mint.to_account_info(),
+            f_token_mint: f_token_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            jupiter_lend_program: required_position_account(&self.jupiter_lend_program)?
+                .to_account_info(),
+        })
+    }
+}
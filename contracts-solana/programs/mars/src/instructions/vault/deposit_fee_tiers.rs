@@ -0,0 +1,152 @@
+use crate::constants::{
+    ASSET_SEED, FEE_TIERS_SEED, GLOBAL_SEED, INSURANCE_FEE_TIERS_SEED, PROTOCOL_FEE_FRACTION_SEED,
+    VAULT_SEED,
+};
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TransferChecked};
+
+/// 存款分层费用所需的账户
+///
+/// GlobalState.deposit_fee_tiers_enabled 开启后，基础代币为全局 base_mint 的 Vault
+/// 按 FeeTiers / InsuranceFeeTiers 收取存款费，费用转入 ata_vault 并记入 Asset；
+/// 其余情况仍使用 Vault 自身的 deposit_fee_bps，可选账户可以省略。
+#[derive(Accounts)]
+pub struct DepositFeeTierAccounts<'info> {
+    /// 全局状态（分层存款费开关）
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 按金额规模的费率档位
+    #[account(
+        seeds = [FEE_TIERS_SEED],
+        bump
+    )]
+    pub fee_tiers: Option<Box<Account<'info, FeeTiers>>>,
+
+    /// 按金额规模的保险费率档位
+    #[account(
+        seeds = [INSURANCE_FEE_TIERS_SEED],
+        bump
+    )]
+    pub insurance_fee_tiers: Option<Box<Account<'info, InsuranceFeeTiers>>>,
+
+    /// 全局费用账本
+    #[account(
+        mut,
+        seeds = [ASSET_SEED],
+        bump
+    )]
+    pub asset: Option<Box<Account<'info, Asset>>>,
+
+    /// 协议分成比例（拆分 Asset 的 protocol / base 费用）
+    #[account(
+        seeds = [PROTOCOL_FEE_FRACTION_SEED],
+        bump
+    )]
+    pub protocol_fee_fraction: Option<Box<Account<'info, ProtocolFeeFraction>>>,
+
+    /// 全局 vault PDA（ata_vault 的 authority）
+    /// CHECK: 只校验地址
+    #[account(
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    pub vault: Option<UncheckedAccount<'info>>,
+
+    /// 全局 vault 的 base_mint 关联代币账户（接收分层存款费，与 CreateOrder / ClaimAssetFee 为同一账户）
+    #[account(mut)]
+    pub ata_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// 全局 base_mint
+    #[account(address = global_state.base_mint @ CustomError::InvalidMint)]
+    pub base_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+}
+
+impl<'info> DepositFeeTierAccounts<'info> {
+    /// 计算存款费：返回（总费用，启用分层时的费用明细）
+    pub fn deposit_fee(
+        &self,
+        vault_state: &VaultState,
+        amount: u64,
+    ) -> Result<(u64, Option<TieredFee>)> {
+        if !self.global_state.deposit_fee_tiers_enabled
+            || vault_state.base_token_mint != self.global_state.base_mint
+        {
+            return Ok((vault_state.deposit_fee_for(amount)?, None));
+        }
+
+        let fee_tiers = self.fee_tiers.as_deref().ok_or(CustomError::FeeTierAccountsRequired)?;
+        let insurance_fee_tiers =
+            self.insurance_fee_tiers.as_deref().ok_or(CustomError::FeeTierAccountsRequired)?;
+        let tiered_fee = TieredFee::compute(fee_tiers, insurance_fee_tiers, amount)
+            .ok_or(CustomError::MathOverflow)?;
+        let total = tiered_fee.total().ok_or(CustomError::MathOverflow)?;
+
+        Ok((total, Some(tiered_fee)))
+    }
+
+    /// 将分层存款费从 Vault Treasury 转入 ata_vault（vault_state PDA 签名），并记入 Asset
+    pub fn collect(
+        &mut self,
+        tiered_fee: &TieredFee,
+        vault_treasury: AccountInfo<'info>,
+        vault_state: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let total = tiered_fee.total().ok_or(CustomError::MathOverflow)?;
+        if total == 0 {
+            return Ok(());
+        }
+
+        let vault = self.vault.as_ref().ok_or(CustomError::FeeTierAccountsRequired)?;
+        let ata_vault = self.ata_vault.as_ref().ok_or(CustomError::FeeTierAccountsRequired)?;
+        let base_mint = self.base_mint.as_ref().ok_or(CustomError::FeeTierAccountsRequired)?;
+        // 只能记入规范的关联代币账户，claim_asset_fee 从该账户支付 Asset 中记录的费用
+        require_keys_eq!(
+            ata_vault.key(),
+            get_associated_token_address_with_program_id(
+                &vault.key(),
+                &base_mint.key(),
+                token_program.key
+            ),
+            CustomError::InvalidTokenAccount
+        );
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            token_program,
+            TransferChecked {
+                from: vault_treasury,
+                mint: base_mint.to_account_info(),
+                to: ata_vault.to_account_info(),
+                authority: vault_state,
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(transfer_ctx, total, base_mint.decimals)?;
+
+        let protocol_fee_fraction =
+            self.protocol_fee_fraction.as_deref().ok_or(CustomError::FeeTierAccountsRequired)?;
+        self.asset.as_mut().ok_or(CustomError::FeeTierAccountsRequired)?.record_fee(
+            total,
+            tiered_fee.insurance_fee,
+            protocol_fee_fraction,
+        )?;
+
+        msg!(
+            "🏷️ Tiered deposit fee: fee={} ({} bps), insurance={} ({} bps)",
+            tiered_fee.fee,
+            tiered_fee.fee_bps,
+            tiered_fee.insurance_fee,
+            tiered_fee.insurance_fee_bps
+        );
+
+        Ok(())
+    }
+}
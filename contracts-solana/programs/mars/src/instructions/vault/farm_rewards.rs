@@ -45,6 +45,19 @@ pub struct ClaimFarmRewards<'info> {
     )]
     pub vault_state: Box<Account<'info, VaultState>>,
 
+    /// 用户的独立持仓账户（记录累计领取的奖励）
+    #[account(
+        mut,
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key() @ MarsError::InvalidOwner,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
     /// Vault mint (PYUSD) - the base token for this vault
     /// CHECK: Should match vault_state.base_token_mint
     pub vault_mint: AccountInfo<'info>,
@@ -116,6 +129,7 @@ impl<'info> ClaimFarmRewards<'info> {
         // Validate global_state and vault_state are properly initialized
         require!(ctx.accounts.global_state.admin != Pubkey::default(), MarsError::OnlyAdmin);
         require!(ctx.accounts.vault_state.admin != Pubkey::default(), MarsError::InvalidAdmin);
+        ctx.accounts.vault_state.require_not_closed()?;
 
         // 验证 platform_fee_ata 的所有权
         // 读取 platform_fee_ata 的 owner 字段（偏移量 32，即 mint 之后）
@@ -286,6 +300,9 @@ impl<'info> ClaimFarmRewards<'info> {
         ctx.accounts.vault_state.total_platform_fee_collected =
             ctx.accounts.vault_state.total_platform_fee_collected.saturating_add(platform_fee);
 
+        // 记录用户实际到账的奖励
+        ctx.accounts.user_position.record_rewards_claimed(user_reward_after_fee)?;
+
         msg!("🎉 Claim farm rewards completed!");
         msg!(
             "  Total rewards claimed (lifetime): {}",
@@ -0,0 +1,70 @@
+use crate::error::*;
+use crate::instructions::vault::position_valuation::*;
+use crate::state::*;
+use crate::utils::FeeEngine;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::TokenAccount as BaseTokenAccount;
+
+/// 管理费 / 性能费结算 crank（任何人都可以调用）
+#[derive(Accounts)]
+pub struct AccrueFees<'info> {
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Mars Vault 的代币金库
+    #[account(
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Account<'info, TokenAccount>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl AccrueFees<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = ctx.accounts.vault_state.key();
+        ctx.accounts
+            .position_valuation
+            .mark_to_market(&mut ctx.accounts.vault_state, &vault_key)?;
+
+        let settlement = FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        if settlement.management.fee_shares == 0 && settlement.performance.fee_amount == 0 {
+            msg!("ℹ️ No fees to accrue");
+        }
+
+        Ok(())
+    }
+}
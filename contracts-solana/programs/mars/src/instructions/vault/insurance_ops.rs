@@ -0,0 +1,331 @@
+use crate::constants::{
+    GLOBAL_SEED, INSURANCE_TREASURY_SEED, ROLE_REGISTRY_SEED, SHORTFALL_SEED, VAULT_SEED,
+};
+use crate::error::*;
+use crate::events::{ShortfallCovered, ShortfallDetected};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface::{
+    self, Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface, TransferChecked,
+};
+
+/// 创建保险金库（PDA: [INSURANCE_TREASURY_SEED]，全局 vault PDA 为 authority）
+///
+/// 资金来源为 Asset 的 insurance 费用桶（`claim_insurance_fee` 只能转入此账户）
+#[derive(Accounts)]
+pub struct InitializeInsuranceTreasury<'info> {
+    /// 全局 admin
+    #[account(
+        mut,
+        constraint = admin.key() == global_state.admin @ CustomError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    /// 全局状态
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// 全局 vault PDA（保险金库的 authority）
+    /// CHECK: 只校验地址
+    #[account(
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// 保险金库
+    #[account(
+        init,
+        payer = admin,
+        seeds = [INSURANCE_TREASURY_SEED],
+        bump,
+        token::mint = base_mint,
+        token::authority = vault,
+        token::token_program = base_token_program
+    )]
+    pub insurance_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// 全局 base_mint（USDC）
+    #[account(
+        address = global_state.base_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_mint: Box<InterfaceAccount<'info, BaseMint>>,
+
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeInsuranceTreasury<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        msg!(
+            "🛡️ Insurance treasury initialized: {}, mint={}",
+            ctx.accounts.insurance_treasury.key(),
+            ctx.accounts.base_mint.key()
+        );
+
+        Ok(())
+    }
+}
+
+/// 检测协议亏损（keeper）
+///
+/// 按协议自身汇率（注册表绑定的汇率来源和收益凭证账户）重新计价协议持仓，
+/// 记录重新计价确认的未恢复亏损（用户操作先行计价时亏损同样已被记录）
+#[derive(Accounts)]
+pub struct DetectShortfall<'info> {
+    /// 管理员或 keeper（支付 ShortfallRecord 租金）
+    #[account(
+        mut,
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Keeper, &global_state.admin, &detector.key())
+            @ CustomError::UnauthorizedSigner
+    )]
+    pub detector: Signer<'info>,
+
+    /// 全局状态
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// Vault 状态
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 亏损记录
+    #[account(
+        init_if_needed,
+        payer = detector,
+        space = ShortfallRecord::space(),
+        seeds = [SHORTFALL_SEED, vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub shortfall_record: Box<Account<'info, ShortfallRecord>>,
+
+    /// 收益凭证汇率来源（须与 ProtocolConfig.rate_source 一致）
+    /// CHECK: 在 VaultState::protocol_exchange_rate 中校验地址、owner 和 mint
+    pub rate_source: UncheckedAccount<'info>,
+
+    /// Vault 持有的协议收益凭证账户（须与 ProtocolConfig.receipt_account 一致）
+    #[account(
+        constraint = receipt_token_account.owner == vault_state.key() @ CustomError::InvalidOwner
+    )]
+    pub receipt_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl DetectShortfall<'_> {
+    pub fn process_instruction(ctx: Context<Self>, protocol_id: u8) -> Result<()> {
+        let recorded_allocation = ctx
+            .accounts
+            .vault_state
+            .find_protocol(protocol_id)
+            .ok_or(CustomError::UnsupportedProtocol)?
+            .current_allocation;
+
+        // 1. 按协议汇率重新计价持仓，价值下跌计入 unrecovered_loss
+        let receipt_key = ctx.accounts.receipt_token_account.key();
+        let measured_value = ctx.accounts.vault_state.mark_to_market(
+            protocol_id,
+            &ctx.accounts.rate_source,
+            &receipt_key,
+            &ctx.accounts.receipt_token_account,
+        )?;
+        let unrecovered_loss = ctx
+            .accounts
+            .vault_state
+            .find_protocol(protocol_id)
+            .ok_or(CustomError::UnsupportedProtocol)?
+            .unrecovered_loss;
+
+        // 2. 记录缺口（每次检测覆盖上一次的结果）
+        let now = Clock::get()?.unix_timestamp;
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        ctx.accounts.vault_state.last_updated = now;
+        let record = &mut ctx.accounts.shortfall_record;
+        record.vault_id = vault_id;
+        record.bump = ctx.bumps.shortfall_record;
+        let shortfall = record.record(protocol_id, measured_value, unrecovered_loss, now);
+
+        emit!(ShortfallDetected {
+            vault_id,
+            protocol_id,
+            recorded_allocation,
+            measured_value,
+            shortfall,
+            detector: ctx.accounts.detector.key(),
+            timestamp: now,
+        });
+
+        msg!(
+            "🔍 Shortfall check: protocol={}, recorded={}, measured={}, shortfall={}",
+            protocol_id,
+            recorded_allocation,
+            measured_value,
+            shortfall
+        );
+
+        Ok(())
+    }
+}
+
+/// 用保险金库补偿协议亏损（guardian）
+///
+/// 亏损在重新计价时已计入 NAV；保险金转入 Vault Treasury 补回 NAV，并等额减记未恢复的亏损
+#[derive(Accounts)]
+pub struct CoverShortfall<'info> {
+    /// 管理员或 guardian
+    #[account(
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Guardian, &global_state.admin, &executor.key())
+            @ CustomError::UnauthorizedSigner
+    )]
+    pub executor: Signer<'info>,
+
+    /// 全局状态
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    /// Vault 状态（基础代币必须是全局 base_mint）
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.base_token_mint == global_state.base_mint @ CustomError::InvalidMint
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 亏损记录
+    #[account(
+        mut,
+        seeds = [SHORTFALL_SEED, vault_state.vault_id.as_ref()],
+        bump = shortfall_record.bump
+    )]
+    pub shortfall_record: Box<Account<'info, ShortfallRecord>>,
+
+    /// Mars Vault 的代币金库（接收补偿）
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// 全局 vault PDA（保险金库的 authority）
+    /// CHECK: 只校验地址
+    #[account(
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// 保险金库
+    #[account(
+        mut,
+        seeds = [INSURANCE_TREASURY_SEED],
+        bump
+    )]
+    pub insurance_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// 基础代币 Mint
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, BaseMint>>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+}
+
+impl CoverShortfall<'_> {
+    pub fn process_instruction(ctx: Context<Self>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.insurance_treasury.amount >= amount,
+            CustomError::InsufficientInsuranceFunds
+        );
+
+        // 1. 减记未恢复的亏损（检测之后的用户操作计价不影响补偿额度）
+        let protocol_id = ctx.accounts.shortfall_record.protocol_id;
+        let protocol = ctx
+            .accounts
+            .vault_state
+            .find_protocol_mut(protocol_id)
+            .ok_or(CustomError::UnsupportedProtocol)?;
+        protocol.cover_loss(amount)?;
+        let remaining_loss = protocol.unrecovered_loss;
+        ctx.accounts.shortfall_record.record_cover(amount, remaining_loss)?;
+
+        // 2. 保险金转入 Vault Treasury（全局 vault PDA 签名）
+        let vault_bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &vault_bump]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.insurance_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
+                to: ctx.accounts.vault_treasury.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(
+            transfer_ctx,
+            amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
+        ctx.accounts.insurance_treasury.reload()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.last_updated = now;
+        let remaining_shortfall = ctx.accounts.shortfall_record.shortfall;
+
+        emit!(ShortfallCovered {
+            vault_id: vault_state.vault_id,
+            protocol_id,
+            covered_amount: amount,
+            remaining_shortfall,
+            insurance_balance: ctx.accounts.insurance_treasury.amount,
+            executor: ctx.accounts.executor.key(),
+            timestamp: now,
+        });
+
+        msg!(
+            "🛡️ Shortfall covered: protocol={}, amount={}, remaining={}, insurance_balance={}",
+            protocol_id,
+            amount,
+            remaining_shortfall,
+            ctx.accounts.insurance_treasury.amount
+        );
+
+        Ok(())
+    }
+}
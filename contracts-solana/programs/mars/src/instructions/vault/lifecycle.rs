@@ -1,4 +1,4 @@
-use crate::constants::GLOBAL_SEED;
+use crate::constants::{GLOBAL_SEED, ROLE_REGISTRY_SEED};
 use crate::error::MarsError;
 use crate::state::*;
 use anchor_lang::prelude::*;
@@ -7,10 +7,11 @@ use anchor_spl::token::Token;
 #[derive(Accounts)]
 #[instruction(vault_id: [u8; 32])]
 pub struct InitializeVault<'info> {
-    /// 管理员账户 - 必须是 global admin
+    /// 管理员账户 - 必须是 global admin 或 curator（成为该 Vault 的 admin）
     #[account(
         mut,
-        constraint = admin.key() == global_state.admin @ MarsError::OnlyAdmin,
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Curator, &global_state.admin, &admin.key())
+            @ MarsError::OnlyAdmin,
     )]
     pub admin: Signer<'info>,
 
@@ -51,6 +52,13 @@ pub struct InitializeVault<'info> {
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
 }
 
 impl<'info> InitializeVault<'info> {
@@ -111,8 +119,14 @@ impl<'info> InitializeVault<'info> {
         vault_state.total_rewards_claimed = 0;
         vault_state.total_platform_fee_collected = 0;
 
+        vault_state.last_fee_accrual_time = vault_state.created_at;
+        vault_state.high_water_mark = crate::utils::FeeEngine::SHARE_PRICE_PRECISION;
+
         // 保留字段
-        vault_state.reserved = [0u8; 48];
+        vault_state.max_total_deposits = 0;
+        vault_state.max_user_deposit = 0;
+        vault_state.min_deposit_amount = 0;
+        vault_state.max_deposit_amount = 0;
         msg!("  Platform fee: {} bps ({}%)", platform_fee_bps, platform_fee_bps as f64 / 100.0);
         Ok(())
     }
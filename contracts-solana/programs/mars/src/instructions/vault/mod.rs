@@ -3,22 +3,68 @@ pub mod lifecycle;
 pub use lifecycle::*;
 
 // === 用户操作 ===
+pub mod deposit_fee_tiers;
+pub use deposit_fee_tiers::*;
+
+pub mod position_valuation;
+pub use position_valuation::*;
+
 pub mod user_ops_deposit;
 pub use user_ops_deposit::*;
 
 pub mod user_ops_withdraw;
 pub use user_ops_withdraw::*;
 
+pub mod user_ops_migrate;
+pub use user_ops_migrate::*;
+
+pub mod user_ops_emergency_withdraw;
+pub use user_ops_emergency_withdraw::*;
+
+pub mod user_ops_withdrawal_queue;
+pub use user_ops_withdrawal_queue::*;
+
+pub mod user_ops_zap;
+pub use user_ops_zap::*;
+
+pub mod user_ops_zap_out;
+pub use user_ops_zap_out::*;
+
 // === 管理员操作 ===
 pub mod admin_ops_fees;
 pub use admin_ops_fees::*;
 
+pub mod admin_ops_fee_config;
+pub use admin_ops_fee_config::*;
+
 pub mod admin_ops_platform_fee;
 pub use admin_ops_platform_fee::*;
 
+pub mod admin_ops_deposit_limits;
+pub use admin_ops_deposit_limits::*;
+
 pub mod admin_ops_wallet;
 pub use admin_ops_wallet::*;
 
+pub mod admin_ops_rebalance;
+pub use admin_ops_rebalance::*;
+
+pub mod admin_ops_protocols;
+pub use admin_ops_protocols::*;
+
+pub mod admin_ops_status;
+pub use admin_ops_status::*;
+
+pub mod admin_ops_unwind;
+pub use admin_ops_unwind::*;
+
+pub mod admin_ops_authority;
+pub use admin_ops_authority::*;
+
+// === 费用结算 ===
+pub mod fee_ops_accrue;
+pub use fee_ops_accrue::*;
+
 // === Farm 奖励 ===
 pub mod farm_rewards;
 pub use farm_rewards::*;
@@ -26,3 +72,7 @@ pub use farm_rewards::*;
 // === 再平衡操作（新增） ===
 pub mod rebalance_ops;
 pub use rebalance_ops::*;
+
+// === 保险基金 ===
+pub mod insurance_ops;
+pub use insurance_ops::*;
@@ -0,0 +1,70 @@
+use crate::constants::{PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO};
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// 协议持仓估值所需的账户
+///
+/// 按 NAV 定价前，用协议自身的汇率（Kamino 份额价格、jlToken 兑换价格）重新计价持仓。
+/// 有持仓的协议必须传入注册表中绑定的汇率来源和收益凭证账户，没有持仓的协议可以省略。
+#[derive(Accounts)]
+pub struct PositionValuationAccounts<'info> {
+    /// Kamino Vault 状态（Kamino 份额价格来源）
+    /// CHECK: 与 ProtocolConfig.rate_source 比对，并校验 owner 为 Kamino 程序
+    pub kamino_vault_state: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 持有的 Kamino 份额账户
+    pub vault_kamino_shares: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Jupiter Lending 账户（jlToken 兑换价格来源）
+    /// CHECK: 与 ProtocolConfig.rate_source 比对，并校验 owner 为 Jupiter Lend 程序
+    pub jupiter_lending: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 持有的 jlToken 账户
+    pub vault_jupiter_f_token: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+impl PositionValuationAccounts<'_> {
+    /// 重新计价 Vault 在各协议中的持仓（更新 current_allocation）
+    pub fn mark_to_market(&self, vault_state: &mut VaultState, vault_key: &Pubkey) -> Result<()> {
+        let positions: Vec<(u8, u64)> = vault_state
+            .supported_protocols
+            .iter()
+            .map(|p| (p.protocol_id, p.current_allocation))
+            .collect();
+
+        for (protocol_id, book_value) in positions {
+            let accounts = match protocol_id {
+                PROTOCOL_KAMINO => {
+                    self.kamino_vault_state.as_ref().zip(self.vault_kamino_shares.as_ref())
+                }
+                PROTOCOL_JUPITER_LEND => {
+                    self.jupiter_lending.as_ref().zip(self.vault_jupiter_f_token.as_ref())
+                }
+                _ => None,
+            };
+
+            let Some((rate_source, receipt)) = accounts else {
+                require!(book_value == 0, CustomError::PositionAccountsRequired);
+                continue;
+            };
+            require_keys_eq!(receipt.owner, *vault_key, CustomError::InvalidOwner);
+
+            let value = vault_state.mark_to_market(
+                protocol_id,
+                &rate_source.to_account_info(),
+                &receipt.key(),
+                receipt,
+            )?;
+            msg!("📐 Protocol {} marked to market: {} -> {}", protocol_id, book_value, value);
+        }
+
+        Ok(())
+    }
+}
+
+/// 有持仓的协议必须传入对应账户
+pub fn required_position_account<T>(account: &Option<T>) -> Result<&T> {
+    account.as_ref().ok_or(error!(CustomError::PositionAccountsRequired))
+}
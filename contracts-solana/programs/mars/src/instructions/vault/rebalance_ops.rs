@@ -1,75 +1,224 @@
+use crate::constants::{
+    GLOBAL_SEED, PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO, REBALANCE_CONFIG_SEED, ROLE_REGISTRY_SEED,
+};
 use crate::error::*;
+use crate::events::{ProtocolApyUpdated, VaultDepositEvent};
+use crate::instructions::vault::user_ops_deposit::*;
+use crate::instructions::{
+    invoke_jupiter_lend_deposit, invoke_jupiter_lend_withdraw, invoke_kamino_deposit,
+    invoke_kamino_withdraw, JupiterLendDepositAccounts, JupiterLendWithdrawAccounts,
+    KaminoDepositAccounts, KaminoWithdrawAccounts,
+};
 use crate::state::*;
-use crate::utils::{PythOracle, RebalanceConfig, RebalanceEngine};
+use crate::utils::{FeeEngine, PositionValuation, RebalanceEngine};
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{
+    Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface,
+};
 
 /// 执行自动再平衡操作
 ///
 /// 该指令会：
 /// 1. 检查当前各协议的分配是否偏离目标
 /// 2. 如果偏差超过阈值，生成再平衡计划
-/// 3. 执行高优先级的再平衡动作
+/// 3. 在同一笔交易中从源协议提取并存入目标协议（Kamino ⇄ Jupiter Lend）
 #[derive(Accounts)]
 pub struct ExecuteRebalance<'info> {
-    /// 授权执行者（通常是管理员或自动化bot）
+    /// 授权执行者（管理员或 keeper）
     #[account(
-        constraint = executor.key() == global_state.admin @ CustomError::UnauthorizedSigner
+        mut,
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Keeper, &global_state.admin, &executor.key())
+            @ CustomError::UnauthorizedSigner
     )]
     pub executor: Signer<'info>,
 
     /// 全局状态
     #[account(
-        seeds = [b"global-state"],
+        seeds = [GLOBAL_SEED],
         bump,
         constraint = !global_state.frozen @ CustomError::GlobalStateFrozen
     )]
-    pub global_state: Account<'info, GlobalState>,
+    pub global_state: Box<Account<'info, GlobalState>>,
 
-    /// Vault 状态
+    /// Vault 状态（追加再平衡记录时扩容）
     #[account(
         mut,
         seeds = [b"vault-state", vault_state.vault_id.as_ref()],
         bump = vault_state.bump,
-        constraint = vault_state.status == VaultStatus::Active @ CustomError::VaultPaused
+        constraint = vault_state.status == VaultStatus::Active @ CustomError::VaultPaused,
+        realloc = vault_state.space_with_new_rebalance_record(),
+        realloc::payer = executor,
+        realloc::zero = false
     )]
-    pub vault_state: Account<'info, VaultState>,
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 再平衡配置（记录冷却时间）
+    #[account(
+        mut,
+        seeds = [REBALANCE_CONFIG_SEED, vault_state.vault_id.as_ref()],
+        bump = rebalance_config.bump
+    )]
+    pub rebalance_config: Box<Account<'info, RebalanceConfig>>,
+
+    /// Mars Vault 的代币金库（资金中转）
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022）
+    #[account(
+        mut,
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, BaseMint>>,
+
+    /// Mars Vault 份额 Mint（结算管理费时增发）
+    #[account(mut, address = vault_state.shares_mint @ CustomError::InvalidMint)]
+    pub shares_mint: Box<Account<'info, Mint>>,
+
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Box<Account<'info, TokenAccount>>,
+
+    // === Kamino ===
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_vault_state: AccountInfo<'info>,
+
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_token_vault: AccountInfo<'info>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_base_vault_authority: AccountInfo<'info>,
+
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_shares_mint: AccountInfo<'info>,
+
+    /// Mars Vault 持有的 Kamino 份额账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_kamino_shares.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_KAMINO, &vault_kamino_shares.key())
+            @ CustomError::PositionAccountMismatch,
+        constraint = vault_kamino_shares.mint == kamino_shares_mint.key() @ CustomError::InvalidMint
+    )]
+    pub vault_kamino_shares: Box<Account<'info, TokenAccount>>,
 
-    /// 再平衡配置（可选，使用默认值）
-    /// CHECK: 如果提供，将从账户读取配置
-    pub rebalance_config: Option<AccountInfo<'info>>,
+    /// CHECK: Klend 程序
+    #[account(address = crate::constants::protocols::kamino::LEND_PROGRAM_ID)]
+    pub klend_program: AccountInfo<'info>,
 
-    /// Pyth 价格账户（用于验证价格和计算APY）
-    /// CHECK: Pyth 程序验证
-    pub price_feed: Option<AccountInfo<'info>>,
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_event_authority: AccountInfo<'info>,
 
+    /// CHECK: 硬编码程序 ID
+    #[account(address = crate::constants::protocols::kamino::PROGRAM_ID)]
+    pub kamino_program: AccountInfo<'info>,
+
+    // === Jupiter Lend ===
+    /// CHECK: 由 Jupiter Lend 程序验证
+    pub jupiter_lending_admin: AccountInfo<'info>,
+
+    /// CHECK: 由 Jupiter Lend 程序验证
+    #[account(mut)]
+    pub jupiter_lending: AccountInfo<'info>,
+
+    /// CHECK: 由 Jupiter Lend 程序验证
+    #[account(mut)]
+    pub jupiter_f_token_mint: AccountInfo<'info>,
+
+    /// Mars Vault 持有的 jlToken 账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_jupiter_f_token.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_JUPITER_LEND, &vault_jupiter_f_token.key())
+            @ CustomError::PositionAccountMismatch,
+        constraint = vault_jupiter_f_token.mint == jupiter_f_token_mint.key() @ CustomError::InvalidMint
+    )]
+    pub vault_jupiter_f_token: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 硬编码程序 ID
+    #[account(address = crate::constants::protocols::jupiter::LEND_PROGRAM_ID)]
+    pub jupiter_lend_program: AccountInfo<'info>,
+
+    /// 份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
 }
 
-impl ExecuteRebalance<'_> {
+impl<'info> ExecuteRebalance<'info> {
+    /// remaining_accounts: 前 kamino_accounts_len 个为 Kamino 的 reserves / lending markets，
+    /// 其余为 Jupiter Lend 所需的账户
     pub fn process_instruction(
-        ctx: Context<Self>,
-        protocol_apys: Vec<(u8, u64)>, // (protocol_id, apy_bps)
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        kamino_accounts_len: u8,
     ) -> Result<()> {
         msg!("🔄 Starting rebalance check...");
 
-        // 1. 加载或使用默认再平衡配置
-        let config = if let Some(_config_account) = &ctx.accounts.rebalance_config {
-            // TODO: 从账户解析配置
-            RebalanceConfig::default()
-        } else {
-            RebalanceConfig::default()
-        };
-
-        // 2. 检查冷却期
         require!(
-            !config.is_in_cooldown()?,
-            CustomError::InvalidParameter // 可以添加专门的错误类型
+            (kamino_accounts_len as usize) <= ctx.remaining_accounts.len(),
+            CustomError::InvalidParameter
         );
+        let (kamino_remaining, jupiter_remaining) =
+            ctx.remaining_accounts.split_at(kamino_accounts_len as usize);
+
+        // 1. 加载再平衡配置并检查冷却期
+        let config = &ctx.accounts.rebalance_config;
+        require!(!config.is_in_cooldown()?, CustomError::RebalanceCooldownActive);
+
+        // 按协议汇率重新计价持仓，再平衡金额以市值为准
+        let kamino_shares_key = ctx.accounts.vault_kamino_shares.key();
+        let jupiter_f_token_key = ctx.accounts.vault_jupiter_f_token.key();
+        if ctx.accounts.vault_state.find_protocol(PROTOCOL_KAMINO).is_some() {
+            ctx.accounts.vault_state.mark_to_market(
+                PROTOCOL_KAMINO,
+                &ctx.accounts.kamino_vault_state,
+                &kamino_shares_key,
+                &ctx.accounts.vault_kamino_shares,
+            )?;
+        }
+        if ctx.accounts.vault_state.find_protocol(PROTOCOL_JUPITER_LEND).is_some() {
+            ctx.accounts.vault_state.mark_to_market(
+                PROTOCOL_JUPITER_LEND,
+                &ctx.accounts.jupiter_lending,
+                &jupiter_f_token_key,
+                &ctx.accounts.vault_jupiter_f_token,
+            )?;
+        }
+
+        // 结算管理费和性能费（再平衡不改变 NAV，但作为结算时点之一）
+        FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
 
-        // 3. 检查是否需要再平衡
+        // 2. 检查是否需要再平衡
         let vault_state = &ctx.accounts.vault_state;
         let (needs_rebalance, deviations) =
-            RebalanceEngine::check_rebalance_needed(vault_state, &config)?;
+            RebalanceEngine::check_rebalance_needed(vault_state, config)?;
 
         if !needs_rebalance {
             msg!("✅ No rebalance needed - all allocations within threshold");
@@ -78,9 +227,11 @@ impl ExecuteRebalance<'_> {
 
         msg!("⚠️ Rebalance needed! Deviations: {:?}", deviations);
 
-        // 4. 生成再平衡计划
+        // 3. 使用链上记录的 APY 生成再平衡计划
+        let protocol_apys: Vec<(u8, u64)> =
+            vault_state.supported_protocols.iter().map(|p| (p.protocol_id, p.apy_bps)).collect();
         let actions =
-            RebalanceEngine::generate_rebalance_plan(vault_state, &config, &protocol_apys)?;
+            RebalanceEngine::generate_rebalance_plan(vault_state, config, &protocol_apys)?;
 
         if actions.is_empty() {
             msg!("ℹ️ No viable rebalance actions generated");
@@ -89,10 +240,15 @@ impl ExecuteRebalance<'_> {
 
         msg!("📋 Generated {} rebalance actions", actions.len());
 
-        // 5. 执行第一个高优先级动作
-        // 注意：实际执行需要调用相应协议的 withdraw + deposit CPI
-        // 这里仅演示逻辑，完整实现需要在单独的指令中处理
-        let action = &actions[0];
+        // 4. 执行第一个高优先级动作，金额不超过源协议的账面持仓
+        let mut action = actions[0].clone();
+        let source_allocation = vault_state
+            .find_protocol(action.from_protocol)
+            .ok_or(CustomError::UnsupportedProtocol)?
+            .current_allocation;
+        action.amount = action.amount.min(source_allocation);
+        require!(action.amount > 0, CustomError::InvalidAmount);
+
         msg!(
             "🎯 Top priority action: {} -> {}, amount: {}, APY diff: {} bps",
             action.from_protocol,
@@ -101,35 +257,199 @@ impl ExecuteRebalance<'_> {
             action.apy_difference_bps
         );
 
-        // 记录再平衡事件
+        let vault_id = vault_state.vault_id;
+        let bump_seed = [vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        // 5. 从源协议提取到 Treasury，按实际到账金额检查滑点
+        let treasury_before = ctx.accounts.vault_treasury.amount;
+        match action.from_protocol {
+            PROTOCOL_KAMINO => {
+                // 按账面价值比例计算需要赎回的 Kamino 份额（向上取整）
+                let kamino_shares_balance = ctx.accounts.vault_kamino_shares.amount;
+                let kamino_shares_to_redeem = (action.amount as u128)
+                    .checked_mul(kamino_shares_balance as u128)
+                    .and_then(|v| v.checked_add(source_allocation as u128 - 1))
+                    .and_then(|v| v.checked_div(source_allocation as u128))
+                    .and_then(|v| u64::try_from(v).ok())
+                    .ok_or(CustomError::MathOverflow)?
+                    .min(kamino_shares_balance);
+
+                invoke_kamino_withdraw(
+                    &ctx.accounts.kamino_withdraw_accounts(),
+                    kamino_remaining,
+                    kamino_shares_to_redeem,
+                    signer_seeds,
+                )?;
+            }
+            PROTOCOL_JUPITER_LEND => {
+                invoke_jupiter_lend_withdraw(
+                    &ctx.accounts.jupiter_withdraw_accounts(),
+                    jupiter_remaining,
+                    action.amount,
+                    signer_seeds,
+                )?;
+            }
+            _ => return Err(error!(CustomError::UnsupportedProtocol)),
+        }
+
+        ctx.accounts.vault_treasury.reload()?;
+        let amount_received = ctx
+            .accounts
+            .vault_treasury
+            .amount
+            .checked_sub(treasury_before)
+            .ok_or(CustomError::CpiCallFailed)?;
+
+        let min_received = ctx.accounts.vault_state.min_amount_after_slippage(action.amount)?;
+        require!(amount_received >= min_received, CustomError::SlippageTooHigh);
+
+        // 6. 将到账金额存入目标协议，以 Treasury 实际转出的金额记账
+        let treasury_before_deposit = ctx.accounts.vault_treasury.amount;
+        match action.to_protocol {
+            PROTOCOL_KAMINO => {
+                invoke_kamino_deposit(
+                    &ctx.accounts.kamino_deposit_accounts(),
+                    kamino_remaining,
+                    amount_received,
+                    signer_seeds,
+                )?;
+            }
+            PROTOCOL_JUPITER_LEND => {
+                invoke_jupiter_lend_deposit(
+                    &ctx.accounts.jupiter_deposit_accounts(),
+                    jupiter_remaining,
+                    amount_received,
+                    signer_seeds,
+                )?;
+            }
+            _ => return Err(error!(CustomError::UnsupportedProtocol)),
+        }
+
+        ctx.accounts.vault_treasury.reload()?;
+        let amount_deployed = treasury_before_deposit
+            .checked_sub(ctx.accounts.vault_treasury.amount)
+            .ok_or(CustomError::CpiCallFailed)?;
+
+        // 7. 更新协议分配和再平衡历史，记录再平衡时间
+        RebalanceEngine::execute_rebalance_action(
+            &mut ctx.accounts.vault_state,
+            &action,
+            amount_deployed,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.rebalance_config.last_rebalance_time = now;
+
         emit!(crate::events::RebalanceEvent {
-            vault_id: vault_state.vault_id,
+            vault_id,
             protocol_from: action.from_protocol,
             protocol_to: action.to_protocol,
             amount_in: action.amount,
-            amount_out: action.amount, // 简化，实际需要CPI结果
+            amount_out: amount_deployed,
             executor: ctx.accounts.executor.key(),
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: now,
         });
 
-        msg!("✅ Rebalance action queued for execution");
+        msg!(
+            "✅ Rebalance executed: withdrawn={}, received={}, deployed={}",
+            action.amount,
+            amount_received,
+            amount_deployed
+        );
 
         Ok(())
     }
+
+    fn kamino_deposit_accounts(&self) -> KaminoDepositAccounts<'info> {
+        KaminoDepositAccounts {
+            user: self.vault_state.to_account_info(),
+            vault_state: self.kamino_vault_state.to_account_info(),
+            token_vault: self.kamino_token_vault.to_account_info(),
+            token_mint: self.base_token_mint.to_account_info(),
+            base_vault_authority: self.kamino_base_vault_authority.to_account_info(),
+            shares_mint: self.kamino_shares_mint.to_account_info(),
+            user_token_ata: self.vault_treasury.to_account_info(),
+            user_shares_ata: self.vault_kamino_shares.to_account_info(),
+            klend_program: self.klend_program.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            shares_token_program: self.token_program.to_account_info(),
+            event_authority: self.kamino_event_authority.to_account_info(),
+            kamino_vault_program: self.kamino_program.to_account_info(),
+        }
+    }
+
+    fn kamino_withdraw_accounts(&self) -> KaminoWithdrawAccounts<'info> {
+        KaminoWithdrawAccounts {
+            user: self.vault_state.to_account_info(),
+            vault_state: self.kamino_vault_state.to_account_info(),
+            token_vault: self.kamino_token_vault.to_account_info(),
+            base_vault_authority: self.kamino_base_vault_authority.to_account_info(),
+            user_token_ata: self.vault_treasury.to_account_info(),
+            token_mint: self.base_token_mint.to_account_info(),
+            user_shares_ata: self.vault_kamino_shares.to_account_info(),
+            shares_mint: self.kamino_shares_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            shares_token_program: self.token_program.to_account_info(),
+            klend_program: self.klend_program.to_account_info(),
+            event_authority: self.kamino_event_authority.to_account_info(),
+            kamino_vault_program: self.kamino_program.to_account_info(),
+        }
+    }
+
+    fn jupiter_deposit_accounts(&self) -> JupiterLendDepositAccounts<'info> {
+        JupiterLendDepositAccounts {
+            signer: self.vault_state.to_account_info(),
+            depositor_token_account: self.vault_treasury.to_account_info(),
+            recipient_token_account: self.vault_jupiter_f_token.to_account_info(),
+            mint: self.base_token_mint.to_account_info(),
+            lending_admin: self.jupiter_lending_admin.to_account_info(),
+            lending: self.jupiter_lending.to_account_info(),
+            f_token_mint: self.jupiter_f_token_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            jupiter_lend_program: self.jupiter_lend_program.to_account_info(),
+        }
+    }
+
+    fn jupiter_withdraw_accounts(&self) -> JupiterLendWithdrawAccounts<'info> {
+        JupiterLendWithdrawAccounts {
+            signer: self.vault_state.to_account_info(),
+            recipient_token_account: self.vault_jupiter_f_token.to_account_info(),
+            depositor_token_account: self.vault_treasury.to_account_info(),
+            lending_admin: self.jupiter_lending_admin.to_account_info(),
+            lending: self.jupiter_lending.to_account_info(),
+            mint: self.base_token_mint.to_account_info(),
+            f_token_mint: self.jupiter_f_token_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            jupiter_lend_program: self.jupiter_lend_program.to_account_info(),
+        }
+    }
 }
 
-/// 使用 Pyth Oracle 更新协议 APY
+/// 根据协议链上汇率更新协议 APY
 ///
 /// 该指令会：
-/// 1. 从 Pyth 获取各协议代币价格
-/// 2. 计算实时 APY
-/// 3. 更新到 Vault 配置中
+/// 1. 从注册表绑定的汇率来源读取收益凭证汇率（Kamino 份额价格、jlToken 兑换价格）
+/// 2. 与上次记录的汇率比较，计算年化收益率
+/// 3. 更新到 Vault 的协议配置中
 #[derive(Accounts)]
 pub struct UpdateProtocolAPY<'info> {
-    /// 管理员或授权更新者
-    #[account(mut)]
+    /// 管理员或 keeper
+    #[account(
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Keeper, &global_state.admin, &updater.key())
+            @ CustomError::UnauthorizedSigner
+    )]
     pub updater: Signer<'info>,
 
+    /// 全局状态
+    #[account(
+        seeds = [GLOBAL_SEED],
+        bump,
+        constraint = !global_state.frozen @ CustomError::GlobalStateFrozen
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
     /// Vault 状态
     #[account(
         mut,
@@ -138,49 +458,72 @@ pub struct UpdateProtocolAPY<'info> {
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    /// Pyth 价格更新账户
-    /// CHECK: Pyth 程序验证
-    pub price_update: AccountInfo<'info>,
+    /// 收益凭证汇率来源（须与 ProtocolConfig.rate_source 一致）
+    /// CHECK: 在 VaultState::protocol_exchange_rate 中校验地址、owner 和 mint
+    pub rate_source: UncheckedAccount<'info>,
+
+    /// Vault 持有的收益凭证账户（须与 ProtocolConfig.receipt_account 一致）
+    pub receipt_account: Box<Account<'info, TokenAccount>>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
 }
 
 impl UpdateProtocolAPY<'_> {
-    pub fn process_instruction(ctx: Context<Self>, protocol_id: u8, feed_id: String) -> Result<()> {
+    pub fn process_instruction(ctx: Context<Self>, protocol_id: u8) -> Result<()> {
         msg!("📊 Updating APY for protocol {}", protocol_id);
 
-        // 1. 从 Pyth 获取价格
-        let price_data = PythOracle::get_price_from_account(&ctx.accounts.price_update, &feed_id)?;
-
-        // 2. 验证价格有效性
-        PythOracle::validate_price(
-            &price_data,
-            60,  // 60秒内的价格
-            200, // 2% 最大置信区间
+        // 1. 从协议账户读取收益凭证汇率
+        let receipt_key = ctx.accounts.receipt_account.key();
+        let rate = ctx.accounts.vault_state.protocol_exchange_rate(
+            protocol_id,
+            &ctx.accounts.rate_source,
+            &receipt_key,
+            &ctx.accounts.receipt_account,
         )?;
+        let price = u64::try_from(rate).map_err(|_| CustomError::MathOverflow)?;
+        let now = Clock::get()?.unix_timestamp;
+        msg!("💰 Exchange rate: {} (1e12 precision)", price);
 
-        msg!(
-            "💰 Price: {} (confidence: {}, exponent: {})",
-            price_data.price,
-            price_data.confidence,
-            price_data.exponent
-        );
-
-        // 3. 计算 UI 价格
-        if let Some(ui_price) = price_data.to_ui_price() {
-            msg!("📈 UI Price: {} (6 decimals)", ui_price);
-        }
-
-        // 4. 更新协议配置
-        // 注意：实际APY计算需要更复杂的逻辑，这里仅演示价格获取
+        // 2. 基于汇率变化计算 APY 并更新协议配置
         let vault_state = &mut ctx.accounts.vault_state;
-        if let Some(protocol) =
-            vault_state.supported_protocols.iter_mut().find(|p| p.protocol_id == protocol_id)
-        {
-            // 这里可以基于价格变化计算 APY
-            // 例如：比较当前价格和历史价格
-            msg!("✅ Protocol {} configuration ready for update", protocol_id);
-        } else {
-            return Err(error!(CustomError::UnsupportedProtocol));
+        let vault_id = vault_state.vault_id;
+        let protocol =
+            vault_state.find_protocol_mut(protocol_id).ok_or(CustomError::UnsupportedProtocol)?;
+
+        // 同一时刻重复更新无法年化
+        require!(now > protocol.last_price_time, CustomError::PriceOracleError);
+
+        // 首次记录汇率时只保存基准，不更新 APY
+        if protocol.last_price > 0 {
+            protocol.apy_bps = PositionValuation::annualize_price_growth(
+                protocol.last_price,
+                protocol.last_price_time,
+                price,
+                now,
+            )
+            .ok_or(CustomError::MathOverflow)?;
         }
+        protocol.last_price = price;
+        protocol.last_price_time = now;
+        let apy_bps = protocol.apy_bps;
+
+        vault_state.last_updated = now;
+
+        emit!(ProtocolApyUpdated {
+            vault_id,
+            protocol_id,
+            price,
+            apy_bps,
+            updater: ctx.accounts.updater.key(),
+            timestamp: now,
+        });
+
+        msg!("✅ Protocol {} APY updated: {} bps", protocol_id, apy_bps);
 
         Ok(())
     }
@@ -192,72 +535,47 @@ impl UpdateProtocolAPY<'_> {
 /// - 避免 VaultState 账户大小限制
 /// - 每个用户独立账户，并发性更好
 /// - 易于查询和索引
+///
+/// 存入的资金留在 Treasury 中，由再平衡分配到各协议
 #[derive(Accounts)]
-#[instruction(amount: u64)]
 pub struct DepositWithUserPosition<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    /// 用户的独立持仓账户
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = UserPosition::space(),
-        seeds = [
-            UserPosition::SEED_PREFIX,
-            vault_state.vault_id.as_ref(),
-            user.key().as_ref()
-        ],
-        bump
-    )]
-    pub user_position: Account<'info, UserPosition>,
-
-    /// Vault 状态
-    #[account(
-        mut,
-        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
-        bump = vault_state.bump,
-        constraint = vault_state.status == VaultStatus::Active @ CustomError::VaultPaused
-    )]
-    pub vault_state: Account<'info, VaultState>,
-
-    pub system_program: Program<'info, System>,
+    /// 存款共用账户
+    pub deposit: DepositAccounts<'info>,
 }
 
 impl DepositWithUserPosition<'_> {
-    pub fn process_instruction(ctx: Context<Self>, amount: u64, shares: u64) -> Result<()> {
-        let user_position = &mut ctx.accounts.user_position;
-        let vault_state = &mut ctx.accounts.vault_state;
-
-        // 初始化（如果是首次存款）
-        if user_position.shares == 0 {
-            user_position.initialize(
-                vault_state.vault_id,
-                ctx.accounts.user.key(),
-                ctx.bumps.user_position,
-            );
-        }
-
-        // 记录存款
-        user_position.record_deposit(amount, shares)?;
-
-        // 更新 Vault 总量
-        vault_state.total_deposits = vault_state
-            .total_deposits
-            .checked_add(amount)
-            .ok_or(error!(CustomError::MathOverflow))?;
-
-        vault_state.total_shares = vault_state
-            .total_shares
-            .checked_add(shares)
-            .ok_or(error!(CustomError::MathOverflow))?;
+    /// - min_shares_out: 用户可接受的最少份额
+    /// - expected_shares: 用户链下按当前 NAV 估算的份额，实际份额相对它的偏离不超过 max_slippage_bps
+    pub fn process_instruction(
+        ctx: Context<Self>,
+        amount: u64,
+        min_shares_out: u64,
+        expected_shares: u64,
+    ) -> Result<()> {
+        let quote = ctx.accounts.deposit.receive(amount, min_shares_out, expected_shares)?;
+        ctx.accounts.deposit.issue_shares(&quote, ctx.bumps.deposit.user_position)?;
+
+        let deposit = &ctx.accounts.deposit;
+        emit!(VaultDepositEvent {
+            user: deposit.user.key(),
+            vault_id: deposit.vault_state.vault_id,
+            amount: quote.received_amount,
+            shares_received: quote.shares_to_mint,
+            protocol_id: 0, // 资金暂留在 Treasury 中
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         msg!("✅ Deposit recorded in UserPosition account");
-        msg!("   User: {}", ctx.accounts.user.key());
-        msg!("   Amount: {}", amount);
-        msg!("   Shares: {}", shares);
-        msg!("   Total deposits: {}", user_position.total_deposited);
-        msg!("   Total shares: {}", user_position.shares);
+        msg!("   User: {}", deposit.user.key());
+        msg!(
+            "   Amount: {} (received: {}, fee: {})",
+            amount,
+            quote.received_amount,
+            quote.deposit_fee
+        );
+        msg!("   Shares: {}", quote.shares_to_mint);
+        msg!("   Total deposits: {}", deposit.user_position.total_deposited);
+        msg!("   Total shares: {}", deposit.user_position.shares);
 
         Ok(())
     }
@@ -1,11 +1,21 @@
+use crate::constants::PROTOCOL_KAMINO;
 use crate::error::*;
+use crate::events::VaultDepositEvent;
+use crate::instructions::vault::deposit_fee_tiers::*;
+use crate::instructions::vault::position_valuation::*;
+use crate::instructions::{invoke_kamino_deposit, KaminoDepositAccounts};
 use crate::state::*;
+use crate::utils::FeeEngine;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use anchor_spl::token_interface::{
+    self, Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface, TransferChecked,
+};
 
+/// 存款指令共用的账户（VaultDeposit 与 DepositWithUserPosition）
 #[derive(Accounts)]
 #[instruction(amount: u64)]
-pub struct VaultDeposit<'info> {
+pub struct DepositAccounts<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
@@ -13,14 +23,32 @@ pub struct VaultDeposit<'info> {
     #[account(
         mut,
         constraint = user_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_token_account.mint == vault_state.base_token_mint @ CustomError::InvalidMint,
         constraint = user_token_account.amount >= amount @ CustomError::InsufficientFunds
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, BaseTokenAccount>,
 
-    /// 用户的份额代币账户
-    #[account(mut)]
+    /// 用户的份额代币账户（接收 Mars Vault 份额）
+    #[account(
+        mut,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
     pub user_shares_account: Account<'info, TokenAccount>,
 
+    /// 用户的独立持仓账户
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
     /// Mars Vault 状态账户
     #[account(
         mut,
@@ -35,7 +63,57 @@ pub struct VaultDeposit<'info> {
         seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
         bump
     )]
-    pub vault_treasury: Account<'info, TokenAccount>,
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022，如 PYUSD）
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// Mars Vault 份额 Mint（mint authority 必须是 vault_state PDA）
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Account<'info, TokenAccount>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    /// 分层存款费账户（未启用分层费用时可选账户可省略）
+    pub fee_tier_accounts: DepositFeeTierAccounts<'info>,
+
+    /// 份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VaultDeposit<'info> {
+    /// 存款共用账户
+    pub deposit: DepositAccounts<'info>,
+
+    /// Mars Vault 持有的 Kamino 份额账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_kamino_shares.owner == deposit.vault_state.key() @ CustomError::InvalidOwner,
+        constraint = deposit.vault_state.is_bound_receipt_account(PROTOCOL_KAMINO, &vault_kamino_shares.key())
+            @ CustomError::PositionAccountMismatch,
+        constraint = vault_kamino_shares.mint == kamino_shares_mint.key() @ CustomError::InvalidMint
+    )]
+    pub vault_kamino_shares: Account<'info, TokenAccount>,
 
     /// Kamino Vault 状态账户（CPI 目标）
     /// CHECK: 由 Kamino 程序验证
@@ -47,131 +125,290 @@ pub struct VaultDeposit<'info> {
     #[account(mut)]
     pub kamino_token_vault: AccountInfo<'info>,
 
+    /// Kamino Vault 权限 PDA
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_base_vault_authority: AccountInfo<'info>,
+
     /// Kamino 份额 Mint
     /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
     pub kamino_shares_mint: AccountInfo<'info>,
 
+    /// CHECK: Klend 程序
+    #[account(address = crate::constants::protocols::kamino::LEND_PROGRAM_ID)]
+    pub klend_program: AccountInfo<'info>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_event_authority: AccountInfo<'info>,
+
     /// Kamino 程序
     /// CHECK: 硬编码程序 ID
-    #[account(constraint = kamino_program.key() == crate::constants::protocols::kamino::PROGRAM_ID)]
+    #[account(address = crate::constants::protocols::kamino::PROGRAM_ID)]
     pub kamino_program: AccountInfo<'info>,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+/// 存款的计价结果，在份额铸造前确定
+pub struct DepositQuote {
+    /// Treasury 实际到账金额（扣除 Token-2022 转账费后）
+    pub received_amount: u64,
+    /// 存款费用（分层费用或 Vault 存款费）
+    pub deposit_fee: u64,
+    /// 计入 Vault 费用统计的部分（分层费用已转出，不计入）
+    pub vault_fee: u64,
+    pub net_deposit_amount: u64,
+    pub shares_to_mint: u64,
 }
 
-impl VaultDeposit<'_> {
-    pub fn process_instruction(ctx: Context<Self>, amount: u64) -> Result<()> {
+impl<'info> DepositAccounts<'info> {
+    /// 重新计价持仓、结算费用、收款并计算份额
+    ///
+    /// 份额按存款前的 NAV 计算，并经过存款限额和滑点检查；分层存款费在此转出 Treasury
+    pub fn receive(
+        &mut self,
+        amount: u64,
+        min_shares_out: u64,
+        expected_shares: u64,
+    ) -> Result<DepositQuote> {
         // 验证输入
         require!(amount > 0, CustomError::ZeroAmount);
-        require!(ctx.accounts.vault_state.status == VaultStatus::Active, CustomError::VaultPaused);
-
-        // 1. 计算存款费用
-        let vault_state = &ctx.accounts.vault_state;
-        let deposit_fee_bps = vault_state.fee_config.deposit_fee_bps;
-        let deposit_fee = (amount as u128)
-            .checked_mul(deposit_fee_bps as u128)
-            .and_then(|v| v.checked_div(10_000))
-            .and_then(|v| u64::try_from(v).ok())
+        self.vault_state.require_deposits_allowed()?;
+
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = self.vault_state.key();
+        self.position_valuation.mark_to_market(&mut self.vault_state, &vault_key)?;
+
+        // 1. 先结算管理费和性能费，使新存款不承担此前的费用
+        FeeEngine::settle_fees(
+            &mut self.vault_state,
+            self.vault_treasury.amount,
+            self.shares_mint.to_account_info(),
+            &self.fee_recipient_shares_account,
+            self.token_program.to_account_info(),
+        )?;
+
+        // 2. 按存款前的 NAV 定价（转账前的 Treasury 余额）
+        let treasury_before_deposit = self.vault_treasury.amount;
+        let total_assets = self.vault_state.total_assets(treasury_before_deposit)?;
+
+        // 3. 将用户代币转入 Mars Vault Treasury
+        let transfer_ctx = CpiContext::new(
+            self.base_token_program.to_account_info(),
+            TransferChecked {
+                from: self.user_token_account.to_account_info(),
+                mint: self.base_token_mint.to_account_info(),
+                to: self.vault_treasury.to_account_info(),
+                authority: self.user.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, self.base_token_mint.decimals)?;
+
+        // Token-2022 转账费扩展会在途中扣费，按 Treasury 实际到账金额记账
+        self.vault_treasury.reload()?;
+        let received_amount = self
+            .vault_treasury
+            .amount
+            .checked_sub(treasury_before_deposit)
             .ok_or(CustomError::MathOverflow)?;
+        require!(received_amount > 0, CustomError::InvalidAmount);
 
+        // 4. 计算存款费用和应铸造的份额
+        let vault_state = &self.vault_state;
+        let (deposit_fee, tiered_fee) =
+            self.fee_tier_accounts.deposit_fee(vault_state, received_amount)?;
         let net_deposit_amount =
-            amount.checked_sub(deposit_fee).ok_or(CustomError::MathOverflow)?;
+            received_amount.checked_sub(deposit_fee).ok_or(CustomError::MathOverflow)?;
 
         msg!(
-            "💰 Deposit: total={}, fee={} ({} bps), net={}",
+            "💰 Deposit: requested={}, received={}, fee={} (tiered={}), net={}",
             amount,
+            received_amount,
             deposit_fee,
-            deposit_fee_bps,
+            tiered_fee.is_some(),
             net_deposit_amount
         );
 
-        // 2. 将用户代币转入 Mars Vault Treasury
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.vault_treasury.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
+        vault_state.check_deposit_limits(
+            received_amount,
+            net_deposit_amount,
+            self.user_position.shares,
+            total_assets,
+        )?;
+        let shares_to_mint = vault_state.convert_to_shares(net_deposit_amount, total_assets)?;
+        require!(shares_to_mint > 0, CustomError::InvalidAmount);
+
+        // 滑点保护：以用户签名的最少份额和预期份额为准
+        vault_state.check_slippage(shares_to_mint, expected_shares, min_shares_out)?;
+
+        msg!("📊 NAV: total_assets={}, total_shares={}", total_assets, vault_state.total_shares);
+
+        // 分层存款费转入全局 ata_vault，不留在 Treasury
+        if let Some(tiered_fee) = &tiered_fee {
+            let vault_id = self.vault_state.vault_id;
+            let bump_seed = [self.vault_state.bump];
+            let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+            self.fee_tier_accounts.collect(
+                tiered_fee,
+                self.vault_treasury.to_account_info(),
+                self.vault_state.to_account_info(),
+                self.base_token_program.to_account_info(),
+                &[seeds],
+            )?;
+            self.vault_treasury.reload()?;
+        }
+
+        Ok(DepositQuote {
+            received_amount,
+            deposit_fee,
+            vault_fee: if tiered_fee.is_some() {
+                0
+            } else {
+                deposit_fee
+            },
+            net_deposit_amount,
+            shares_to_mint,
+        })
+    }
+
+    /// 铸造份额给用户，并更新 Vault 总量和用户持仓（首次存款时初始化）
+    pub fn issue_shares(&mut self, quote: &DepositQuote, position_bump: u8) -> Result<()> {
+        let vault_id = self.vault_state.vault_id;
+        let bump_seed = [self.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        let mint_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            MintTo {
+                mint: self.shares_mint.to_account_info(),
+                to: self.user_shares_account.to_account_info(),
+                authority: self.vault_state.to_account_info(),
             },
+            signer_seeds,
         );
-        token::transfer(transfer_ctx, amount)?;
+        token::mint_to(mint_ctx, quote.shares_to_mint)?;
+
+        self.vault_state.record_deposit(
+            quote.net_deposit_amount,
+            quote.shares_to_mint,
+            quote.vault_fee,
+        )?;
+
+        let user_key = self.user.key();
+        let user_position = &mut self.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user_key, position_bump);
+        }
+        user_position.record_deposit(quote.net_deposit_amount, quote.shares_to_mint)
+    }
+}
 
-        // 3. 提取需要的数据避免借用冲突
-        let vault_id = ctx.accounts.vault_state.vault_id;
-        let bump = ctx.accounts.vault_state.bump;
+impl<'info> VaultDeposit<'info> {
+    /// remaining_accounts: Kamino Vault 的 reserves 和 lending markets
+    ///
+    /// - min_shares_out: 用户可接受的最少份额
+    /// - expected_shares: 用户链下按当前 NAV 估算的份额，实际份额相对它的偏离不超过 max_slippage_bps
+    pub fn process_instruction(
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        amount: u64,
+        min_shares_out: u64,
+        expected_shares: u64,
+    ) -> Result<()> {
+        // 1. 计价、收款并计算份额
+        let quote = ctx.accounts.deposit.receive(amount, min_shares_out, expected_shares)?;
 
-        // 4. 通过 CPI 调用 Kamino 存款（使用净金额）
-        let kamino_deposit_result =
-            Self::kamino_deposit_cpi(&ctx, net_deposit_amount, vault_id, bump)?;
+        // 2. Kamino 已注册且启用时，通过 CPI 将净金额存入 Kamino（vault_state PDA 签名）；
+        //    否则资金留在 Treasury，等待再平衡部署
+        let vault_id = ctx.accounts.deposit.vault_state.vault_id;
+        let bump_seed = [ctx.accounts.deposit.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
 
-        // 5. 更新 Mars Vault 状态和费用
-        let vault_state = &mut ctx.accounts.vault_state;
-        vault_state.total_deposits += net_deposit_amount;
-        vault_state.total_shares += kamino_deposit_result.shares_received;
+        let kamino_enabled = ctx
+            .accounts
+            .deposit
+            .vault_state
+            .find_protocol(PROTOCOL_KAMINO)
+            .is_some_and(|p| p.enabled);
+        let (deployed_amount, kamino_shares_received) = if kamino_enabled {
+            let treasury_before = ctx.accounts.deposit.vault_treasury.amount;
+            let kamino_shares_before = ctx.accounts.vault_kamino_shares.amount;
+            invoke_kamino_deposit(
+                &ctx.accounts.kamino_deposit_accounts(),
+                ctx.remaining_accounts,
+                quote.net_deposit_amount,
+                signer_seeds,
+            )?;
 
-        // 记录费用
-        vault_state.unclaimed_deposit_fee = vault_state
-            .unclaimed_deposit_fee
-            .checked_add(deposit_fee)
-            .ok_or(CustomError::MathOverflow)?;
-        vault_state.total_deposit_fee_collected = vault_state
-            .total_deposit_fee_collected
-            .checked_add(deposit_fee)
-            .ok_or(CustomError::MathOverflow)?;
+            // Kamino 可能只接收部分金额，以实际转出的数量记账
+            ctx.accounts.deposit.vault_treasury.reload()?;
+            let deployed_amount = treasury_before
+                .checked_sub(ctx.accounts.deposit.vault_treasury.amount)
+                .ok_or(CustomError::CpiCallFailed)?;
+            ctx.accounts.vault_kamino_shares.reload()?;
+            let kamino_shares_received = ctx
+                .accounts
+                .vault_kamino_shares
+                .amount
+                .checked_sub(kamino_shares_before)
+                .ok_or(CustomError::CpiCallFailed)?;
+            (deployed_amount, kamino_shares_received)
+        } else {
+            msg!("ℹ️ Kamino not enabled, funds kept in treasury");
+            (0, 0)
+        };
 
-        // 6. 记录用户存款（净金额）
-        vault_state.insert_user_deposit(
-            ctx.accounts.user.key(),
-            UserDeposit {
-                amount: net_deposit_amount,
-                shares: kamino_deposit_result.shares_received,
-                timestamp: Clock::get()?.unix_timestamp,
-                last_action_time: Clock::get()?.unix_timestamp,
-                total_rewards: 0,
+        // 3. 铸造 Mars Vault 份额给用户，更新 Vault 状态和用户持仓
+        ctx.accounts.deposit.issue_shares(&quote, ctx.bumps.deposit.user_position)?;
+
+        if let Some(kamino_protocol) =
+            ctx.accounts.deposit.vault_state.find_protocol_mut(PROTOCOL_KAMINO)
+        {
+            kamino_protocol.current_allocation = kamino_protocol
+                .current_allocation
+                .checked_add(deployed_amount)
+                .ok_or(CustomError::MathOverflow)?;
+        }
+
+        emit!(VaultDepositEvent {
+            user: ctx.accounts.deposit.user.key(),
+            vault_id,
+            amount: quote.received_amount,
+            shares_received: quote.shares_to_mint,
+            protocol_id: if kamino_enabled {
+                PROTOCOL_KAMINO
+            } else {
+                0
             },
-        );
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         msg!(
-            "✅ Vault deposit successful: amount={}, fee={}, net={}, shares={}",
-            amount,
-            deposit_fee,
-            net_deposit_amount,
-            kamino_deposit_result.shares_received
+            "✅ Vault deposit successful: amount={}, fee={}, net={}, shares={}, kamino_shares={}",
+            quote.received_amount,
+            quote.deposit_fee,
+            quote.net_deposit_amount,
+            quote.shares_to_mint,
+            kamino_shares_received
         );
 
         Ok(())
     }
 
-    fn kamino_deposit_cpi(
-        ctx: &Context<Self>,
-        amount: u64,
-        vault_id: [u8; 32],
-        bump: u8,
-    ) -> Result<KaminoDepositResult> {
-        // Kamino CPI 调用逻辑
-        let seeds = &[b"vault-state", vault_id.as_ref(), &[bump]];
-        let signer_seeds = &[&seeds[..]];
-
-        // 构建 Kamino deposit instruction
-        let kamino_accounts = vec![
-            ctx.accounts.vault_treasury.to_account_info(),
-            ctx.accounts.user_shares_account.to_account_info(),
-            ctx.accounts.kamino_vault_state.to_account_info(),
-            ctx.accounts.kamino_token_vault.to_account_info(),
-            ctx.accounts.kamino_shares_mint.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ];
-
-        // 这里需要根据 Kamino 的实际 IDL 构建指令
-        // 暂时返回模拟结果
-        Ok(KaminoDepositResult {
-            shares_received: amount, // 1:1 比例，实际需要根据 Kamino 返回
-        })
+    fn kamino_deposit_accounts(&self) -> KaminoDepositAccounts<'info> {
+        KaminoDepositAccounts {
+            user: self.deposit.vault_state.to_account_info(),
+            vault_state: self.kamino_vault_state.to_account_info(),
+            token_vault: self.kamino_token_vault.to_account_info(),
+            token_mint: self.deposit.base_token_mint.to_account_info(),
+            base_vault_authority: self.kamino_base_vault_authority.to_account_info(),
+            shares_mint: self.kamino_shares_mint.to_account_info(),
+            user_token_ata: self.deposit.vault_treasury.to_account_info(),
+            user_shares_ata: self.vault_kamino_shares.to_account_info(),
+            klend_program: self.klend_program.to_account_info(),
+            token_program: self.deposit.base_token_program.to_account_info(),
+            shares_token_program: self.deposit.token_program.to_account_info(),
+            event_authority: self.kamino_event_authority.to_account_info(),
+            kamino_vault_program: self.kamino_program.to_account_info(),
+        }
     }
 }
-
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct KaminoDepositResult {
-    pub shares_received: u64,
-}
@@ -0,0 +1,161 @@
+use crate::error::*;
+use crate::events::VaultWithdrawEvent;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{
+    self, Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface, TransferChecked,
+};
+
+/// 紧急提款：Vault 处于 Emergency 状态时，按份额比例赎回 Treasury 中的资金
+///
+/// 不调用 Kamino / Jupiter 等外部协议，不收取提款费
+#[derive(Accounts)]
+#[instruction(shares_amount: u64)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 用户的代币账户（接收赎回的代币）
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_token_account.mint == vault_state.base_token_mint @ CustomError::InvalidMint
+    )]
+    pub user_token_account: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// 用户的份额代币账户
+    #[account(
+        mut,
+        constraint = user_shares_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint,
+        constraint = user_shares_account.amount >= shares_amount @ CustomError::InsufficientShares
+    )]
+    pub user_shares_account: Account<'info, TokenAccount>,
+
+    /// 用户的独立持仓账户（通过转账或管理费获得份额的持有人可能还没有，按需创建）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump,
+        constraint = vault_state.status == VaultStatus::Emergency @ CustomError::InvalidVaultState
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Mars Vault 的代币金库
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022）
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl EmergencyWithdraw<'_> {
+    pub fn process_instruction(ctx: Context<Self>, shares_amount: u64) -> Result<()> {
+        require!(shares_amount > 0, CustomError::ZeroAmount);
+
+        // 1. 按份额比例计算可赎回的 Treasury 资金
+        let vault_state = &ctx.accounts.vault_state;
+        let amount = vault_state
+            .emergency_redeem_amount(shares_amount, ctx.accounts.vault_treasury.amount)?;
+        require!(amount > 0, CustomError::InsufficientFunds);
+
+        let vault_id = vault_state.vault_id;
+        let bump_seed = [vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        msg!("🚨 Emergency withdraw: shares={}, amount={}", shares_amount, amount);
+
+        // 2. 销毁用户的份额
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                from: ctx.accounts.user_shares_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::burn(burn_ctx, shares_amount)?;
+
+        // 3. 从 Treasury 转账给用户
+        let user_balance_before = ctx.accounts.user_token_account.amount;
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(
+            transfer_ctx,
+            amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
+
+        // 转账费扩展会使用户实际到账少于赎回金额
+        ctx.accounts.user_token_account.reload()?;
+        let amount_received =
+            ctx.accounts.user_token_account.amount.saturating_sub(user_balance_before);
+
+        // 4. 更新 Vault 和用户持仓
+        ctx.accounts.vault_state.record_withdraw(amount, shares_amount, 0)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let user_key = ctx.accounts.user.key();
+        let user_position = &mut ctx.accounts.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user_key, ctx.bumps.user_position);
+        }
+        user_position.record_withdraw(shares_amount)?;
+
+        emit!(VaultWithdrawEvent {
+            user: ctx.accounts.user.key(),
+            vault_id,
+            shares_burned: shares_amount,
+            amount_received,
+            protocol_id: 0, // 仅从 Treasury 赎回
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
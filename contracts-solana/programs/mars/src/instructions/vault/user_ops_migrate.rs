@@ -0,0 +1,108 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+
+/// 将 VaultState.user_deposits 中的旧版存款记录迁移到用户的 UserPosition PDA
+///
+/// 任何人都可以为某个用户发起迁移（payer 支付租金），
+/// 每次迁移一个用户，记录从 Vec 中移除后合并到 PDA，并为尚无份额代币的旧版份额铸造代币
+#[derive(Accounts)]
+pub struct MigrateUserDeposit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// 旧版存款记录所属的用户
+    /// CHECK: 仅用作 PDA 种子和记录查找
+    pub user: UncheckedAccount<'info>,
+
+    /// 用户的份额代币账户（接收迁移的份额）
+    #[account(
+        mut,
+        constraint = user_shares_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub user_shares_account: Account<'info, TokenAccount>,
+
+    /// 用户的独立持仓账户
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Vault 状态
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl MigrateUserDeposit<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let user_key = ctx.accounts.user.key();
+        let vault_state = &mut ctx.accounts.vault_state;
+
+        // 1. 从旧版 Vec 中移除记录
+        let legacy_deposit =
+            vault_state.remove_user_deposit(&user_key).ok_or(CustomError::NoDepositsFound)?;
+
+        let vault_id = vault_state.vault_id;
+        let remaining_entries = vault_state.user_deposits.len();
+
+        // 2. 铸造迁移的份额（vault_state PDA 签名）
+        //    旧版份额已计入 total_shares，只补足尚无份额代币对应的部分，代币供应量不超过 total_shares
+        let unbacked_shares =
+            vault_state.total_shares.saturating_sub(ctx.accounts.shares_mint.supply);
+        let shares_to_mint = legacy_deposit.shares.min(unbacked_shares);
+        if shares_to_mint > 0 {
+            let bump_seed = [vault_state.bump];
+            let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+            let signer_seeds = &[seeds];
+            let mint_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.shares_mint.to_account_info(),
+                    to: ctx.accounts.user_shares_account.to_account_info(),
+                    authority: ctx.accounts.vault_state.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::mint_to(mint_ctx, shares_to_mint)?;
+        }
+
+        // 3. 合并到 UserPosition（迁移前可能已通过新指令存过款）
+        let user_position = &mut ctx.accounts.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user_key, ctx.bumps.user_position);
+        }
+        user_position.absorb_legacy_deposit(&legacy_deposit)?;
+
+        msg!("✅ Migrated legacy deposit to UserPosition");
+        msg!("   User: {}", user_key);
+        msg!("   Amount: {}", legacy_deposit.amount);
+        msg!("   Shares: {} (minted {})", legacy_deposit.shares, shares_to_mint);
+        msg!("   Remaining legacy entries: {}", remaining_entries);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,15 @@
+use crate::constants::PROTOCOL_KAMINO;
 use crate::error::*;
+use crate::events::VaultWithdrawEvent;
+use crate::instructions::vault::position_valuation::*;
+use crate::instructions::{invoke_kamino_withdraw, KaminoWithdrawAccounts};
 use crate::state::*;
+use crate::utils::FeeEngine;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{
+    self, Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface, TransferChecked,
+};
 
 #[derive(Accounts)]
 #[instruction(shares_amount: u64)]
@@ -12,18 +20,34 @@ pub struct VaultWithdraw<'info> {
     /// 用户的代币账户（接收提取的代币）
     #[account(
         mut,
-        constraint = user_token_account.owner == user.key() @ CustomError::InvalidOwner
+        constraint = user_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_token_account.mint == vault_state.base_token_mint @ CustomError::InvalidMint
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, BaseTokenAccount>,
 
     /// 用户的份额代币账户
     #[account(
         mut,
         constraint = user_shares_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint,
         constraint = user_shares_account.amount >= shares_amount @ CustomError::InsufficientShares
     )]
     pub user_shares_account: Account<'info, TokenAccount>,
 
+    /// 用户的独立持仓账户（通过转账或管理费获得份额的持有人可能还没有，按需创建）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
     /// Mars Vault 状态账户
     #[account(
         mut,
@@ -33,10 +57,50 @@ pub struct VaultWithdraw<'info> {
     pub vault_state: Account<'info, VaultState>,
 
     /// Mars Vault 的代币金库
-    #[account(mut)]
-    pub vault_treasury: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022，Kamino 赎回时可写）
+    #[account(
+        mut,
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
 
-    /// Kamino Vault 状态账户
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Account<'info, TokenAccount>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    /// Mars Vault 持有的 Kamino 份额账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_kamino_shares.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_KAMINO, &vault_kamino_shares.key())
+            @ CustomError::PositionAccountMismatch,
+        constraint = vault_kamino_shares.mint == kamino_shares_mint.key() @ CustomError::InvalidMint
+    )]
+    pub vault_kamino_shares: Account<'info, TokenAccount>,
+
+    /// Kamino Vault 状态账户（CPI 目标）
     /// CHECK: 由 Kamino 程序验证
     #[account(mut)]
     pub kamino_vault_state: AccountInfo<'info>,
@@ -46,138 +110,248 @@ pub struct VaultWithdraw<'info> {
     #[account(mut)]
     pub kamino_token_vault: AccountInfo<'info>,
 
+    /// Kamino Vault 权限 PDA
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_base_vault_authority: AccountInfo<'info>,
+
+    /// Kamino 份额 Mint
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_shares_mint: AccountInfo<'info>,
+
+    /// CHECK: Klend 程序
+    #[account(address = crate::constants::protocols::kamino::LEND_PROGRAM_ID)]
+    pub klend_program: AccountInfo<'info>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_event_authority: AccountInfo<'info>,
+
     /// Kamino 程序
     /// CHECK: 硬编码程序 ID
-    #[account(constraint = kamino_program.key() == crate::constants::protocols::kamino::PROGRAM_ID)]
+    #[account(address = crate::constants::protocols::kamino::PROGRAM_ID)]
     pub kamino_program: AccountInfo<'info>,
 
+    /// 份额代币使用的 Token Program
     pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
 
-impl VaultWithdraw<'_> {
-    pub fn process_instruction(ctx: Context<Self>, shares_amount: u64) -> Result<()> {
+impl<'info> VaultWithdraw<'info> {
+    /// remaining_accounts: Kamino Vault 的 reserves 和 lending markets（仅在需要从 Kamino 赎回时使用）
+    ///
+    /// - min_amount_out: 用户至少收到的基础代币数量
+    /// - expected_amount: 用户链下按当前 NAV 估算的到账数量，实际到账相对它的偏离不超过 max_slippage_bps
+    pub fn process_instruction(
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        shares_amount: u64,
+        min_amount_out: u64,
+        expected_amount: u64,
+    ) -> Result<()> {
         // 验证输入
         require!(shares_amount > 0, CustomError::ZeroAmount);
-        require!(ctx.accounts.vault_state.status == VaultStatus::Active, CustomError::VaultPaused);
+        ctx.accounts.vault_state.require_withdrawals_allowed()?;
+
+        // 1. 验证用户有足够的份额（以份额代币余额为准，管理费和转账获得的份额同样可以赎回）
+        require!(
+            ctx.accounts.user_shares_account.amount >= shares_amount,
+            CustomError::InsufficientShares
+        );
+
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = ctx.accounts.vault_state.key();
+        ctx.accounts
+            .position_valuation
+            .mark_to_market(&mut ctx.accounts.vault_state, &vault_key)?;
+
+        // 2. 先结算管理费和性能费，再按当前 NAV 计算份额对应的代币数量
+        FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        let vault_state = &ctx.accounts.vault_state;
+        let treasury_balance = ctx.accounts.vault_treasury.amount;
+        let total_assets = vault_state.total_assets(treasury_balance)?;
+        let gross_amount = vault_state.convert_to_assets(shares_amount, total_assets)?;
+        require!(gross_amount > 0, CustomError::InvalidAmount);
 
-        // 1. 验证用户有足够的份额
-        let user_deposit = ctx
-            .accounts
-            .vault_state
-            .find_user_deposit(&ctx.accounts.user.key())
-            .ok_or(CustomError::NoDepositsFound)?
-            .clone();
+        // 3. 提取需要的数据避免借用冲突
+        let vault_id = vault_state.vault_id;
+        let bump = vault_state.bump;
+        let withdraw_fee_bps = vault_state.fee_config.withdraw_fee_bps;
+        let available_liquidity =
+            treasury_balance.saturating_sub(vault_state.total_unclaimed_fees()?);
 
-        require!(user_deposit.shares >= shares_amount, CustomError::InsufficientShares);
+        let bump_seed = [bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
 
-        // 2. 提取需要的数据避免借用冲突
-        let vault_id = ctx.accounts.vault_state.vault_id;
-        let bump = ctx.accounts.vault_state.bump;
-        let withdraw_fee_bps = ctx.accounts.vault_state.fee_config.withdraw_fee_bps;
+        // 4. Treasury 流动性不足时，从 Kamino 赎回差额
+        let mut payout_amount = gross_amount;
+        if available_liquidity < gross_amount {
+            let shortfall = gross_amount - available_liquidity;
+            let kamino_allocation = vault_state
+                .find_protocol(PROTOCOL_KAMINO)
+                .ok_or(CustomError::UnsupportedProtocol)?
+                .current_allocation;
+            require!(kamino_allocation >= shortfall, CustomError::InsufficientFunds);
 
-        // 3. 通过 CPI 从 Kamino 赎回
-        let tokens_received_from_kamino =
-            Self::kamino_withdraw_cpi(&ctx, shares_amount, vault_id, bump)?;
+            // 按账面价值比例计算需要赎回的 Kamino 份额（向上取整）
+            let kamino_shares_balance = ctx.accounts.vault_kamino_shares.amount;
+            let kamino_shares_to_redeem = (shortfall as u128)
+                .checked_mul(kamino_shares_balance as u128)
+                .and_then(|v| v.checked_add(kamino_allocation as u128 - 1))
+                .and_then(|v| v.checked_div(kamino_allocation as u128))
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or(CustomError::MathOverflow)?
+                .min(kamino_shares_balance);
 
-        // 4. 计算提款费用
-        let withdraw_fee = (tokens_received_from_kamino as u128)
+            let treasury_before_redeem = ctx.accounts.vault_treasury.amount;
+            invoke_kamino_withdraw(
+                &ctx.accounts.kamino_withdraw_accounts(),
+                ctx.remaining_accounts,
+                kamino_shares_to_redeem,
+                signer_seeds,
+            )?;
+            ctx.accounts.vault_treasury.reload()?;
+            let tokens_received = ctx
+                .accounts
+                .vault_treasury
+                .amount
+                .checked_sub(treasury_before_redeem)
+                .ok_or(CustomError::CpiCallFailed)?;
+
+            // 账面持仓按差额扣减；实际到账少于差额的损失由赎回者承担，
+            // 多出的部分留在 Treasury 中归全体持有人
+            let kamino_protocol = ctx
+                .accounts
+                .vault_state
+                .find_protocol_mut(PROTOCOL_KAMINO)
+                .ok_or(CustomError::UnsupportedProtocol)?;
+            kamino_protocol.current_allocation = kamino_allocation - shortfall;
+
+            if tokens_received < shortfall {
+                payout_amount = gross_amount - (shortfall - tokens_received);
+            }
+
+            msg!(
+                "🔄 Redeemed from Kamino: shortfall={}, kamino_shares={}, received={}",
+                shortfall,
+                kamino_shares_to_redeem,
+                tokens_received
+            );
+        }
+
+        // 5. 计算提款费用
+        let withdraw_fee = (payout_amount as u128)
             .checked_mul(withdraw_fee_bps as u128)
             .and_then(|v| v.checked_div(10_000))
             .and_then(|v| u64::try_from(v).ok())
             .ok_or(CustomError::MathOverflow)?;
 
-        let net_withdrawal_amount = tokens_received_from_kamino
-            .checked_sub(withdraw_fee)
-            .ok_or(CustomError::MathOverflow)?;
+        let net_withdrawal_amount =
+            payout_amount.checked_sub(withdraw_fee).ok_or(CustomError::MathOverflow)?;
 
         msg!(
             "💰 Withdraw: gross={}, fee={} ({} bps), net={}",
-            tokens_received_from_kamino,
+            payout_amount,
             withdraw_fee,
             withdraw_fee_bps,
             net_withdrawal_amount
         );
 
-        // 5. 将代币从 Mars Treasury 转给用户（扣除费用后的净额）
-        let seeds = &[b"vault-state", vault_id.as_ref(), &[bump]];
-        let signer_seeds = &[&seeds[..]];
+        // 6. 销毁用户的份额
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                from: ctx.accounts.user_shares_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::burn(burn_ctx, shares_amount)?;
 
+        // 7. 将代币从 Mars Treasury 转给用户（扣除费用后的净额）
+        let user_balance_before = ctx.accounts.user_token_account.amount;
         let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.vault_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
                 to: ctx.accounts.user_token_account.to_account_info(),
                 authority: ctx.accounts.vault_state.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, net_withdrawal_amount)?;
-
-        // 6. 更新状态和费用
-        let vault_state = &mut ctx.accounts.vault_state;
-        vault_state.total_deposits = vault_state
-            .total_deposits
-            .checked_sub(tokens_received_from_kamino)
-            .ok_or(CustomError::MathOverflow)?;
-        vault_state.total_shares =
-            vault_state.total_shares.checked_sub(shares_amount).ok_or(CustomError::MathOverflow)?;
+        token_interface::transfer_checked(
+            transfer_ctx,
+            net_withdrawal_amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
 
-        // 记录费用（留在 treasury 中）
-        vault_state.unclaimed_withdraw_fee = vault_state
-            .unclaimed_withdraw_fee
-            .checked_add(withdraw_fee)
-            .ok_or(CustomError::MathOverflow)?;
-        vault_state.total_withdraw_fee_collected = vault_state
-            .total_withdraw_fee_collected
-            .checked_add(withdraw_fee)
-            .ok_or(CustomError::MathOverflow)?;
+        // 转账费扩展会使用户实际到账少于净额
+        ctx.accounts.user_token_account.reload()?;
+        let amount_received =
+            ctx.accounts.user_token_account.amount.saturating_sub(user_balance_before);
+        // 滑点保护：Kamino 赎回损失和转账费都可能使到账低于用户的预期
+        ctx.accounts.vault_state.check_slippage(
+            amount_received,
+            expected_amount,
+            min_amount_out,
+        )?;
 
-        // 7. 更新用户存款记录
-        let mut updated_deposit = user_deposit.clone();
-        updated_deposit.shares =
-            updated_deposit.shares.checked_sub(shares_amount).ok_or(CustomError::MathOverflow)?;
-        updated_deposit.amount = updated_deposit
-            .amount
-            .checked_sub(tokens_received_from_kamino)
-            .ok_or(CustomError::MathOverflow)?;
+        // 8. 更新状态和费用
+        ctx.accounts.vault_state.record_withdraw(payout_amount, shares_amount, withdraw_fee)?;
+        let now = Clock::get()?.unix_timestamp;
 
-        if updated_deposit.shares == 0 {
-            vault_state.remove_user_deposit(&ctx.accounts.user.key());
-        } else {
-            vault_state.insert_user_deposit(ctx.accounts.user.key(), updated_deposit);
+        // 9. 更新用户持仓
+        let user_key = ctx.accounts.user.key();
+        let user_position = &mut ctx.accounts.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user_key, ctx.bumps.user_position);
         }
+        user_position.record_withdraw(shares_amount)?;
+
+        emit!(VaultWithdrawEvent {
+            user: ctx.accounts.user.key(),
+            vault_id,
+            shares_burned: shares_amount,
+            amount_received,
+            protocol_id: PROTOCOL_KAMINO,
+            timestamp: now,
+        });
 
         msg!(
-            "✅ Vault withdrawal successful: shares={}, gross_tokens={}, fee={}, net_tokens={}",
+            "✅ Vault withdrawal successful: shares={}, gross_tokens={}, fee={}, net_tokens={}, received={}",
             shares_amount,
-            tokens_received_from_kamino,
+            payout_amount,
             withdraw_fee,
-            net_withdrawal_amount
+            net_withdrawal_amount,
+            amount_received
         );
 
         Ok(())
     }
-
-    fn kamino_withdraw_cpi(
-        ctx: &Context<Self>,
-        shares_amount: u64,
-        vault_id: [u8; 32],
-        bump: u8,
-    ) -> Result<u64> {
-        let seeds = &[b"vault-state", vault_id.as_ref(), &[bump]];
-        let signer_seeds = &[&seeds[..]];
-
-        // 构建 Kamino withdraw instruction
-        let kamino_accounts = vec![
-            ctx.accounts.user_shares_account.to_account_info(),
-            ctx.accounts.vault_treasury.to_account_info(),
-            ctx.accounts.kamino_vault_state.to_account_info(),
-            ctx.accounts.kamino_token_vault.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ];
-
-        // 这里需要根据 Kamino 的实际 IDL 构建指令
-        // 暂时返回模拟结果
-        Ok(shares_amount) // 1:1 比例，实际需要根据 Kamino 返回
+    fn kamino_withdraw_accounts(&self) -> KaminoWithdrawAccounts<'info> {
+        KaminoWithdrawAccounts {
+            user: self.vault_state.to_account_info(),
+            vault_state: self.kamino_vault_state.to_account_info(),
+            token_vault: self.kamino_token_vault.to_account_info(),
+            base_vault_authority: self.kamino_base_vault_authority.to_account_info(),
+            user_token_ata: self.vault_treasury.to_account_info(),
+            token_mint: self.base_token_mint.to_account_info(),
+            user_shares_ata: self.vault_kamino_shares.to_account_info(),
+            shares_mint: self.kamino_shares_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            shares_token_program: self.token_program.to_account_info(),
+            klend_program: self.klend_program.to_account_info(),
+            event_authority: self.kamino_event_authority.to_account_info(),
+            kamino_vault_program: self.kamino_program.to_account_info(),
+        }
     }
 }
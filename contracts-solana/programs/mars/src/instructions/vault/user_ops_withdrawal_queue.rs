@@ -0,0 +1,778 @@
+use crate::constants::{
+    PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO, ROLE_REGISTRY_SEED, WITHDRAWAL_ESCROW_SEED,
+    WITHDRAWAL_REQUEST_SEED, WITHDRAWAL_RESERVE_SEED,
+};
+use crate::error::*;
+use crate::events::{
+    WithdrawalCancelled, WithdrawalClaimed, WithdrawalLiquidityReleased, WithdrawalProcessed,
+    WithdrawalRequested,
+};
+use crate::instructions::vault::position_valuation::*;
+use crate::instructions::{
+    invoke_jupiter_lend_withdraw, invoke_kamino_withdraw, JupiterLendWithdrawAccounts,
+    KaminoWithdrawAccounts,
+};
+use crate::state::*;
+use crate::utils::FeeEngine;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{
+    self, Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface, TransferChecked,
+};
+
+/// 发起排队提款：将份额锁入 Vault 托管账户，等待 keeper 处理
+#[derive(Accounts)]
+#[instruction(shares_amount: u64)]
+pub struct RequestWithdrawal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 用户的份额代币账户
+    #[account(
+        mut,
+        constraint = user_shares_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint,
+        constraint = user_shares_account.amount >= shares_amount @ CustomError::InsufficientShares
+    )]
+    pub user_shares_account: Account<'info, TokenAccount>,
+
+    /// 提款请求（每个用户在每个 Vault 同时只能有一个请求）
+    #[account(
+        init,
+        payer = user,
+        space = WithdrawalRequest::space(),
+        seeds = [WITHDRAWAL_REQUEST_SEED, vault_state.vault_id.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    /// 用户的独立持仓账户（通过转账或管理费获得份额的持有人可能还没有，按需创建）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// Mars Vault 份额 Mint
+    #[account(address = vault_state.shares_mint @ CustomError::InvalidMint)]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 份额托管账户（owner 为 vault_state PDA）
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [WITHDRAWAL_ESCROW_SEED, vault_state.vault_id.as_ref()],
+        bump,
+        token::mint = shares_mint,
+        token::authority = vault_state
+    )]
+    pub withdrawal_escrow: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl RequestWithdrawal<'_> {
+    pub fn process_instruction(ctx: Context<Self>, shares_amount: u64) -> Result<()> {
+        require!(shares_amount > 0, CustomError::ZeroAmount);
+        ctx.accounts.vault_state.require_withdrawals_allowed()?;
+        let user_balance_before = ctx.accounts.user_shares_account.amount;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_shares_account.to_account_info(),
+                to: ctx.accounts.withdrawal_escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, shares_amount)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        let user = ctx.accounts.user.key();
+
+        // 处理时会更新持仓记录，提前创建并在请求时校验，以免请求无法处理
+        // 份额以份额代币余额为准：持仓记录先校正为当前余额（含管理费和转账获得的份额）
+        let user_position = &mut ctx.accounts.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user, ctx.bumps.user_position);
+        }
+        user_position.sync_shares(user_balance_before);
+        require!(user_position.shares >= shares_amount, CustomError::InsufficientShares);
+
+        let request = &mut ctx.accounts.withdrawal_request;
+        request.vault_id = vault_id;
+        request.user = user;
+        request.shares = shares_amount;
+        request.status = WithdrawalRequestStatus::Pending;
+        request.claimable_amount = 0;
+        request.withdraw_fee = 0;
+        request.requested_at = now;
+        request.processed_at = 0;
+        request.bump = ctx.bumps.withdrawal_request;
+
+        msg!("⏳ Withdrawal requested: user={}, shares={}", user, shares_amount);
+
+        emit!(WithdrawalRequested {
+            vault_id,
+            user,
+            shares: shares_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// 取消尚未处理的排队提款，份额退回用户
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 用户的份额代币账户（接收退回的份额）
+    #[account(
+        mut,
+        constraint = user_shares_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub user_shares_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_REQUEST_SEED, vault_state.vault_id.as_ref(), user.key().as_ref()],
+        bump = withdrawal_request.bump,
+        constraint = withdrawal_request.status == WithdrawalRequestStatus::Pending
+            @ CustomError::InvalidWithdrawalRequestStatus,
+        close = user
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 份额托管账户
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_ESCROW_SEED, vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub withdrawal_escrow: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl CancelWithdrawal<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let shares = ctx.accounts.withdrawal_request.shares;
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        let bump_seed = [ctx.accounts.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.withdrawal_escrow.to_account_info(),
+                to: ctx.accounts.user_shares_account.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, shares)?;
+
+        msg!("🛑 Withdrawal cancelled: user={}, shares={}", ctx.accounts.user.key(), shares);
+
+        emit!(WithdrawalCancelled {
+            vault_id,
+            user: ctx.accounts.user.key(),
+            shares,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Keeper 处理排队提款：按当前 NAV 固定价格，销毁托管份额，并将应付代币从 Treasury 移入提款储备账户
+///
+/// 调用前 keeper 需确保 Treasury 有足够流动性（先通过 `release_withdrawal_liquidity` 从协议中撤回资金）
+#[derive(Accounts)]
+pub struct ProcessWithdrawal<'info> {
+    /// Vault admin 或 keeper
+    #[account(
+        mut,
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Keeper, &vault_state.admin, &keeper.key())
+            @ CustomError::UnauthorizedSigner
+    )]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_REQUEST_SEED, vault_state.vault_id.as_ref(), withdrawal_request.user.as_ref()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    /// 请求用户的独立持仓账户
+    #[account(
+        mut,
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            withdrawal_request.user.as_ref()
+        ],
+        bump = user_position.bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// Mars Vault 的代币金库
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022）
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, BaseMint>>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Box<Account<'info, Mint>>,
+
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Box<Account<'info, TokenAccount>>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    /// 份额托管账户
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_ESCROW_SEED, vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub withdrawal_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// 提款储备账户（已结算待领取的代币，不计入 NAV）
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        seeds = [WITHDRAWAL_RESERVE_SEED, vault_state.vault_id.as_ref()],
+        bump,
+        token::mint = base_token_mint,
+        token::authority = vault_state,
+        token::token_program = base_token_program
+    )]
+    pub withdrawal_reserve: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// 份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl ProcessWithdrawal<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        ctx.accounts.vault_state.require_withdrawals_allowed()?;
+
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = ctx.accounts.vault_state.key();
+        ctx.accounts
+            .position_valuation
+            .mark_to_market(&mut ctx.accounts.vault_state, &vault_key)?;
+
+        // 1. 先结算管理费和性能费，使提款价格反映费用
+        FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        // 2. 按当前 NAV 固定价格
+        let now = Clock::get()?.unix_timestamp;
+        let treasury_balance = ctx.accounts.vault_treasury.amount;
+        let total_assets = ctx.accounts.vault_state.total_assets(treasury_balance)?;
+        let (claimable_amount, withdraw_fee) =
+            ctx.accounts.withdrawal_request.settle(&ctx.accounts.vault_state, total_assets, now)?;
+        let shares = ctx.accounts.withdrawal_request.shares;
+
+        // 未认领费用留在 Treasury 中，不能用于支付提款
+        let available =
+            treasury_balance.saturating_sub(ctx.accounts.vault_state.total_unclaimed_fees()?);
+        require!(available >= claimable_amount, CustomError::InsufficientLiquidity);
+
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        let bump_seed = [ctx.accounts.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        // 3. 销毁托管的份额
+        let burn_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                from: ctx.accounts.withdrawal_escrow.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::burn(burn_ctx, shares)?;
+
+        // 4. 将应付代币移入提款储备账户
+        let reserve_before = ctx.accounts.withdrawal_reserve.amount;
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
+                to: ctx.accounts.withdrawal_reserve.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(
+            transfer_ctx,
+            claimable_amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
+
+        // 转账费扩展会使储备实际到账少于应付金额，领取时以实际到账为准
+        ctx.accounts.withdrawal_reserve.reload()?;
+        ctx.accounts.withdrawal_request.claimable_amount =
+            ctx.accounts.withdrawal_reserve.amount.saturating_sub(reserve_before);
+
+        // 5. 更新状态和费用
+        let gross_amount =
+            claimable_amount.checked_add(withdraw_fee).ok_or(CustomError::MathOverflow)?;
+        ctx.accounts.vault_state.record_withdraw(gross_amount, shares, withdraw_fee)?;
+
+        ctx.accounts.user_position.record_withdraw(shares)?;
+
+        let user = ctx.accounts.withdrawal_request.user;
+        msg!(
+            "✅ Withdrawal processed: user={}, shares={}, claimable={}, fee={}",
+            user,
+            shares,
+            claimable_amount,
+            withdraw_fee
+        );
+
+        emit!(WithdrawalProcessed {
+            vault_id,
+            user,
+            shares_burned: shares,
+            claimable_amount,
+            withdraw_fee,
+            processed_by: ctx.accounts.keeper.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// Keeper 为排队提款释放流动性：从协议中撤回资金到 Treasury
+///
+/// Vault 正常运行（Active）时 `unwind_all_positions` 不可用，排队提款依赖此指令补充 Treasury 流动性。
+/// 每次从一个协议撤回，只需传入该协议的账户
+#[derive(Accounts)]
+pub struct ReleaseWithdrawalLiquidity<'info> {
+    /// Vault admin 或 keeper
+    #[account(
+        constraint = RoleRegistry::authorizes(&role_registry, Role::Keeper, &vault_state.admin, &keeper.key())
+            @ CustomError::UnauthorizedSigner
+    )]
+    pub keeper: Signer<'info>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// Mars Vault 的代币金库（接收撤回的资金）
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: Box<InterfaceAccount<'info, BaseTokenAccount>>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022）
+    #[account(
+        mut,
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, BaseMint>>,
+
+    // === Kamino（从 Kamino 撤回时传入） ===
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_vault_state: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证
+    #[account(mut)]
+    pub kamino_token_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_base_vault_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证，并与 vault_kamino_shares.mint 比对
+    #[account(mut)]
+    pub kamino_shares_mint: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 持有的 Kamino 份额账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_kamino_shares.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_KAMINO, &vault_kamino_shares.key())
+            @ CustomError::PositionAccountMismatch
+    )]
+    pub vault_kamino_shares: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: Klend 程序
+    #[account(address = crate::constants::protocols::kamino::LEND_PROGRAM_ID)]
+    pub klend_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Kamino 程序验证
+    pub kamino_event_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 硬编码程序 ID
+    #[account(address = crate::constants::protocols::kamino::PROGRAM_ID)]
+    pub kamino_program: Option<UncheckedAccount<'info>>,
+
+    // === Jupiter Lend（从 Jupiter Lend 撤回时传入） ===
+    /// CHECK: 由 Jupiter Lend 程序验证
+    pub jupiter_lending_admin: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Jupiter Lend 程序验证
+    #[account(mut)]
+    pub jupiter_lending: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: 由 Jupiter Lend 程序验证，并与 vault_jupiter_f_token.mint 比对
+    #[account(mut)]
+    pub jupiter_f_token_mint: Option<UncheckedAccount<'info>>,
+
+    /// Mars Vault 持有的 jlToken 账户（owner 为 vault_state PDA，必须是注册表绑定的收益凭证账户）
+    #[account(
+        mut,
+        constraint = vault_jupiter_f_token.owner == vault_state.key() @ CustomError::InvalidOwner,
+        constraint = vault_state.is_bound_receipt_account(PROTOCOL_JUPITER_LEND, &vault_jupiter_f_token.key())
+            @ CustomError::PositionAccountMismatch
+    )]
+    pub vault_jupiter_f_token: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: 硬编码程序 ID
+    #[account(address = crate::constants::protocols::jupiter::LEND_PROGRAM_ID)]
+    pub jupiter_lend_program: Option<UncheckedAccount<'info>>,
+
+    /// 协议份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    /// 角色注册表（可选，未传入时只认 admin）
+    #[account(
+        seeds = [ROLE_REGISTRY_SEED],
+        bump = role_registry.bump
+    )]
+    pub role_registry: Option<Box<Account<'info, RoleRegistry>>>,
+}
+
+impl<'info> ReleaseWithdrawalLiquidity<'info> {
+    /// remaining_accounts: 所选协议取款所需的其他账户（Kamino 的 reserves / lending markets，
+    /// 或 Jupiter Lend SDK 返回的账户）
+    ///
+    /// amount: 按协议汇率计价的撤回金额，不超过该协议的当前持仓价值
+    pub fn process_instruction(
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        protocol_id: u8,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, CustomError::ZeroAmount);
+        ctx.accounts.vault_state.require_withdrawals_allowed()?;
+
+        // 按协议汇率重新计价该协议的持仓，赎回数量不基于过期的账面价值
+        let (rate_source, receipt) = match protocol_id {
+            PROTOCOL_KAMINO => (
+                required_position_account(&ctx.accounts.kamino_vault_state)?,
+                required_position_account(&ctx.accounts.vault_kamino_shares)?,
+            ),
+            PROTOCOL_JUPITER_LEND => (
+                required_position_account(&ctx.accounts.jupiter_lending)?,
+                required_position_account(&ctx.accounts.vault_jupiter_f_token)?,
+            ),
+            _ => return Err(error!(CustomError::UnsupportedProtocol)),
+        };
+        let allocation = ctx.accounts.vault_state.mark_to_market(
+            protocol_id,
+            &rate_source.to_account_info(),
+            &receipt.key(),
+            receipt,
+        )?;
+        let amount = amount.min(allocation);
+        require!(amount > 0, CustomError::InsufficientLiquidity);
+
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        let bump_seed = [ctx.accounts.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        // 1. 从协议撤回到 Treasury
+        let treasury_before = ctx.accounts.vault_treasury.amount;
+        match protocol_id {
+            PROTOCOL_KAMINO => {
+                // 按持仓价值比例计算需要赎回的 Kamino 份额（向上取整）
+                let kamino_accounts = ctx.accounts.kamino_withdraw_accounts()?;
+                let kamino_shares_balance =
+                    required_position_account(&ctx.accounts.vault_kamino_shares)?.amount;
+                let kamino_shares_to_redeem = (amount as u128)
+                    .checked_mul(kamino_shares_balance as u128)
+                    .and_then(|v| v.checked_add(allocation as u128 - 1))
+                    .and_then(|v| v.checked_div(allocation as u128))
+                    .and_then(|v| u64::try_from(v).ok())
+                    .ok_or(CustomError::MathOverflow)?
+                    .min(kamino_shares_balance);
+
+                invoke_kamino_withdraw(
+                    &kamino_accounts,
+                    ctx.remaining_accounts,
+                    kamino_shares_to_redeem,
+                    signer_seeds,
+                )?;
+            }
+            PROTOCOL_JUPITER_LEND => {
+                invoke_jupiter_lend_withdraw(
+                    &ctx.accounts.jupiter_withdraw_accounts()?,
+                    ctx.remaining_accounts,
+                    amount,
+                    signer_seeds,
+                )?;
+            }
+            _ => return Err(error!(CustomError::UnsupportedProtocol)),
+        }
+
+        ctx.accounts.vault_treasury.reload()?;
+        let amount_received = ctx
+            .accounts
+            .vault_treasury
+            .amount
+            .checked_sub(treasury_before)
+            .ok_or(CustomError::CpiCallFailed)?;
+        let min_received = ctx.accounts.vault_state.min_amount_after_slippage(amount)?;
+        require!(amount_received >= min_received, CustomError::SlippageTooHigh);
+
+        // 2. 减记协议持仓（下一次按汇率计价时会校正为实际价值）
+        let now = Clock::get()?.unix_timestamp;
+        let vault_state = &mut ctx.accounts.vault_state;
+        if let Some(protocol) = vault_state.find_protocol_mut(protocol_id) {
+            protocol.current_allocation = protocol.current_allocation.saturating_sub(amount);
+        }
+        vault_state.last_updated = now;
+
+        msg!(
+            "💧 Withdrawal liquidity released: protocol={}, amount={}, received={}",
+            protocol_id,
+            amount,
+            amount_received
+        );
+
+        emit!(WithdrawalLiquidityReleased {
+            vault_id,
+            protocol_id,
+            amount_requested: amount,
+            amount_received,
+            keeper: ctx.accounts.keeper.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    fn kamino_withdraw_accounts(&self) -> Result<KaminoWithdrawAccounts<'info>> {
+        let vault_kamino_shares = required_position_account(&self.vault_kamino_shares)?;
+        let shares_mint = required_position_account(&self.kamino_shares_mint)?;
+        require_keys_eq!(vault_kamino_shares.mint, shares_mint.key(), CustomError::InvalidMint);
+
+        Ok(KaminoWithdrawAccounts {
+            user: self.vault_state.to_account_info(),
+            vault_state: required_position_account(&self.kamino_vault_state)?.to_account_info(),
+            token_vault: required_position_account(&self.kamino_token_vault)?.to_account_info(),
+            base_vault_authority: required_position_account(&self.kamino_base_vault_authority)?
+                .to_account_info(),
+            user_token_ata: self.vault_treasury.to_account_info(),
+            token_mint: self.base_token_mint.to_account_info(),
+            user_shares_ata: vault_kamino_shares.to_account_info(),
+            shares_mint: shares_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            shares_token_program: self.token_program.to_account_info(),
+            klend_program: required_position_account(&self.klend_program)?.to_account_info(),
+            event_authority: required_position_account(&self.kamino_event_authority)?
+                .to_account_info(),
+            kamino_vault_program: required_position_account(&self.kamino_program)?
+                .to_account_info(),
+        })
+    }
+
+    fn jupiter_withdraw_accounts(&self) -> Result<JupiterLendWithdrawAccounts<'info>> {
+        let vault_f_token = required_position_account(&self.vault_jupiter_f_token)?;
+        let f_token_mint = required_position_account(&self.jupiter_f_token_mint)?;
+        require_keys_eq!(vault_f_token.mint, f_token_mint.key(), CustomError::InvalidMint);
+
+        Ok(JupiterLendWithdrawAccounts {
+            signer: self.vault_state.to_account_info(),
+            recipient_token_account: vault_f_token.to_account_info(),
+            depositor_token_account: self.vault_treasury.to_account_info(),
+            lending_admin: required_position_account(&self.jupiter_lending_admin)?
+                .to_account_info(),
+            lending: required_position_account(&self.jupiter_lending)?.to_account_info(),
+            mint: self.base_token_mint.to_account_info(),
+            f_token_mint: f_token_mint.to_account_info(),
+            token_program: self.base_token_program.to_account_info(),
+            jupiter_lend_program: required_position_account(&self.jupiter_lend_program)?
+                .to_account_info(),
+        })
+    }
+}
+
+/// 领取已处理的排队提款
+#[derive(Accounts)]
+pub struct ClaimWithdrawal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 用户的代币账户（接收提取的代币）
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_token_account.mint == vault_state.base_token_mint @ CustomError::InvalidMint
+    )]
+    pub user_token_account: InterfaceAccount<'info, BaseTokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_REQUEST_SEED, vault_state.vault_id.as_ref(), user.key().as_ref()],
+        bump = withdrawal_request.bump,
+        constraint = withdrawal_request.status == WithdrawalRequestStatus::Processed
+            @ CustomError::InvalidWithdrawalRequestStatus,
+        close = user
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// 提款储备账户
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_RESERVE_SEED, vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub withdrawal_reserve: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Vault 基础代币 Mint（SPL Token 或 Token-2022）
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+}
+
+impl ClaimWithdrawal<'_> {
+    pub fn process_instruction(ctx: Context<Self>) -> Result<()> {
+        let amount = ctx.accounts.withdrawal_request.claimable_amount;
+        let vault_id = ctx.accounts.vault_state.vault_id;
+        let bump_seed = [ctx.accounts.vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.withdrawal_reserve.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(
+            transfer_ctx,
+            amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
+
+        let user = ctx.accounts.user.key();
+        msg!("✅ Withdrawal claimed: user={}, amount={}", user, amount);
+
+        emit!(WithdrawalClaimed {
+            vault_id,
+            user,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
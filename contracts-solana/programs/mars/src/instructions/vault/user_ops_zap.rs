@@ -0,0 +1,262 @@
+use crate::error::*;
+use crate::events::{SwapEvent, VaultDepositEvent};
+use crate::instructions::vault::deposit_fee_tiers::*;
+use crate::instructions::vault::position_valuation::*;
+use crate::instructions::{invoke_jupiter_swap, price_impact_bps};
+use crate::state::*;
+use crate::utils::FeeEngine;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint as BaseMint, TokenAccount as BaseTokenAccount};
+
+/// Zap 存款：用户存入任意代币，经 Jupiter 兑换为 Vault 基础代币后按 NAV 铸造份额
+///
+/// Jupiter 路由的输出账户必须是 vault_treasury，兑换所得以 Treasury 余额变化为准。
+/// 资金留在 Treasury 中，由再平衡分配到各协议。
+#[derive(Accounts)]
+#[instruction(amount_in: u64)]
+pub struct ZapDeposit<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 输入代币 Mint（不能是 Vault 基础代币，基础代币直接使用 vault_deposit）
+    #[account(
+        constraint = input_mint.key() != vault_state.base_token_mint @ CustomError::InvalidMint
+    )]
+    pub input_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// 用户的输入代币账户（兑换来源）
+    #[account(
+        mut,
+        constraint = user_input_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_input_token_account.mint == input_mint.key() @ CustomError::InvalidMint,
+        constraint = user_input_token_account.amount >= amount_in @ CustomError::InsufficientFunds
+    )]
+    pub user_input_token_account: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// 用户的份额代币账户（接收 Mars Vault 份额）
+    #[account(
+        mut,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub user_shares_account: Account<'info, TokenAccount>,
+
+    /// 用户的独立持仓账户
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Mars Vault 的代币金库（Jupiter 兑换的输出账户）
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Mars Vault 份额 Mint（mint authority 必须是 vault_state PDA）
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Account<'info, TokenAccount>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    /// Jupiter 聚合器程序
+    /// CHECK: 硬编码程序 ID
+    #[account(constraint = jupiter_program.key() == crate::constants::protocols::jupiter::DEX_PROGRAM_ID)]
+    pub jupiter_program: AccountInfo<'info>,
+
+    /// 分层存款费账户（未启用分层费用时可选账户可省略）
+    pub fee_tier_accounts: DepositFeeTierAccounts<'info>,
+
+    /// 份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ZapDeposit<'info> {
+    /// remaining_accounts: Jupiter 路由账户（按 Swap API 返回的顺序）
+    ///
+    /// - min_out: 兑换后 Treasury 至少收到的基础代币数量
+    /// - quoted_out: Jupiter 报价的输出数量，用于计算价格影响和 max_slippage_bps 下限
+    /// - min_shares_out: 用户可接受的最少份额
+    /// - route_data: Jupiter 兑换指令数据
+    pub fn process_instruction(
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        amount_in: u64,
+        min_out: u64,
+        quoted_out: u64,
+        min_shares_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        require!(amount_in > 0, CustomError::ZeroAmount);
+        require!(quoted_out > 0, CustomError::InvalidMinAmountOut);
+        ctx.accounts.vault_state.require_deposits_allowed()?;
+
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = ctx.accounts.vault_state.key();
+        ctx.accounts
+            .position_valuation
+            .mark_to_market(&mut ctx.accounts.vault_state, &vault_key)?;
+
+        // 1. 先结算管理费和性能费，使新存款不承担此前的费用
+        FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        // 2. 按兑换前的 NAV 定价
+        let treasury_before = ctx.accounts.vault_treasury.amount;
+        let input_before = ctx.accounts.user_input_token_account.amount;
+        let total_assets = ctx.accounts.vault_state.total_assets(treasury_before)?;
+
+        // 3. 通过 Jupiter 将输入代币兑换为基础代币，输出到 Treasury
+        invoke_jupiter_swap(&ctx.accounts.jupiter_program, ctx.remaining_accounts, route_data)?;
+
+        ctx.accounts.vault_treasury.reload()?;
+        ctx.accounts.user_input_token_account.reload()?;
+        let received_amount = ctx
+            .accounts
+            .vault_treasury
+            .amount
+            .checked_sub(treasury_before)
+            .ok_or(CustomError::CpiCallFailed)?;
+        let spent_amount =
+            input_before.saturating_sub(ctx.accounts.user_input_token_account.amount);
+        require!(spent_amount <= amount_in, CustomError::InvalidSwapRoute);
+
+        ctx.accounts.vault_state.check_slippage(received_amount, quoted_out, min_out)?;
+        let price_impact = price_impact_bps(quoted_out, received_amount);
+
+        msg!(
+            "🔁 Zap swap: in={} (spent {}), out={}, quoted={}, impact={} bps",
+            amount_in,
+            spent_amount,
+            received_amount,
+            quoted_out,
+            price_impact
+        );
+
+        // 4. 计算存款费用和应铸造的份额
+        let vault_state = &ctx.accounts.vault_state;
+        let (deposit_fee, tiered_fee) =
+            ctx.accounts.fee_tier_accounts.deposit_fee(vault_state, received_amount)?;
+        let net_deposit_amount =
+            received_amount.checked_sub(deposit_fee).ok_or(CustomError::MathOverflow)?;
+
+        vault_state.check_deposit_limits(
+            received_amount,
+            net_deposit_amount,
+            ctx.accounts.user_position.shares,
+            total_assets,
+        )?;
+        let shares_to_mint = vault_state.convert_to_shares(net_deposit_amount, total_assets)?;
+        require!(shares_to_mint > 0, CustomError::InvalidAmount);
+        require!(shares_to_mint >= min_shares_out, CustomError::SlippageTooHigh);
+
+        // 5. 铸造份额给用户（vault_state PDA 签名）
+        let vault_id = vault_state.vault_id;
+        let base_token_mint = vault_state.base_token_mint;
+        let bump_seed = [vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                to: ctx.accounts.user_shares_account.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(mint_ctx, shares_to_mint)?;
+
+        // 分层存款费转入全局 ata_vault（全局 base_mint 为 SPL Token）
+        if let Some(tiered_fee) = &tiered_fee {
+            ctx.accounts.fee_tier_accounts.collect(
+                tiered_fee,
+                ctx.accounts.vault_treasury.to_account_info(),
+                ctx.accounts.vault_state.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer_seeds,
+            )?;
+        }
+
+        // 6. 记录用户持仓和 Vault 总量
+        let user_key = ctx.accounts.user.key();
+        let user_position = &mut ctx.accounts.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user_key, ctx.bumps.user_position);
+        }
+        user_position.record_deposit(net_deposit_amount, shares_to_mint)?;
+
+        let vault_fee = if tiered_fee.is_some() {
+            0
+        } else {
+            deposit_fee
+        };
+        ctx.accounts.vault_state.record_deposit(net_deposit_amount, shares_to_mint, vault_fee)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        emit!(SwapEvent {
+            vault_id,
+            from_token: ctx.accounts.input_mint.key(),
+            to_token: base_token_mint,
+            amount_in: spent_amount,
+            amount_out: received_amount,
+            price_impact_bps: price_impact,
+            timestamp: now,
+        });
+
+        emit!(VaultDepositEvent {
+            user: user_key,
+            vault_id,
+            amount: received_amount,
+            shares_received: shares_to_mint,
+            protocol_id: 0, // 资金暂留在 Treasury 中
+            timestamp: now,
+        });
+
+        msg!(
+            "✅ Zap deposit successful: received={}, fee={}, net={}, shares={}",
+            received_amount,
+            deposit_fee,
+            net_deposit_amount,
+            shares_to_mint
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,278 @@
+use crate::error::*;
+use crate::events::{SwapEvent, VaultWithdrawEvent};
+use crate::instructions::vault::position_valuation::*;
+use crate::instructions::{invoke_jupiter_swap, price_impact_bps};
+use crate::state::*;
+use crate::utils::FeeEngine;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{
+    self, Mint as BaseMint, TokenAccount as BaseTokenAccount, TokenInterface, TransferChecked,
+};
+
+/// Zap 提款：按 NAV 赎回基础代币，扣除提款费后经 Jupiter 兑换为用户指定的代币
+///
+/// 净额先转入用户的基础代币账户，再由用户签名执行 Jupiter 路由，vault_state PDA 不为路由签名。
+/// 只使用 Treasury 中的可用资金；流动性不足时请使用排队提款。
+#[derive(Accounts)]
+#[instruction(shares_amount: u64)]
+pub struct ZapWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 用户的份额代币账户
+    #[account(
+        mut,
+        constraint = user_shares_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint,
+        constraint = user_shares_account.amount >= shares_amount @ CustomError::InsufficientShares
+    )]
+    pub user_shares_account: Account<'info, TokenAccount>,
+
+    /// 用户的独立持仓账户（通过转账或管理费获得份额的持有人可能还没有，按需创建）
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [
+            UserPosition::SEED_PREFIX,
+            vault_state.vault_id.as_ref(),
+            user.key().as_ref()
+        ],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Mars Vault 状态账户
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.vault_id.as_ref()],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Mars Vault 的代币金库
+    #[account(
+        mut,
+        seeds = [b"vault-treasury", vault_state.vault_id.as_ref()],
+        bump
+    )]
+    pub vault_treasury: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Vault 基础代币 Mint
+    #[account(
+        address = vault_state.base_token_mint @ CustomError::InvalidMint,
+        mint::token_program = base_token_program
+    )]
+    pub base_token_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// 用户的基础代币账户（兑换的输入账户）
+    #[account(
+        mut,
+        constraint = user_base_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_base_token_account.mint == vault_state.base_token_mint @ CustomError::InvalidMint
+    )]
+    pub user_base_token_account: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// 输出代币 Mint（不能是 Vault 基础代币，基础代币直接使用 vault_withdraw）
+    #[account(
+        constraint = output_mint.key() != vault_state.base_token_mint @ CustomError::InvalidMint
+    )]
+    pub output_mint: InterfaceAccount<'info, BaseMint>,
+
+    /// 用户的输出代币账户（Jupiter 路由的输出账户）
+    #[account(
+        mut,
+        constraint = user_output_token_account.owner == user.key() @ CustomError::InvalidOwner,
+        constraint = user_output_token_account.mint == output_mint.key() @ CustomError::InvalidMint
+    )]
+    pub user_output_token_account: InterfaceAccount<'info, BaseTokenAccount>,
+
+    /// Mars Vault 份额 Mint
+    #[account(
+        mut,
+        address = vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub shares_mint: Account<'info, Mint>,
+
+    /// 费用接收方的份额账户（接收管理费份额）
+    #[account(
+        mut,
+        constraint = fee_recipient_shares_account.owner == vault_state.fee_config.fee_recipient @ CustomError::InvalidOwner,
+        constraint = fee_recipient_shares_account.mint == vault_state.shares_mint @ CustomError::InvalidMint
+    )]
+    pub fee_recipient_shares_account: Account<'info, TokenAccount>,
+
+    /// 协议持仓估值账户（有持仓的协议必须传入）
+    pub position_valuation: PositionValuationAccounts<'info>,
+
+    /// Jupiter 聚合器程序
+    /// CHECK: 硬编码程序 ID
+    #[account(constraint = jupiter_program.key() == crate::constants::protocols::jupiter::DEX_PROGRAM_ID)]
+    pub jupiter_program: AccountInfo<'info>,
+
+    /// 份额代币使用的 Token Program
+    pub token_program: Program<'info, Token>,
+    /// 基础代币所属的 Token Program
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ZapWithdraw<'info> {
+    /// remaining_accounts: Jupiter 路由账户（按 Swap API 返回的顺序）
+    ///
+    /// - min_amount_out: 用户至少收到的输出代币数量
+    /// - quoted_out: Jupiter 报价的输出数量，用于计算价格影响和 max_slippage_bps 下限
+    /// - route_data: Jupiter 兑换指令数据
+    pub fn process_instruction(
+        ctx: Context<'_, '_, '_, 'info, Self>,
+        shares_amount: u64,
+        min_amount_out: u64,
+        quoted_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        require!(shares_amount > 0, CustomError::ZeroAmount);
+        require!(quoted_out > 0, CustomError::InvalidMinAmountOut);
+        ctx.accounts.vault_state.require_withdrawals_allowed()?;
+
+        // 按协议汇率重新计价持仓，NAV 不使用过期的账面价值
+        let vault_key = ctx.accounts.vault_state.key();
+        ctx.accounts
+            .position_valuation
+            .mark_to_market(&mut ctx.accounts.vault_state, &vault_key)?;
+
+        // 1. 先结算管理费和性能费，再按当前 NAV 计算份额对应的代币数量
+        FeeEngine::settle_fees(
+            &mut ctx.accounts.vault_state,
+            ctx.accounts.vault_treasury.amount,
+            ctx.accounts.shares_mint.to_account_info(),
+            &ctx.accounts.fee_recipient_shares_account,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        let vault_state = &ctx.accounts.vault_state;
+        let treasury_balance = ctx.accounts.vault_treasury.amount;
+        let total_assets = vault_state.total_assets(treasury_balance)?;
+        let gross_amount = vault_state.convert_to_assets(shares_amount, total_assets)?;
+        require!(gross_amount > 0, CustomError::InvalidAmount);
+
+        let available_liquidity =
+            treasury_balance.saturating_sub(vault_state.total_unclaimed_fees()?);
+        require!(available_liquidity >= gross_amount, CustomError::InsufficientLiquidity);
+
+        // 2. 兑换前扣除提款费（以基础代币计）
+        let withdraw_fee = vault_state.withdraw_fee_for(gross_amount)?;
+        let net_withdrawal_amount =
+            gross_amount.checked_sub(withdraw_fee).ok_or(CustomError::MathOverflow)?;
+
+        let vault_id = vault_state.vault_id;
+        let bump_seed = [vault_state.bump];
+        let seeds: &[&[u8]] = &[b"vault-state", vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        // 3. 销毁用户的份额
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                from: ctx.accounts.user_shares_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::burn(burn_ctx, shares_amount)?;
+
+        // 4. 将净额从 Treasury 转入用户的基础代币账户
+        let base_balance_before = ctx.accounts.user_base_token_account.amount;
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_treasury.to_account_info(),
+                mint: ctx.accounts.base_token_mint.to_account_info(),
+                to: ctx.accounts.user_base_token_account.to_account_info(),
+                authority: ctx.accounts.vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(
+            transfer_ctx,
+            net_withdrawal_amount,
+            ctx.accounts.base_token_mint.decimals,
+        )?;
+
+        ctx.accounts.user_base_token_account.reload()?;
+        let base_received =
+            ctx.accounts.user_base_token_account.amount.saturating_sub(base_balance_before);
+
+        // 5. 由用户签名，通过 Jupiter 将基础代币兑换为输出代币
+        let output_before = ctx.accounts.user_output_token_account.amount;
+        invoke_jupiter_swap(&ctx.accounts.jupiter_program, ctx.remaining_accounts, route_data)?;
+
+        ctx.accounts.user_base_token_account.reload()?;
+        ctx.accounts.user_output_token_account.reload()?;
+        let base_spent = base_balance_before
+            .checked_add(base_received)
+            .ok_or(CustomError::MathOverflow)?
+            .saturating_sub(ctx.accounts.user_base_token_account.amount);
+        require!(base_spent <= base_received, CustomError::InvalidSwapRoute);
+
+        let amount_out = ctx
+            .accounts
+            .user_output_token_account
+            .amount
+            .checked_sub(output_before)
+            .ok_or(CustomError::CpiCallFailed)?;
+        ctx.accounts.vault_state.check_slippage(amount_out, quoted_out, min_amount_out)?;
+        let price_impact = price_impact_bps(quoted_out, amount_out);
+
+        msg!(
+            "🔁 Zap swap: in={} (spent {}), out={}, quoted={}, impact={} bps",
+            base_received,
+            base_spent,
+            amount_out,
+            quoted_out,
+            price_impact
+        );
+
+        // 6. 更新状态和费用（费用以基础代币留在 Treasury 中）
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.record_withdraw(gross_amount, shares_amount, withdraw_fee)?;
+        let now = Clock::get()?.unix_timestamp;
+        let base_token_mint = vault_state.base_token_mint;
+
+        let user_key = ctx.accounts.user.key();
+        let user_position = &mut ctx.accounts.user_position;
+        if user_position.user == Pubkey::default() {
+            user_position.initialize(vault_id, user_key, ctx.bumps.user_position);
+        }
+        user_position.record_withdraw(shares_amount)?;
+
+        emit!(SwapEvent {
+            vault_id,
+            from_token: base_token_mint,
+            to_token: ctx.accounts.output_mint.key(),
+            amount_in: base_spent,
+            amount_out,
+            price_impact_bps: price_impact,
+            timestamp: now,
+        });
+
+        emit!(VaultWithdrawEvent {
+            user: ctx.accounts.user.key(),
+            vault_id,
+            shares_burned: shares_amount,
+            amount_received: base_received,
+            protocol_id: 0, // 仅使用 Treasury 资金
+            timestamp: now,
+        });
+
+        msg!(
+            "✅ Zap withdrawal successful: shares={}, gross={}, fee={}, out={}",
+            shares_amount,
+            gross_amount,
+            withdraw_fee,
+            amount_out
+        );
+
+        Ok(())
+    }
+}
@@ -54,14 +54,112 @@ pub mod mars {
         InitializeVault::process_instruction(ctx, vault_id, platform_fee_bps)
     }
 
-    /// 用户存款到金库
-    pub fn vault_deposit(ctx: Context<VaultDeposit>, amount: u64) -> Result<()> {
-        VaultDeposit::process_instruction(ctx, amount)
+    /// 用户存款到金库（按 NAV 铸造份额，资金部署到 Kamino）
+    pub fn vault_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, VaultDeposit<'info>>,
+        amount: u64,
+        min_shares_out: u64,
+        expected_shares: u64,
+    ) -> Result<()> {
+        VaultDeposit::process_instruction(ctx, amount, min_shares_out, expected_shares)
+    }
+
+    /// 用户从金库提款（按 NAV 销毁份额）
+    pub fn vault_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, VaultWithdraw<'info>>,
+        shares_amount: u64,
+        min_amount_out: u64,
+        expected_amount: u64,
+    ) -> Result<()> {
+        VaultWithdraw::process_instruction(ctx, shares_amount, min_amount_out, expected_amount)
+    }
+
+    /// 将 VaultState 中的旧版用户存款记录迁移到 UserPosition PDA
+    pub fn migrate_user_deposit(ctx: Context<MigrateUserDeposit>) -> Result<()> {
+        MigrateUserDeposit::process_instruction(ctx)
+    }
+
+    /// Zap 存款：任意代币经 Jupiter 兑换为基础代币后存入金库（资金暂留 Treasury）
+    /// remaining_accounts: Jupiter 路由账户
+    pub fn zap_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, ZapDeposit<'info>>,
+        amount_in: u64,
+        min_out: u64,
+        quoted_out: u64,
+        min_shares_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        ZapDeposit::process_instruction(ctx, amount_in, min_out, quoted_out, min_shares_out, route_data)
+    }
+
+    /// Zap 提款：赎回基础代币并扣除提款费后，经 Jupiter 兑换为用户指定的代币
+    /// remaining_accounts: Jupiter 路由账户
+    pub fn zap_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, ZapWithdraw<'info>>,
+        shares_amount: u64,
+        min_amount_out: u64,
+        quoted_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        ZapWithdraw::process_instruction(ctx, shares_amount, min_amount_out, quoted_out, route_data)
+    }
+
+    /// 用户存款到金库，持仓记录在独立的 UserPosition 账户（资金暂留 Treasury）
+    pub fn deposit_with_user_position(
+        ctx: Context<DepositWithUserPosition>,
+        amount: u64,
+        min_shares_out: u64,
+        expected_shares: u64,
+    ) -> Result<()> {
+        DepositWithUserPosition::process_instruction(ctx, amount, min_shares_out, expected_shares)
+    }
+
+    /// 管理员执行再平衡：在 Kamino 与 Jupiter Lend 之间原子地转移资金
+    /// kamino_accounts_len: remaining_accounts 中属于 Kamino 的账户数量，其余属于 Jupiter Lend
+    pub fn execute_rebalance<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteRebalance<'info>>,
+        kamino_accounts_len: u8,
+    ) -> Result<()> {
+        ExecuteRebalance::process_instruction(ctx, kamino_accounts_len)
+    }
+
+    /// Vault 管理员创建再平衡配置
+    pub fn initialize_rebalance_config(
+        ctx: Context<InitializeRebalanceConfig>,
+        deviation_threshold_bps: u16,
+        min_rebalance_amount: u64,
+        max_rebalance_amount: u64,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        InitializeRebalanceConfig::process_instruction(
+            ctx,
+            deviation_threshold_bps,
+            min_rebalance_amount,
+            max_rebalance_amount,
+            cooldown_seconds,
+        )
+    }
+
+    /// Vault 管理员更新再平衡配置（None 表示不修改）
+    pub fn update_rebalance_config(
+        ctx: Context<UpdateRebalanceConfig>,
+        deviation_threshold_bps: Option<u16>,
+        min_rebalance_amount: Option<u64>,
+        max_rebalance_amount: Option<u64>,
+        cooldown_seconds: Option<i64>,
+    ) -> Result<()> {
+        UpdateRebalanceConfig::process_instruction(
+            ctx,
+            deviation_threshold_bps,
+            min_rebalance_amount,
+            max_rebalance_amount,
+            cooldown_seconds,
+        )
     }
 
-    /// 用户从金库提款
-    pub fn vault_withdraw(ctx: Context<VaultWithdraw>, shares_amount: u64) -> Result<()> {
-        VaultWithdraw::process_instruction(ctx, shares_amount)
+    /// Keeper 根据协议链上汇率更新协议 APY
+    pub fn update_protocol_apy(ctx: Context<UpdateProtocolAPY>, protocol_id: u8) -> Result<()> {
+        UpdateProtocolAPY::process_instruction(ctx, protocol_id)
     }
 
     //  Admin can add new freeze authority
@@ -115,21 +213,243 @@ pub mod mars {
         SetFeeTiers::process_instruction(ctx, threshold_amounts.as_ref(), bps_fees.as_ref())
     }
 
+    //  Admin can set the minimum order fee for a destination chain
+    pub fn set_target_chain_min_fee(
+        ctx: Context<SetTargetChainMinFee>,
+        dest_chain_id: u32,
+        token_in: Pubkey,
+        min_fee: u64,
+    ) -> Result<()> {
+        SetTargetChainMinFee::process_instruction(ctx, dest_chain_id, token_in, min_fee)
+    }
+
     //  admin can update threshold amount
     pub fn update_global_state_params(
         ctx: Context<UpdateGlobalStateParams>,
         rebalance_threshold: Option<u16>,
         cross_chain_fee_bps: Option<u16>,
         max_order_amount: Option<u64>,
+        deposit_fee_tiers_enabled: Option<bool>,
     ) -> Result<()> {
         UpdateGlobalStateParams::process_instruction(
             ctx,
             rebalance_threshold,
             cross_chain_fee_bps,
             max_order_amount,
+            deposit_fee_tiers_enabled,
+        )
+    }
+
+    // === 跨链订单 ===
+
+    //  Trader creates an order, USDC is escrowed in ata_vault
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_order(
+        ctx: Context<CreateOrder>,
+        seed: [u8; 32],
+        amount_in: u64,
+        fee: u64,
+        src_chain_id: u32,
+        dest_chain_id: u32,
+        receiver: [u8; 32],
+        token_out: [u8; 32],
+        min_amount_out: String,
+        fill_deadline: i64,
+        order_hash: [u8; 32],
+    ) -> Result<()> {
+        CreateOrder::process_instruction(
+            ctx,
+            seed,
+            amount_in,
+            fee,
+            src_chain_id,
+            dest_chain_id,
+            receiver,
+            token_out,
+            min_amount_out,
+            fill_deadline,
+            order_hash,
+        )
+    }
+
+    //  Orchestrator confirms the order was filled on the destination chain
+    pub fn fill_order(ctx: Context<FillOrder>) -> Result<()> {
+        FillOrder::process_instruction(ctx)
+    }
+
+    //  Trader reverts an unfilled order after the deadline and gets refunded
+    pub fn revert_order(ctx: Context<RevertOrder>) -> Result<()> {
+        RevertOrder::process_instruction(ctx)
+    }
+
+    /// 授予角色（keeper / fee manager / guardian / curator / orchestrator）
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, account: Pubkey) -> Result<()> {
+        GrantRole::process_instruction(ctx, role, account)
+    }
+
+    /// 撤销角色
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: Role, account: Pubkey) -> Result<()> {
+        RevokeRole::process_instruction(ctx, role, account)
+    }
+
+    /// 排队受 timelock 保护的变更（费用、协议注册、全局管理员）
+    pub fn queue_change(
+        ctx: Context<QueueChange>,
+        target: Pubkey,
+        change: PendingChangeData,
+    ) -> Result<()> {
+        QueueChange::process_instruction(ctx, target, change)
+    }
+
+    /// Guardian 或提议者取消待执行的变更
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        CancelChange::process_instruction(ctx)
+    }
+
+    /// 设置 timelock 延迟（延长立即生效，缩短需先排队）
+    pub fn set_timelock_delay(ctx: Context<SetTimelockDelay>, delay_seconds: u64) -> Result<()> {
+        SetTimelockDelay::process_instruction(ctx, delay_seconds)
+    }
+
+    /// 提名新的 Vault 管理员（两步转移）
+    pub fn nominate_vault_admin(ctx: Context<NominateVaultAdmin>, new_admin: Pubkey) -> Result<()> {
+        NominateVaultAdmin::process_instruction(ctx, new_admin)
+    }
+
+    /// 被提名者接受 Vault 管理员角色
+    pub fn accept_vault_admin(ctx: Context<AcceptVaultAdmin>) -> Result<()> {
+        AcceptVaultAdmin::process_instruction(ctx)
+    }
+
+    /// 取消 Vault 管理员提名
+    pub fn cancel_vault_admin_nomination(ctx: Context<CancelVaultAdminNomination>) -> Result<()> {
+        CancelVaultAdminNomination::process_instruction(ctx)
+    }
+
+    /// 为 Vault 注册收益协议（Kamino=1 … Jupiter Lend=5）
+    pub fn add_protocol(
+        ctx: Context<AddProtocol>,
+        protocol_id: u8,
+        program_id: Pubkey,
+        target_allocation_bps: u16,
+        params: Vec<u8>,
+        receipt_account: Pubkey,
+        rate_source: Pubkey,
+    ) -> Result<()> {
+        AddProtocol::process_instruction(
+            ctx,
+            protocol_id,
+            program_id,
+            target_allocation_bps,
+            params,
+            receipt_account,
+            rate_source,
         )
     }
 
+    /// 从 Vault 移除协议（协议中不能有剩余资产）
+    pub fn remove_protocol(ctx: Context<RemoveProtocol>, protocol_id: u8) -> Result<()> {
+        RemoveProtocol::process_instruction(ctx, protocol_id)
+    }
+
+    /// 启用协议
+    pub fn enable_protocol(ctx: Context<UpdateProtocolRegistry>, protocol_id: u8) -> Result<()> {
+        UpdateProtocolRegistry::enable_protocol(ctx, protocol_id)
+    }
+
+    /// 禁用协议
+    pub fn disable_protocol(ctx: Context<UpdateProtocolRegistry>, protocol_id: u8) -> Result<()> {
+        UpdateProtocolRegistry::disable_protocol(ctx, protocol_id)
+    }
+
+    /// 批量设置协议目标分配（启用协议之和必须为 10000 bps）
+    pub fn set_target_allocations(
+        ctx: Context<UpdateProtocolRegistry>,
+        allocations: Vec<ProtocolTargetAllocation>,
+    ) -> Result<()> {
+        UpdateProtocolRegistry::set_target_allocations(ctx, allocations)
+    }
+
+    /// 暂停 Vault（管理员或 guardian）：停止存款，仍允许提款
+    pub fn pause_vault(ctx: Context<UpdateVaultStatus>, reason: String) -> Result<()> {
+        UpdateVaultStatus::pause_vault(ctx, reason)
+    }
+
+    /// 恢复 Vault 为 Active（仅管理员）
+    pub fn resume_vault(ctx: Context<UpdateVaultStatus>, reason: String) -> Result<()> {
+        UpdateVaultStatus::resume_vault(ctx, reason)
+    }
+
+    /// Vault 进入紧急模式（管理员或 guardian）
+    pub fn enter_emergency_mode(ctx: Context<UpdateVaultStatus>, reason: String) -> Result<()> {
+        UpdateVaultStatus::enter_emergency_mode(ctx, reason)
+    }
+
+    /// 关闭 Vault（仅管理员，要求所有份额已赎回）
+    pub fn close_vault(ctx: Context<UpdateVaultStatus>, reason: String) -> Result<()> {
+        UpdateVaultStatus::close_vault(ctx, reason)
+    }
+
+    /// 发起排队提款：锁定份额，等待 keeper 释放流动性后处理
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, shares_amount: u64) -> Result<()> {
+        RequestWithdrawal::process_instruction(ctx, shares_amount)
+    }
+
+    /// 取消尚未处理的排队提款，份额退回用户
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        CancelWithdrawal::process_instruction(ctx)
+    }
+
+    /// Keeper 按当前份额价格处理排队提款
+    pub fn process_withdrawal(ctx: Context<ProcessWithdrawal>) -> Result<()> {
+        ProcessWithdrawal::process_instruction(ctx)
+    }
+
+    /// Keeper 从协议撤回资金到 Treasury，为排队提款释放流动性
+    /// remaining_accounts: 所选协议取款所需的其他账户
+    pub fn release_withdrawal_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseWithdrawalLiquidity<'info>>,
+        protocol_id: u8,
+        amount: u64,
+    ) -> Result<()> {
+        ReleaseWithdrawalLiquidity::process_instruction(ctx, protocol_id, amount)
+    }
+
+    /// 领取已处理的排队提款
+    pub fn claim_withdrawal(ctx: Context<ClaimWithdrawal>) -> Result<()> {
+        ClaimWithdrawal::process_instruction(ctx)
+    }
+
+    /// 紧急模式下按份额比例赎回 Treasury 中的资金（不调用外部协议）
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>, shares_amount: u64) -> Result<()> {
+        EmergencyWithdraw::process_instruction(ctx, shares_amount)
+    }
+
+    /// 管理员将所有协议持仓撤回 Treasury（Vault 需处于 Paused 或 Emergency 状态）
+    /// kamino_accounts_len: remaining_accounts 中属于 Kamino 的账户数量
+    pub fn unwind_all_positions<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnwindAllPositions<'info>>,
+        kamino_accounts_len: u8,
+        reason: String,
+    ) -> Result<()> {
+        UnwindAllPositions::process_instruction(ctx, kamino_accounts_len, reason)
+    }
+
+    /// 管理员创建保险金库（接收 Asset 的 insurance 费用桶）
+    pub fn initialize_insurance_treasury(ctx: Context<InitializeInsuranceTreasury>) -> Result<()> {
+        InitializeInsuranceTreasury::process_instruction(ctx)
+    }
+
+    /// Keeper 按协议汇率检测协议持仓是否低于账面
+    pub fn detect_shortfall(ctx: Context<DetectShortfall>, protocol_id: u8) -> Result<()> {
+        DetectShortfall::process_instruction(ctx, protocol_id)
+    }
+
+    /// Guardian 用保险金库补偿已检测到的协议亏损
+    pub fn cover_shortfall(ctx: Context<CoverShortfall>, amount: u64) -> Result<()> {
+        CoverShortfall::process_instruction(ctx, amount)
+    }
+
     /// 管理员提取 Vault 累积的费用（按类型）
     pub fn claim_fees(ctx: Context<ClaimFees>, amount: u64, fee_type: FeeType) -> Result<()> {
         ClaimFees::process_instruction(ctx, amount, fee_type)
@@ -140,12 +460,53 @@ pub mod mars {
         ClaimFees::claim_all_fees(ctx)
     }
 
+    /// 结算 Vault 的管理费和性能费（任何人都可调用）
+    pub fn accrue_fees(ctx: Context<AccrueFees>) -> Result<()> {
+        AccrueFees::process_instruction(ctx)
+    }
+
     /// 用户领取 Farm 奖励
     /// reward_index: 0 或 1，表示要领取第几个奖励
     pub fn claim_farm_rewards(ctx: Context<ClaimFarmRewards>, reward_index: u64) -> Result<()> {
         ClaimFarmRewards::process_instruction(ctx, reward_index)
     }
 
+    /// 管理员更新 Vault 的费用配置（更新前按旧费率结算费用，未传入的参数保持不变）
+    pub fn update_vault_fee_config(
+        ctx: Context<UpdateVaultFeeConfig>,
+        deposit_fee_bps: Option<u16>,
+        withdraw_fee_bps: Option<u16>,
+        management_fee_bps: Option<u16>,
+        performance_fee_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        UpdateVaultFeeConfig::process_instruction(
+            ctx,
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            management_fee_bps,
+            performance_fee_bps,
+            fee_recipient,
+        )
+    }
+
+    /// 更新 Vault 存款限额（TVL 上限、用户持仓上限、单笔最小 / 最大金额，0 表示不限制）
+    pub fn update_vault_deposit_limits(
+        ctx: Context<UpdateVaultDepositLimits>,
+        max_total_deposits: Option<u64>,
+        max_user_deposit: Option<u64>,
+        min_deposit_amount: Option<u64>,
+        max_deposit_amount: Option<u64>,
+    ) -> Result<()> {
+        UpdateVaultDepositLimits::process_instruction(
+            ctx,
+            max_total_deposits,
+            max_user_deposit,
+            min_deposit_amount,
+            max_deposit_amount,
+        )
+    }
+
     /// 管理员更新 Vault 的平台费率配置
     /// new_platform_fee_bps: 新的平台费率（basis points，如 2500 = 25%）
     pub fn update_vault_platform_fee(
@@ -190,6 +551,26 @@ pub mod mars {
         )
     }
 
+    //  Fee manager claims the base fee bucket of the Asset account
+    pub fn claim_base_fee(ctx: Context<ClaimAssetFee>, amount: u64) -> Result<()> {
+        ClaimAssetFee::process_instruction(ctx, AssetFeeBucket::Base, amount)
+    }
+
+    //  Fee manager claims the lp fee bucket of the Asset account
+    pub fn claim_lp_fee(ctx: Context<ClaimAssetFee>, amount: u64) -> Result<()> {
+        ClaimAssetFee::process_instruction(ctx, AssetFeeBucket::Lp, amount)
+    }
+
+    //  Fee manager claims the protocol fee bucket of the Asset account
+    pub fn claim_protocol_fee(ctx: Context<ClaimAssetFee>, amount: u64) -> Result<()> {
+        ClaimAssetFee::process_instruction(ctx, AssetFeeBucket::Protocol, amount)
+    }
+
+    //  Guardian moves the insurance fee bucket of the Asset account into the insurance treasury
+    pub fn claim_insurance_fee(ctx: Context<ClaimAssetFee>, amount: u64) -> Result<()> {
+        ClaimAssetFee::process_instruction(ctx, AssetFeeBucket::Insurance, amount)
+    }
+
     // Kamino CPI调用: 存款到Kamino Vault（完整实现，匹配Kamino IDL）
     pub fn kamino_deposit<'info>(
         ctx: Context<'_, '_, '_, 'info, KaminoDepositCPI<'info>>,
@@ -124,6 +124,46 @@ impl InsuranceFeeTiers {
     }
 }
 
+/// 按金额规模从 FeeTiers / InsuranceFeeTiers 计算出的费用
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TieredFee {
+    pub fee_bps: u64,
+    pub insurance_fee_bps: u64,
+    pub fee: u64,
+    pub insurance_fee: u64,
+}
+
+impl TieredFee {
+    pub fn compute(
+        fee_tiers: &FeeTiers,
+        insurance_fee_tiers: &InsuranceFeeTiers,
+        amount: u64,
+    ) -> Option<Self> {
+        let fee_bps = FeeTiers::get_bps_fee_for_amount(&fee_tiers.fee_tiers, amount);
+        let insurance_fee_bps = InsuranceFeeTiers::get_insurance_fee_for_amount(
+            &insurance_fee_tiers.insurance_fee_tiers,
+            amount,
+        );
+        let bps_of = |bps: u64| {
+            (amount as u128)
+                .checked_mul(bps as u128)
+                .map(|v| v / BASE_PERCENTAGE as u128)
+                .and_then(|v| u64::try_from(v).ok())
+        };
+
+        Some(Self {
+            fee_bps,
+            insurance_fee_bps,
+            fee: bps_of(fee_bps)?,
+            insurance_fee: bps_of(insurance_fee_bps)?,
+        })
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        self.fee.checked_add(self.insurance_fee)
+    }
+}
+
 impl ProtocolFeeFraction {
     pub fn get_protocol_fee(&self, bps_amount: u64) -> u64 {
         let numerator_u128 = self.numerator as u128;
@@ -133,3 +173,43 @@ impl ProtocolFeeFraction {
         fee as u64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiered_fee_uses_size_tiers() {
+        let fee_tiers = FeeTiers {
+            length: 2,
+            fee_tiers: vec![
+                FeeTier {
+                    threshold_amount: 0,
+                    bps_fee: 30,
+                },
+                FeeTier {
+                    threshold_amount: 10_000_000_000,
+                    bps_fee: 10,
+                },
+            ],
+        };
+        let insurance_fee_tiers = InsuranceFeeTiers {
+            length: 1,
+            insurance_fee_tiers: vec![InsuranceFeeTier {
+                threshold_amount: 0,
+                insurance_fee: 5,
+            }],
+        };
+
+        let small = TieredFee::compute(&fee_tiers, &insurance_fee_tiers, 1_000_000_000).unwrap();
+        assert_eq!(small.fee, 3_000_000);
+        assert_eq!(small.insurance_fee, 500_000);
+        assert_eq!(small.total(), Some(3_500_000));
+
+        // 大额订单落入更低的费率档位
+        let large = TieredFee::compute(&fee_tiers, &insurance_fee_tiers, 20_000_000_000).unwrap();
+        assert_eq!(large.fee_bps, 10);
+        assert_eq!(large.fee, 20_000_000);
+        assert_eq!(large.insurance_fee, 10_000_000);
+    }
+}
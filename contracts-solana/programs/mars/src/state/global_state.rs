@@ -1,3 +1,5 @@
+use crate::error::MarsError;
+use crate::state::ProtocolFeeFraction;
 use anchor_lang::prelude::*;
 
 /**
@@ -30,11 +32,12 @@ pub struct GlobalState {
     // Platform fee wallet address for collecting fees from farm rewards
     pub platform_fee_wallet: Pubkey,
 
-    // unused u64, can be used in future, added to avoid data migration
-    pub unused_u64_1: u64,
+    // delay (seconds) before a queued admin change can be executed, 0 disables the timelock
+    pub timelock_delay_seconds: u64,
 
-    // unused u64, can be used in future, added to avoid data migration
-    pub unused_u64_2: u64,
+    // if set to true, deposits into vaults whose base token is base_mint are charged by
+    // FeeTiers / InsuranceFeeTiers instead of the vault's flat deposit fee
+    pub deposit_fee_tiers_enabled: bool,
 }
 
 /**
@@ -71,6 +74,23 @@ pub struct Asset {
     pub unclaimed_insurance_fee: u64,
 }
 
+// Fee buckets of the Asset account that can be claimed separately
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetFeeBucket {
+    Base,
+    Lp,
+    Protocol,
+    Insurance,
+}
+
+// How a single fee is split across the Asset buckets
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub protocol_fee: u64,
+    pub insurance_fee: u64,
+    pub base_fee: u64,
+}
+
 #[account]
 #[derive(Default)]
 pub struct GlobalStateAuthority {
@@ -104,8 +124,130 @@ impl GlobalState {
     }
 }
 
+impl FeeSplit {
+    // insurance portion comes from the tier, the protocol portion is taken from the rest of
+    // the fee by ProtocolFeeFraction and whatever is left goes to base
+    pub fn compute(
+        fee: u64,
+        insurance_fee: u64,
+        protocol_fee_fraction: &ProtocolFeeFraction,
+    ) -> Option<Self> {
+        let non_insurance_fee = fee.checked_sub(insurance_fee)?;
+        let protocol_fee =
+            protocol_fee_fraction.get_protocol_fee(non_insurance_fee).min(non_insurance_fee);
+
+        Some(Self {
+            protocol_fee,
+            insurance_fee,
+            base_fee: non_insurance_fee - protocol_fee,
+        })
+    }
+}
+
 impl Asset {
     pub fn get_unclaimed_fees(&self) -> u64 {
-        self.unclaimed_base_fee + self.unclaimed_lp_fee + self.unclaimed_protocol_fee
+        self.unclaimed_base_fee
+            + self.unclaimed_lp_fee
+            + self.unclaimed_protocol_fee
+            + self.unclaimed_insurance_fee
+    }
+
+    // Records a fee received in ata_vault into the protocol, insurance and base buckets
+    pub fn record_fee(
+        &mut self,
+        fee: u64,
+        insurance_fee: u64,
+        protocol_fee_fraction: &ProtocolFeeFraction,
+    ) -> Result<FeeSplit> {
+        let split = FeeSplit::compute(fee, insurance_fee, protocol_fee_fraction)
+            .ok_or(MarsError::MathOverflow)?;
+
+        self.total_fee_collected =
+            self.total_fee_collected.checked_add(fee).ok_or(MarsError::MathOverflow)?;
+        self.protocol_fee_collected = self
+            .protocol_fee_collected
+            .checked_add(split.protocol_fee)
+            .ok_or(MarsError::MathOverflow)?;
+        self.unclaimed_protocol_fee = self
+            .unclaimed_protocol_fee
+            .checked_add(split.protocol_fee)
+            .ok_or(MarsError::MathOverflow)?;
+        self.insurance_fee_collected = self
+            .insurance_fee_collected
+            .checked_add(split.insurance_fee)
+            .ok_or(MarsError::MathOverflow)?;
+        self.unclaimed_insurance_fee = self
+            .unclaimed_insurance_fee
+            .checked_add(split.insurance_fee)
+            .ok_or(MarsError::MathOverflow)?;
+        self.base_fee_collected =
+            self.base_fee_collected.checked_add(split.base_fee).ok_or(MarsError::MathOverflow)?;
+        self.unclaimed_base_fee =
+            self.unclaimed_base_fee.checked_add(split.base_fee).ok_or(MarsError::MathOverflow)?;
+
+        Ok(split)
+    }
+
+    pub fn unclaimed(&self, bucket: AssetFeeBucket) -> u64 {
+        match bucket {
+            AssetFeeBucket::Base => self.unclaimed_base_fee,
+            AssetFeeBucket::Lp => self.unclaimed_lp_fee,
+            AssetFeeBucket::Protocol => self.unclaimed_protocol_fee,
+            AssetFeeBucket::Insurance => self.unclaimed_insurance_fee,
+        }
+    }
+
+    // Deducts a claim from the unclaimed amount of the bucket
+    pub fn claim(&mut self, bucket: AssetFeeBucket, amount: u64) -> Result<()> {
+        let unclaimed = match bucket {
+            AssetFeeBucket::Base => &mut self.unclaimed_base_fee,
+            AssetFeeBucket::Lp => &mut self.unclaimed_lp_fee,
+            AssetFeeBucket::Protocol => &mut self.unclaimed_protocol_fee,
+            AssetFeeBucket::Insurance => &mut self.unclaimed_insurance_fee,
+        };
+        *unclaimed = unclaimed.checked_sub(amount).ok_or(MarsError::InsufficientFunds)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_fee_splits_into_buckets() {
+        let fraction = ProtocolFeeFraction {
+            numerator: 1,
+            denominator: 4,
+        };
+        let mut asset = Asset::default();
+
+        // 5 insurance, 25% of the remaining 100 to protocol, 75 to base
+        let split = asset.record_fee(105, 5, &fraction).unwrap();
+        assert_eq!(
+            split,
+            FeeSplit {
+                protocol_fee: 25,
+                insurance_fee: 5,
+                base_fee: 75,
+            }
+        );
+        assert_eq!(asset.total_fee_collected, 105);
+        assert_eq!(asset.get_unclaimed_fees(), 105);
+
+        asset.claim(AssetFeeBucket::Insurance, 5).unwrap();
+        assert_eq!(asset.unclaimed_insurance_fee, 0);
+        assert_eq!(asset.insurance_fee_collected, 5);
+        assert!(asset.claim(AssetFeeBucket::Protocol, 26).is_err());
+
+        // a fraction above 1 cannot take more than the non insurance fee
+        let greedy = ProtocolFeeFraction {
+            numerator: 2,
+            denominator: 1,
+        };
+        let split = FeeSplit::compute(10, 0, &greedy).unwrap();
+        assert_eq!(split.protocol_fee, 10);
+        assert_eq!(split.base_fee, 0);
     }
 }
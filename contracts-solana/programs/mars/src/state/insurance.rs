@@ -0,0 +1,100 @@
+use crate::error::CustomError;
+use anchor_lang::prelude::*;
+
+/// 协议亏损记录（PDA: [SHORTFALL_SEED, vault_id]）
+///
+/// 持仓按协议汇率重新计价时，价值下跌计入 `ProtocolConfig.unrecovered_loss`（亏损随即计入 NAV）。
+/// keeper 检测时记录该亏损，guardian 随后用保险金库补足 Vault Treasury 并等额减记亏损。
+#[account]
+pub struct ShortfallRecord {
+    /// 所属 Vault 的 ID
+    pub vault_id: [u8; 32],
+
+    /// 出现亏损的协议 ID
+    pub protocol_id: u8,
+
+    /// 检测时按协议汇率测得的持仓价值
+    pub measured_value: u64,
+
+    /// 尚未补偿的缺口（检测或补偿时的 unrecovered_loss）
+    pub shortfall: u64,
+
+    /// 累计已补偿金额
+    pub total_covered: u64,
+
+    /// 检测时间
+    pub detected_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ShortfallRecord {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // vault_id
+        1 + // protocol_id
+        8 + // measured_value
+        8 + // shortfall
+        8 + // total_covered
+        8 + // detected_at
+        1 // bump
+    }
+
+    /// 记录一次检测结果，返回缺口
+    pub fn record(
+        &mut self,
+        protocol_id: u8,
+        measured_value: u64,
+        unrecovered_loss: u64,
+        now: i64,
+    ) -> u64 {
+        self.protocol_id = protocol_id;
+        self.measured_value = measured_value;
+        self.shortfall = unrecovered_loss;
+        self.detected_at = now;
+        self.shortfall
+    }
+
+    /// 记录一次补偿，remaining_loss 为补偿后协议剩余的未恢复亏损
+    pub fn record_cover(&mut self, amount: u64, remaining_loss: u64) -> Result<()> {
+        self.shortfall = remaining_loss;
+        self.total_covered =
+            self.total_covered.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_record() -> ShortfallRecord {
+        ShortfallRecord {
+            vault_id: [1u8; 32],
+            protocol_id: 0,
+            measured_value: 0,
+            shortfall: 0,
+            total_covered: 0,
+            detected_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_shortfall_detect_and_cover() {
+        let mut record = empty_record();
+        assert!(record.try_to_vec().unwrap().len() + 8 <= ShortfallRecord::space());
+
+        // 账面 1000 的持仓计价为 945，未恢复亏损 55
+        assert_eq!(record.record(1, 945, 55, 100), 55);
+
+        record.record_cover(50, 5).unwrap();
+        assert_eq!(record.shortfall, 5);
+        assert_eq!(record.total_covered, 50);
+
+        record.record_cover(5, 0).unwrap();
+        assert_eq!(record.shortfall, 0);
+        assert_eq!(record.total_covered, 55);
+    }
+}
@@ -17,3 +17,23 @@ pub use order::*;
 // === User Position (独立账户，解决账户大小限制) ===
 pub mod user_position;
 pub use user_position::*;
+
+// === Rebalance Configuration ===
+pub mod rebalance_config;
+pub use rebalance_config::*;
+
+// === Timelocked Admin Changes ===
+pub mod pending_change;
+pub use pending_change::*;
+
+// === Role-Based Access Control ===
+pub mod role_registry;
+pub use role_registry::*;
+
+// === Withdrawal Queue ===
+pub mod withdrawal_request;
+pub use withdrawal_request::*;
+
+// === Insurance Fund ===
+pub mod insurance;
+pub use insurance::*;
@@ -1,3 +1,5 @@
+use crate::constants::*;
+use crate::state::TieredFee;
 use anchor_lang::prelude::*;
 
 #[account]
@@ -58,8 +60,8 @@ pub struct Order {
     // chain id of the destination chain
     pub dest_chain_id: u32,
 
-    // unused i64, will be removed if we do data migration
-    pub unused_i64_1: i64,
+    // timestamp after which an unfilled order can be reverted by the trader
+    pub fill_deadline: i64,
 
     // mint of token_in (corresponding to bytes32 in Solidity)
     pub token_in: [u8; 32],
@@ -78,4 +80,88 @@ pub struct Order {
 
     // uinique identifier of the order outside the chain
     pub order_hash: [u8; 32],
+
+    // swap fee rate charged at creation, refunded if the order is reverted
+    pub swap_fee_bps: u16,
+
+    // insurance portion of the fee, kept by the protocol even if the order is reverted
+    pub insurance_fee: u64,
+
+    // PDA bump
+    pub bump: u8,
+}
+
+impl TargetChainMinFee {
+    pub fn space() -> usize {
+        8 + // discriminator
+        4 + // dest_chain_id
+        32 + // token_in
+        8 // min_fee
+    }
+}
+
+impl Order {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // seed
+        8 + // amount_in
+        32 + // trader
+        32 + // receiver
+        4 + // src_chain_id
+        4 + // dest_chain_id
+        8 + // fill_deadline
+        32 + // token_in
+        2 + // status
+        8 + // fee
+        4 + MAX_MIN_AMOUNT_OUT_LENGTH + // min_amount_out
+        32 + // token_out
+        32 + // order_hash
+        2 + // swap_fee_bps
+        8 + // insurance_fee
+        1 // bump
+    }
+
+    /// min_amount_out is a decimal uint256 from the destination chain
+    pub fn is_valid_min_amount_out(min_amount_out: &str) -> bool {
+        !min_amount_out.is_empty()
+            && min_amount_out.len() <= MAX_MIN_AMOUNT_OUT_LENGTH
+            && min_amount_out.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Minimum fee for an order: the tiered swap and insurance fees plus the target chain minimum
+    pub fn required_fee(tiered_fee: &TieredFee, min_fee: u64) -> Option<u64> {
+        tiered_fee.total()?.checked_add(min_fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_amount_out_validation() {
+        assert!(Order::is_valid_min_amount_out("1000000"));
+        assert!(!Order::is_valid_min_amount_out(""));
+        assert!(!Order::is_valid_min_amount_out("-1"));
+        assert!(!Order::is_valid_min_amount_out("1e18"));
+        assert!(!Order::is_valid_min_amount_out(&"9".repeat(MAX_MIN_AMOUNT_OUT_LENGTH + 1)));
+    }
+
+    #[test]
+    fn test_required_fee_and_refund() {
+        // 0.3% swap fee + 0.05% insurance + 2 USDC target chain minimum on a 1,000 USDC order
+        let tiered_fee = TieredFee {
+            fee_bps: 30,
+            insurance_fee_bps: 5,
+            fee: 3_000_000,
+            insurance_fee: 500_000,
+        };
+        let fee = Order::required_fee(&tiered_fee, 2_000_000).unwrap();
+        assert_eq!(fee, 5_500_000);
+
+        // reverting refunds the swap fee, insurance and the target chain minimum are kept
+        let refund = crate::util::calculate_refund_amount(1_000_000_000, fee, 30);
+        assert_eq!(refund.protocol_fee, 2_500_000);
+        assert_eq!(refund.refund_amount, 997_500_000);
+    }
 }
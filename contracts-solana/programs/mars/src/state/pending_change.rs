@@ -0,0 +1,353 @@
+use crate::constants::MAX_FEE_TIERS_LENGTH;
+use crate::state::ProtocolTargetAllocation;
+use anchor_lang::prelude::*;
+
+/// 待执行的管理变更（timelock）
+///
+/// 管理员先排队变更，等待 `GlobalState.timelock_delay_seconds` 后才能执行；
+/// 在此期间 guardian 可以取消。每个目标账户的每种变更同时只能有一个待执行项。
+#[account]
+pub struct PendingChange {
+    /// 变更目标（vault_state 或 global_state 地址）
+    pub target: Pubkey,
+
+    /// 变更类型（见 `PendingChangeData::kind`）
+    pub kind: u8,
+
+    /// 变更内容（执行时的参数必须与此完全一致）
+    pub change: PendingChangeData,
+
+    /// 排队变更的管理员（取消或执行后租金退还给该地址）
+    pub proposer: Pubkey,
+
+    /// 排队时间
+    pub queued_at: i64,
+
+    /// 最早可执行时间
+    pub eta: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum PendingChangeData {
+    /// 更新 Vault 费用配置（与 `update_vault_fee_config` 参数一致）
+    VaultFeeConfig {
+        deposit_fee_bps: Option<u16>,
+        withdraw_fee_bps: Option<u16>,
+        management_fee_bps: Option<u16>,
+        performance_fee_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+    },
+    /// 更新 Vault 平台费率
+    VaultPlatformFee {
+        platform_fee_bps: u16,
+    },
+    /// 为 Vault 注册新协议
+    AddProtocol {
+        protocol_id: u8,
+        program_id: Pubkey,
+        target_allocation_bps: u16,
+        params: Vec<u8>,
+        receipt_account: Pubkey,
+        rate_source: Pubkey,
+    },
+    /// 提名新的全局管理员
+    GlobalAdmin {
+        new_admin: Pubkey,
+    },
+    /// 缩短 timelock 延迟（延长立即生效）
+    TimelockDelay {
+        delay_seconds: u64,
+    },
+    /// 提名新的 Vault 管理员
+    VaultAdmin {
+        new_admin: Pubkey,
+    },
+    /// 设置跨链订单费用层级（与 `set_fee_tiers` 参数一致）
+    FeeTiers {
+        threshold_amounts: Vec<u64>,
+        bps_fees: Vec<u64>,
+    },
+    /// 设置保险费用层级（与 `set_insurance_fee_tiers` 参数一致）
+    InsuranceFeeTiers {
+        threshold_amounts: Vec<u64>,
+        insurance_fees: Vec<u64>,
+    },
+    /// 设置协议费用比例
+    ProtocolFeeFraction {
+        protocol_fee_numerator: u64,
+        protocol_fee_denominator: u64,
+    },
+    /// 更新全局费用参数（`update_global_state_params` 中影响费用的字段）
+    GlobalStateParams {
+        cross_chain_fee_bps: Option<u16>,
+        deposit_fee_tiers_enabled: Option<bool>,
+    },
+    /// 启用 / 禁用 Vault 中的协议
+    ProtocolEnabled {
+        protocol_id: u8,
+        enabled: bool,
+    },
+    /// 批量设置 Vault 协议目标分配
+    TargetAllocations {
+        allocations: Vec<ProtocolTargetAllocation>,
+    },
+    /// 更新 Vault 存款限额（与 `update_vault_deposit_limits` 参数一致）
+    VaultDepositLimits {
+        max_total_deposits: Option<u64>,
+        max_user_deposit: Option<u64>,
+        min_deposit_amount: Option<u64>,
+        max_deposit_amount: Option<u64>,
+    },
+}
+
+impl PendingChangeData {
+    pub const KIND_VAULT_FEE_CONFIG: u8 = 0;
+    pub const KIND_VAULT_PLATFORM_FEE: u8 = 1;
+    pub const KIND_ADD_PROTOCOL: u8 = 2;
+    pub const KIND_GLOBAL_ADMIN: u8 = 3;
+    pub const KIND_TIMELOCK_DELAY: u8 = 4;
+    pub const KIND_VAULT_ADMIN: u8 = 5;
+    pub const KIND_FEE_TIERS: u8 = 6;
+    pub const KIND_INSURANCE_FEE_TIERS: u8 = 7;
+    pub const KIND_PROTOCOL_FEE_FRACTION: u8 = 8;
+    pub const KIND_GLOBAL_STATE_PARAMS: u8 = 9;
+    pub const KIND_PROTOCOL_ENABLED: u8 = 10;
+    pub const KIND_TARGET_ALLOCATIONS: u8 = 11;
+    pub const KIND_VAULT_DEPOSIT_LIMITS: u8 = 12;
+
+    /// 最大序列化长度（费用层级最大：1 + 4 + 8 × 10 + 4 + 8 × 10）
+    ///
+    /// 费用层级数量在排队时限制为 MAX_FEE_TIERS_LENGTH，AddProtocol 为
+    /// 1 + 1 + 32 + 2 + 4 + 32 + 32 + 32，小于此值
+    pub const MAX_SPACE: usize = 1 + (4 + 8 * MAX_FEE_TIERS_LENGTH) * 2;
+
+    pub fn kind(&self) -> u8 {
+        match self {
+            PendingChangeData::VaultFeeConfig {
+                ..
+            } => Self::KIND_VAULT_FEE_CONFIG,
+            PendingChangeData::VaultPlatformFee {
+                ..
+            } => Self::KIND_VAULT_PLATFORM_FEE,
+            PendingChangeData::AddProtocol {
+                ..
+            } => Self::KIND_ADD_PROTOCOL,
+            PendingChangeData::GlobalAdmin {
+                ..
+            } => Self::KIND_GLOBAL_ADMIN,
+            PendingChangeData::TimelockDelay {
+                ..
+            } => Self::KIND_TIMELOCK_DELAY,
+            PendingChangeData::VaultAdmin {
+                ..
+            } => Self::KIND_VAULT_ADMIN,
+            PendingChangeData::FeeTiers {
+                ..
+            } => Self::KIND_FEE_TIERS,
+            PendingChangeData::InsuranceFeeTiers {
+                ..
+            } => Self::KIND_INSURANCE_FEE_TIERS,
+            PendingChangeData::ProtocolFeeFraction {
+                ..
+            } => Self::KIND_PROTOCOL_FEE_FRACTION,
+            PendingChangeData::GlobalStateParams {
+                ..
+            } => Self::KIND_GLOBAL_STATE_PARAMS,
+            PendingChangeData::ProtocolEnabled {
+                ..
+            } => Self::KIND_PROTOCOL_ENABLED,
+            PendingChangeData::TargetAllocations {
+                ..
+            } => Self::KIND_TARGET_ALLOCATIONS,
+            PendingChangeData::VaultDepositLimits {
+                ..
+            } => Self::KIND_VAULT_DEPOSIT_LIMITS,
+        }
+    }
+
+    /// 目标是否为 Vault（否则为 GlobalState）
+    pub fn targets_vault(&self) -> bool {
+        matches!(
+            self,
+            PendingChangeData::VaultFeeConfig { .. }
+                | PendingChangeData::VaultPlatformFee { .. }
+                | PendingChangeData::AddProtocol { .. }
+                | PendingChangeData::VaultAdmin { .. }
+                | PendingChangeData::ProtocolEnabled { .. }
+                | PendingChangeData::TargetAllocations { .. }
+                | PendingChangeData::VaultDepositLimits { .. }
+        )
+    }
+}
+
+impl PendingChange {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // target
+        1 + // kind
+        PendingChangeData::MAX_SPACE + // change
+        32 + // proposer
+        8 + // queued_at
+        8 + // eta
+        1 // bump
+    }
+
+    /// 是否已过等待期
+    pub fn is_executable_at(&self, now: i64) -> bool {
+        now >= self.eta
+    }
+
+    /// 检查一次管理变更是否满足 timelock
+    ///
+    /// - 未启用 timelock（延迟为 0）且未提供待执行项：直接放行
+    /// - 否则必须提供目标一致、内容一致且已到期的待执行项
+    pub fn authorize(
+        delay_seconds: u64,
+        pending: Option<&PendingChange>,
+        target: &Pubkey,
+        expected: &PendingChangeData,
+        now: i64,
+    ) -> Result<()> {
+        let pending = match pending {
+            Some(pending) => pending,
+            None if delay_seconds == 0 => return Ok(()),
+            None => return err!(crate::error::CustomError::TimelockRequired),
+        };
+
+        require_keys_eq!(pending.target, *target, crate::error::CustomError::PendingChangeMismatch);
+        require!(pending.change == *expected, crate::error::CustomError::PendingChangeMismatch);
+        require!(pending.is_executable_at(now), crate::error::CustomError::TimelockNotExpired);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(target: Pubkey, change: PendingChangeData, eta: i64) -> PendingChange {
+        PendingChange {
+            target,
+            kind: change.kind(),
+            change,
+            proposer: Pubkey::new_unique(),
+            queued_at: 0,
+            eta,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_authorize_without_timelock() {
+        let target = Pubkey::new_unique();
+        let change = PendingChangeData::VaultPlatformFee {
+            platform_fee_bps: 100,
+        };
+
+        assert!(PendingChange::authorize(0, None, &target, &change, 0).is_ok());
+        assert!(PendingChange::authorize(3600, None, &target, &change, 0).is_err());
+    }
+
+    #[test]
+    fn test_authorize_requires_matching_matured_change() {
+        let target = Pubkey::new_unique();
+        let change = PendingChangeData::VaultPlatformFee {
+            platform_fee_bps: 100,
+        };
+        let queued = pending(target, change.clone(), 1_000);
+
+        // 未到期
+        assert!(PendingChange::authorize(3600, Some(&queued), &target, &change, 999).is_err());
+        // 已到期
+        assert!(PendingChange::authorize(3600, Some(&queued), &target, &change, 1_000).is_ok());
+
+        // 参数或目标不一致
+        let other = PendingChangeData::VaultPlatformFee {
+            platform_fee_bps: 200,
+        };
+        assert!(PendingChange::authorize(3600, Some(&queued), &target, &other, 1_000).is_err());
+        let other_target = Pubkey::new_unique();
+        assert!(
+            PendingChange::authorize(3600, Some(&queued), &other_target, &change, 1_000).is_err()
+        );
+    }
+
+    #[test]
+    fn test_change_targets() {
+        let fee_fraction = PendingChangeData::ProtocolFeeFraction {
+            protocol_fee_numerator: 1,
+            protocol_fee_denominator: 100,
+        };
+        assert!(!fee_fraction.targets_vault());
+
+        let global_params = PendingChangeData::GlobalStateParams {
+            cross_chain_fee_bps: Some(30),
+            deposit_fee_tiers_enabled: None,
+        };
+        assert!(!global_params.targets_vault());
+
+        // 协议启停、目标分配和存款限额作用于 Vault
+        let protocol_enabled = PendingChangeData::ProtocolEnabled {
+            protocol_id: 1,
+            enabled: false,
+        };
+        let limits = PendingChangeData::VaultDepositLimits {
+            max_total_deposits: Some(1_000_000),
+            max_user_deposit: None,
+            min_deposit_amount: None,
+            max_deposit_amount: None,
+        };
+        assert!(protocol_enabled.targets_vault());
+        assert!(limits.targets_vault());
+        assert_ne!(protocol_enabled.kind(), limits.kind());
+    }
+
+    #[test]
+    fn test_max_space_covers_largest_change() {
+        let tiers = vec![u64::MAX; MAX_FEE_TIERS_LENGTH];
+        let change = PendingChangeData::FeeTiers {
+            threshold_amounts: tiers.clone(),
+            bps_fees: tiers.clone(),
+        };
+        assert_eq!(change.try_to_vec().unwrap().len(), PendingChangeData::MAX_SPACE);
+
+        let insurance_change = PendingChangeData::InsuranceFeeTiers {
+            threshold_amounts: tiers.clone(),
+            insurance_fees: tiers,
+        };
+        assert!(insurance_change.try_to_vec().unwrap().len() <= PendingChangeData::MAX_SPACE);
+
+        let add_protocol = PendingChangeData::AddProtocol {
+            protocol_id: 1,
+            program_id: Pubkey::new_unique(),
+            target_allocation_bps: 10_000,
+            params: vec![0; crate::state::ProtocolConfig::MAX_PARAMS_LEN],
+            receipt_account: Pubkey::new_unique(),
+            rate_source: Pubkey::new_unique(),
+        };
+        assert!(add_protocol.try_to_vec().unwrap().len() <= PendingChangeData::MAX_SPACE);
+
+        let allocations = (0..crate::constants::MAX_PROTOCOLS_PER_VAULT as u8)
+            .map(|protocol_id| ProtocolTargetAllocation {
+                protocol_id,
+                target_allocation_bps: 1_000,
+            })
+            .collect();
+        let allocation_change = PendingChangeData::TargetAllocations {
+            allocations,
+        };
+        assert!(allocation_change.try_to_vec().unwrap().len() <= PendingChangeData::MAX_SPACE);
+
+        let fee_change = PendingChangeData::VaultFeeConfig {
+            deposit_fee_bps: Some(1),
+            withdraw_fee_bps: Some(1),
+            management_fee_bps: Some(1),
+            performance_fee_bps: Some(1),
+            fee_recipient: Some(Pubkey::new_unique()),
+        };
+        assert!(fee_change.try_to_vec().unwrap().len() <= PendingChangeData::MAX_SPACE);
+    }
+}
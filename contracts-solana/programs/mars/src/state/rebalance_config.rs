@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+/// 每个 Vault 的再平衡配置（PDA: [REBALANCE_CONFIG_SEED, vault_id]）
+#[account]
+pub struct RebalanceConfig {
+    /// 所属Vault的ID
+    pub vault_id: [u8; 32],
+
+    /// 触发再平衡的偏差阈值（基点）
+    /// 例如：500 = 5% 的偏差将触发再平衡
+    pub deviation_threshold_bps: u16,
+
+    /// 最小再平衡金额（避免频繁小额操作）
+    pub min_rebalance_amount: u64,
+
+    /// 最大单次再平衡金额（风险控制）
+    pub max_rebalance_amount: u64,
+
+    /// 再平衡冷却时间（秒）
+    pub cooldown_seconds: i64,
+
+    /// 最后一次再平衡时间
+    pub last_rebalance_time: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl RebalanceConfig {
+    pub const DEFAULT_DEVIATION_THRESHOLD_BPS: u16 = 500; // 5%
+    pub const DEFAULT_MIN_AMOUNT: u64 = 10_000_000; // $10 (6位小数)
+    pub const DEFAULT_MAX_AMOUNT: u64 = 1_000_000_000_000; // $1M
+    pub const DEFAULT_COOLDOWN_SECONDS: i64 = 3600; // 1小时
+
+    pub fn space() -> usize {
+        8 +  // discriminator
+        32 + // vault_id
+        2 +  // deviation_threshold_bps
+        8 +  // min_rebalance_amount
+        8 +  // max_rebalance_amount
+        8 +  // cooldown_seconds
+        8 +  // last_rebalance_time
+        1 // bump
+    }
+
+    /// 校验配置参数
+    pub fn validate_params(
+        deviation_threshold_bps: u16,
+        min_rebalance_amount: u64,
+        max_rebalance_amount: u64,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            deviation_threshold_bps > 0 && deviation_threshold_bps <= 10_000,
+            crate::error::CustomError::InvalidRebalanceConfig
+        );
+        require!(
+            max_rebalance_amount > 0 && min_rebalance_amount <= max_rebalance_amount,
+            crate::error::CustomError::InvalidRebalanceConfig
+        );
+        require!(cooldown_seconds >= 0, crate::error::CustomError::InvalidRebalanceConfig);
+        Ok(())
+    }
+
+    /// 检查是否在冷却期
+    pub fn is_in_cooldown(&self) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        Ok(self.is_in_cooldown_at(current_time))
+    }
+
+    pub fn is_in_cooldown_at(&self, current_time: i64) -> bool {
+        let time_since_last = current_time.saturating_sub(self.last_rebalance_time);
+        time_since_last < self.cooldown_seconds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_params() {
+        assert!(RebalanceConfig::validate_params(500, 10, 100, 3600).is_ok());
+        assert!(RebalanceConfig::validate_params(0, 10, 100, 3600).is_err());
+        assert!(RebalanceConfig::validate_params(500, 200, 100, 3600).is_err());
+        assert!(RebalanceConfig::validate_params(500, 10, 100, -1).is_err());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let config = RebalanceConfig {
+            vault_id: [0u8; 32],
+            deviation_threshold_bps: 500,
+            min_rebalance_amount: 10,
+            max_rebalance_amount: 100,
+            cooldown_seconds: 3600,
+            last_rebalance_time: 10_000,
+            bump: 255,
+        };
+
+        assert!(config.is_in_cooldown_at(10_000 + 3599));
+        assert!(!config.is_in_cooldown_at(10_000 + 3600));
+    }
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+
+/// 全局角色注册表（PDA: [ROLE_REGISTRY_SEED]）
+///
+/// 由全局 admin 授予 / 撤销，使自动化 bot 等不必持有 admin 私钥。
+/// admin 本身始终拥有所有角色的权限。
+#[account]
+pub struct RoleRegistry {
+    /// Keeper：执行再平衡、更新协议 APY
+    pub keepers: Vec<Pubkey>,
+
+    /// Fee manager：更新费率、领取费用
+    pub fee_managers: Vec<Pubkey>,
+
+    /// Guardian：暂停 Vault、进入紧急模式、取消 timelock 变更
+    pub guardians: Vec<Pubkey>,
+
+    /// Curator：创建 Vault（成为该 Vault 的 admin）
+    ///
+    /// 注册表是全局的，curator 角色本身不授予对其他 Vault 的管理权限；
+    /// 协议目标分配、存款限额和再平衡配置只能由各 Vault 的 admin 修改
+    pub curators: Vec<Pubkey>,
+
+    /// Orchestrator：确认跨链订单已在目标链成交
+    pub orchestrators: Vec<Pubkey>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Keeper,
+    FeeManager,
+    Guardian,
+    Curator,
+    Orchestrator,
+}
+
+impl RoleRegistry {
+    pub fn space() -> usize {
+        8 + // discriminator
+        5 * (4 + 32 * crate::constants::MAX_ROLE_MEMBERS) + // role lists
+        1 // bump
+    }
+
+    pub fn members(&self, role: Role) -> &Vec<Pubkey> {
+        match role {
+            Role::Keeper => &self.keepers,
+            Role::FeeManager => &self.fee_managers,
+            Role::Guardian => &self.guardians,
+            Role::Curator => &self.curators,
+            Role::Orchestrator => &self.orchestrators,
+        }
+    }
+
+    fn members_mut(&mut self, role: Role) -> &mut Vec<Pubkey> {
+        match role {
+            Role::Keeper => &mut self.keepers,
+            Role::FeeManager => &mut self.fee_managers,
+            Role::Guardian => &mut self.guardians,
+            Role::Curator => &mut self.curators,
+            Role::Orchestrator => &mut self.orchestrators,
+        }
+    }
+
+    pub fn has_role(&self, role: Role, account: &Pubkey) -> bool {
+        self.members(role).contains(account)
+    }
+
+    pub fn grant(&mut self, role: Role, account: Pubkey) -> Result<()> {
+        require!(account != Pubkey::default(), crate::error::CustomError::InvalidParameter);
+        let members = self.members_mut(role);
+        require!(!members.contains(&account), crate::error::CustomError::AuthorityAlreadyExists);
+        require!(
+            members.len() < crate::constants::MAX_ROLE_MEMBERS,
+            crate::error::CustomError::RoleMembersLimitExceeded
+        );
+        members.push(account);
+        Ok(())
+    }
+
+    pub fn revoke(&mut self, role: Role, account: &Pubkey) -> Result<()> {
+        let members = self.members_mut(role);
+        require!(members.contains(account), crate::error::CustomError::AuthorityDoesNotExist);
+        members.retain(|member| member != account);
+        Ok(())
+    }
+
+    /// signer 是否为 admin 或拥有指定角色（未传入注册表时只认 admin）
+    pub fn authorizes(
+        registry: &Option<Box<Account<RoleRegistry>>>,
+        role: Role,
+        admin: &Pubkey,
+        signer: &Pubkey,
+    ) -> bool {
+        signer == admin || registry.as_ref().is_some_and(|registry| registry.has_role(role, signer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_registry() -> RoleRegistry {
+        RoleRegistry {
+            keepers: vec![],
+            fee_managers: vec![],
+            guardians: vec![],
+            curators: vec![],
+            orchestrators: vec![],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_grant_and_revoke_role() {
+        let mut registry = empty_registry();
+        let keeper = Pubkey::new_unique();
+
+        registry.grant(Role::Keeper, keeper).unwrap();
+        assert!(registry.has_role(Role::Keeper, &keeper));
+        assert!(!registry.has_role(Role::Curator, &keeper));
+
+        // 重复授予 / 撤销不存在的角色
+        assert!(registry.grant(Role::Keeper, keeper).is_err());
+        assert!(registry.revoke(Role::Guardian, &keeper).is_err());
+
+        registry.revoke(Role::Keeper, &keeper).unwrap();
+        assert!(!registry.has_role(Role::Keeper, &keeper));
+    }
+
+    #[test]
+    fn test_role_members_limit() {
+        let mut registry = empty_registry();
+        for _ in 0..crate::constants::MAX_ROLE_MEMBERS {
+            registry.grant(Role::FeeManager, Pubkey::new_unique()).unwrap();
+        }
+        assert!(registry.grant(Role::FeeManager, Pubkey::new_unique()).is_err());
+        assert!(registry.try_to_vec().unwrap().len() + 8 <= RoleRegistry::space());
+    }
+}
@@ -113,21 +113,48 @@ impl UserPosition {
         Ok(())
     }
 
-    /// 记录提款
-    pub fn record_withdraw(&mut self, shares_burned: u64) -> Result<()> {
-        require!(self.shares >= shares_burned, crate::error::CustomError::InsufficientShares);
+    /// 合并 VaultState 中的旧版存款记录
+    pub fn absorb_legacy_deposit(&mut self, deposit: &super::UserDeposit) -> Result<()> {
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(deposit.amount)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
 
         self.shares = self
             .shares
-            .checked_sub(shares_burned)
+            .checked_add(deposit.shares)
             .ok_or(error!(crate::error::CustomError::MathOverflow))?;
 
+        self.total_rewards_claimed = self
+            .total_rewards_claimed
+            .checked_add(deposit.total_rewards)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+
+        self.first_deposit_time = self.first_deposit_time.min(deposit.timestamp);
+        self.last_action_time = self.last_action_time.max(deposit.last_action_time);
+        self.deposit_count = self.deposit_count.saturating_add(1);
+
+        Ok(())
+    }
+
+    /// 记录提款
+    ///
+    /// 赎回以份额代币余额为准：管理费增发或转账得到的份额不会记入持仓，
+    /// 因此持仓记录的份额可能少于本次销毁的份额，此时归零
+    pub fn record_withdraw(&mut self, shares_burned: u64) -> Result<()> {
+        self.shares = self.shares.saturating_sub(shares_burned);
+
         self.withdraw_count = self.withdraw_count.saturating_add(1);
         self.last_action_time = Clock::get()?.unix_timestamp;
 
         Ok(())
     }
 
+    /// 以份额代币余额校正持仓记录的份额（管理费增发或转账会使两者不一致）
+    pub fn sync_shares(&mut self, share_balance: u64) {
+        self.shares = share_balance;
+    }
+
     /// 记录奖励领取
     pub fn record_rewards_claimed(&mut self, amount: u64) -> Result<()> {
         self.total_rewards_claimed = self
@@ -237,4 +264,39 @@ mod tests {
         assert_eq!(pda1, pda2);
         assert_eq!(bump1, bump2);
     }
+
+    #[test]
+    fn test_absorb_legacy_deposit() {
+        let mut position = UserPosition {
+            vault_id: [1u8; 32],
+            user: Pubkey::new_unique(),
+            total_deposited: 500,
+            shares: 400,
+            first_deposit_time: 2_000,
+            last_action_time: 2_000,
+            total_rewards_claimed: 0,
+            deposit_count: 1,
+            withdraw_count: 0,
+            protocol_allocations: Vec::new(),
+            lifetime_apy_bps: 0,
+            bump: 255,
+            reserved: [0; 64],
+        };
+        let legacy = crate::state::UserDeposit {
+            amount: 1_000,
+            shares: 900,
+            timestamp: 1_000,
+            last_action_time: 1_500,
+            total_rewards: 7,
+        };
+
+        position.absorb_legacy_deposit(&legacy).unwrap();
+
+        assert_eq!(position.total_deposited, 1_500);
+        assert_eq!(position.shares, 1_300);
+        assert_eq!(position.total_rewards_claimed, 7);
+        assert_eq!(position.first_deposit_time, 1_000);
+        assert_eq!(position.last_action_time, 2_000);
+        assert_eq!(position.deposit_count, 2);
+    }
 }
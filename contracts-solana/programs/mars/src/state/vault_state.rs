@@ -1,4 +1,6 @@
+use crate::utils::PositionValuation;
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
 #[account]
 pub struct VaultState {
@@ -38,7 +40,8 @@ pub struct VaultState {
     /// 支持的协议列表
     pub supported_protocols: Vec<ProtocolConfig>,
 
-    /// 用户存款记录 (使用Vec存储)
+    /// 旧版用户存款记录（已弃用，仅保留账户布局）
+    /// 用户持仓已迁移到独立的 UserPosition PDA，见 `migrate_user_deposit`
     pub user_deposits: Vec<UserDepositEntry>,
 
     /// 再平衡记录
@@ -80,8 +83,24 @@ pub struct VaultState {
     /// 总共收取的平台费（从奖励中收取）
     pub total_platform_fee_collected: u64,
 
-    /// 保留字段用于未来扩展
-    pub reserved: [u8; 48],
+    /// 上次结算管理费的时间（0 表示尚未开始计费）
+    pub last_fee_accrual_time: i64,
+
+    /// 份额价格高水位（精度见 `FeeEngine::SHARE_PRICE_PRECISION`，0 表示尚未记录）
+    /// 只有份额价格超过高水位的部分才收取性能费
+    pub high_water_mark: u64,
+
+    /// 金库 TVL 上限（按 NAV 计算，0 表示不限制）
+    pub max_total_deposits: u64,
+
+    /// 单个用户持仓上限（按 NAV 计算，0 表示不限制）
+    pub max_user_deposit: u64,
+
+    /// 单笔最小存款金额（0 表示不限制）
+    pub min_deposit_amount: u64,
+
+    /// 单笔最大存款金额（0 表示不限制）
+    pub max_deposit_amount: u64,
 }
 
 impl VaultState {
@@ -119,7 +138,12 @@ impl VaultState {
         8 + // total_performance_fee_collected
         8 + // total_rewards_claimed
         8 + // total_platform_fee_collected
-        48 // reserved
+        8 + // last_fee_accrual_time
+        8 + // high_water_mark
+        8 + // max_total_deposits
+        8 + // max_user_deposit
+        8 + // min_deposit_amount
+        8 // max_deposit_amount
     }
 
     /// 计算最大空间（包含所有动态内容）
@@ -128,10 +152,49 @@ impl VaultState {
     pub fn max_space_estimate() -> usize {
         Self::space() +
         10 * ProtocolConfig::space() + // 最多10个协议
-        100 * UserDepositEntry::space() + // 最多100个用户（降低以适应Solana限制）
         100 * RebalanceRecord::space() // 最多100条记录
     }
 
+    /// 按当前动态数组长度计算账户所需空间（用于 realloc）
+    pub fn current_space(&self) -> usize {
+        Self::space()
+            + self.supported_protocols.len() * ProtocolConfig::space()
+            + self.user_deposits.len() * UserDepositEntry::space()
+            + self.rebalance_history.len() * RebalanceRecord::space()
+    }
+
+    /// 追加一条再平衡记录后所需的空间（达到历史上限后旧记录被移除，不再增长）
+    pub fn space_with_new_rebalance_record(&self) -> usize {
+        if self.rebalance_history.len() >= crate::constants::MAX_REBALANCE_HISTORY {
+            self.current_space()
+        } else {
+            self.current_space() + RebalanceRecord::space()
+        }
+    }
+
+    /// 按 max_slippage_bps 计算可接受的最小到账金额
+    pub fn min_amount_after_slippage(&self, amount: u64) -> Result<u64> {
+        (amount as u128)
+            .checked_mul(10_000u128.saturating_sub(self.max_slippage_bps as u128))
+            .and_then(|v| v.checked_div(10_000))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 滑点检查：实际到账不低于用户签名的最小值，且相对用户提交的预期值偏离不超过 max_slippage_bps
+    ///
+    /// 预期值必须来自用户（链下报价），不能用同一笔交易内的 NAV 推算，否则下限形同虚设；
+    /// 预期值为 0 时 max_slippage_bps 不起作用，直接拒绝
+    pub fn check_slippage(&self, actual: u64, expected: u64, user_min: u64) -> Result<()> {
+        require!(expected > 0, crate::error::CustomError::InvalidMinAmountOut);
+        require!(actual >= user_min, crate::error::CustomError::SlippageTooHigh);
+        require!(
+            actual >= self.min_amount_after_slippage(expected)?,
+            crate::error::CustomError::SlippageTooHigh
+        );
+        Ok(())
+    }
+
     /// 更新再平衡记录
     pub fn update_rebalance_record(
         &mut self,
@@ -152,7 +215,7 @@ impl VaultState {
         };
 
         // 保留最近100条记录，超出时移除最旧的
-        if self.rebalance_history.len() >= 100 {
+        if self.rebalance_history.len() >= crate::constants::MAX_REBALANCE_HISTORY {
             // 安全地移除第一个元素（最旧的记录）
             if !self.rebalance_history.is_empty() {
                 self.rebalance_history.remove(0);
@@ -172,49 +235,368 @@ impl VaultState {
         Ok(500) // 5% APY (基点表示)
     }
 
-    /// 获取用户的总份额价值
-    pub fn get_user_share_value(&self, user: &Pubkey) -> Option<u64> {
-        self.user_deposits.iter().find(|entry| entry.user == *user).map(|entry| {
-            if self.total_shares == 0 {
-                0
-            } else {
-                // 使用 checked_mul 和 checked_div 避免溢出
-                entry
-                    .deposit
-                    .shares
-                    .checked_mul(self.total_deposits)
-                    .and_then(|val| val.checked_div(self.total_shares))
-                    .unwrap_or(0)
-            }
+    /// 查找协议配置
+    pub fn find_protocol(&self, protocol_id: u8) -> Option<&ProtocolConfig> {
+        self.supported_protocols.iter().find(|p| p.protocol_id == protocol_id)
+    }
+
+    /// 查找协议配置的可变引用
+    pub fn find_protocol_mut(&mut self, protocol_id: u8) -> Option<&mut ProtocolConfig> {
+        self.supported_protocols.iter_mut().find(|p| p.protocol_id == protocol_id)
+    }
+
+    /// 注册新协议（默认启用）
+    pub fn add_protocol(&mut self, protocol: ProtocolConfig) -> Result<()> {
+        require!(
+            self.supported_protocols.len() < crate::constants::MAX_PROTOCOLS_PER_VAULT,
+            crate::error::CustomError::AllocationLimitExceeded
+        );
+        require!(
+            self.find_protocol(protocol.protocol_id).is_none(),
+            crate::error::CustomError::ProtocolAlreadyRegistered
+        );
+        protocol.validate()?;
+        require!(
+            self.enabled_target_allocation_bps() + protocol.target_allocation_bps as u32 <= 10_000,
+            crate::error::CustomError::AllocationLimitExceeded
+        );
+
+        self.supported_protocols.push(protocol);
+        Ok(())
+    }
+
+    /// 移除协议（仍有资产部署在该协议中时不允许移除）
+    pub fn remove_protocol(&mut self, protocol_id: u8) -> Result<ProtocolConfig> {
+        let index = self
+            .supported_protocols
+            .iter()
+            .position(|p| p.protocol_id == protocol_id)
+            .ok_or(crate::error::CustomError::UnsupportedProtocol)?;
+        require!(
+            self.supported_protocols[index].current_allocation == 0,
+            crate::error::CustomError::ProtocolNotEmpty
+        );
+        Ok(self.supported_protocols.remove(index))
+    }
+
+    /// 批量设置目标分配比例
+    ///
+    /// 未出现在列表中的协议目标置 0；启用协议的目标之和必须等于 10000
+    pub fn set_target_allocations(
+        &mut self,
+        allocations: &[ProtocolTargetAllocation],
+    ) -> Result<()> {
+        for (i, allocation) in allocations.iter().enumerate() {
+            require!(
+                allocations[..i].iter().all(|a| a.protocol_id != allocation.protocol_id),
+                crate::error::CustomError::InvalidProtocolConfig
+            );
+            require!(
+                self.find_protocol(allocation.protocol_id).is_some(),
+                crate::error::CustomError::UnsupportedProtocol
+            );
+        }
+
+        for protocol in self.supported_protocols.iter_mut() {
+            let target_bps = allocations
+                .iter()
+                .find(|a| a.protocol_id == protocol.protocol_id)
+                .map(|a| a.target_allocation_bps)
+                .unwrap_or(0);
+            protocol.target_allocation_bps = target_bps;
+            protocol.allocation_weight_bps = target_bps;
+        }
+
+        require!(
+            self.enabled_target_allocation_bps() == 10_000,
+            crate::error::CustomError::InvalidAllocationSum
+        );
+        Ok(())
+    }
+
+    /// 启用协议的目标分配之和（基点）
+    pub fn enabled_target_allocation_bps(&self) -> u32 {
+        self.supported_protocols
+            .iter()
+            .filter(|p| p.enabled)
+            .map(|p| p.target_allocation_bps as u32)
+            .sum()
+    }
+
+    /// 所有未认领费用之和（这部分代币属于费用接收方，不计入用户资产）
+    pub fn total_unclaimed_fees(&self) -> Result<u64> {
+        self.unclaimed_deposit_fee
+            .checked_add(self.unclaimed_withdraw_fee)
+            .and_then(|v| v.checked_add(self.unclaimed_management_fee))
+            .and_then(|v| v.checked_add(self.unclaimed_performance_fee))
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 已部署到各协议中的资产总额（最近一次按协议汇率计价的价值）
+    pub fn total_protocol_allocation(&self) -> Result<u64> {
+        self.supported_protocols.iter().try_fold(0u64, |acc, p| {
+            acc.checked_add(p.current_allocation)
+                .ok_or(error!(crate::error::CustomError::MathOverflow))
         })
     }
 
-    /// 查找用户存款记录
-    pub fn find_user_deposit(&self, user: &Pubkey) -> Option<&UserDeposit> {
-        self.user_deposits.iter().find(|entry| entry.user == *user).map(|entry| &entry.deposit)
+    /// 收益凭证账户必须是注册表中绑定的账户（协议必须已注册）
+    pub fn require_receipt_account(&self, protocol_id: u8, receipt_key: &Pubkey) -> Result<()> {
+        let protocol = self
+            .find_protocol(protocol_id)
+            .ok_or(error!(crate::error::CustomError::UnsupportedProtocol))?;
+        require_keys_eq!(
+            *receipt_key,
+            protocol.receipt_account,
+            crate::error::CustomError::PositionAccountMismatch
+        );
+        Ok(())
     }
 
-    /// 查找用户存款记录的可变引用
-    pub fn find_user_deposit_mut(&mut self, user: &Pubkey) -> Option<&mut UserDeposit> {
-        self.user_deposits
-            .iter_mut()
-            .find(|entry| entry.user == *user)
-            .map(|entry| &mut entry.deposit)
+    /// 账户约束使用：协议已注册时收益凭证账户必须与绑定一致
+    ///
+    /// 协议未注册时不会向其存取，传入的账户不被使用
+    pub fn is_bound_receipt_account(&self, protocol_id: u8, receipt_key: &Pubkey) -> bool {
+        self.find_protocol(protocol_id).is_none_or(|p| p.receipt_account == *receipt_key)
     }
 
-    /// 添加或更新用户存款
-    pub fn insert_user_deposit(&mut self, user: Pubkey, deposit: UserDeposit) {
-        if let Some(entry) = self.user_deposits.iter_mut().find(|entry| entry.user == user) {
-            entry.deposit = deposit;
-        } else {
-            self.user_deposits.push(UserDepositEntry {
-                user,
-                deposit,
-            });
+    /// 读取协议收益凭证的汇率（EXCHANGE_RATE_PRECISION 精度）
+    ///
+    /// rate_source / receipt 必须是注册表中绑定的账户，调用方无法自选价格来源
+    pub fn protocol_exchange_rate(
+        &self,
+        protocol_id: u8,
+        rate_source: &AccountInfo,
+        receipt_key: &Pubkey,
+        receipt: &TokenAccount,
+    ) -> Result<u128> {
+        let protocol = self
+            .find_protocol(protocol_id)
+            .ok_or(error!(crate::error::CustomError::UnsupportedProtocol))?;
+        require_keys_eq!(
+            rate_source.key(),
+            protocol.rate_source,
+            crate::error::CustomError::PositionAccountMismatch
+        );
+        self.require_receipt_account(protocol_id, receipt_key)?;
+
+        PositionValuation::exchange_rate(
+            protocol_id,
+            rate_source,
+            &self.base_token_mint,
+            &receipt.mint,
+        )
+    }
+
+    /// 按协议自身汇率重新计价持仓，更新 current_allocation 并返回持仓价值
+    ///
+    /// 定价前调用，以免使用过期的账面价值；价值下跌计入协议的 unrecovered_loss，
+    /// 供保险金库补偿
+    pub fn mark_to_market(
+        &mut self,
+        protocol_id: u8,
+        rate_source: &AccountInfo,
+        receipt_key: &Pubkey,
+        receipt: &TokenAccount,
+    ) -> Result<u64> {
+        let rate = self.protocol_exchange_rate(protocol_id, rate_source, receipt_key, receipt)?;
+        let value = PositionValuation::value_of(receipt.amount, rate)?;
+        if let Some(protocol) = self.find_protocol_mut(protocol_id) {
+            protocol.revalue(value);
+        }
+        Ok(value)
+    }
+
+    /// 计算金库总资产（NAV）
+    ///
+    /// 总资产 = Treasury 余额 + 各协议持仓（按 mark_to_market 的计价）- 未认领费用
+    pub fn total_assets(&self, treasury_balance: u64) -> Result<u64> {
+        let gross = treasury_balance
+            .checked_add(self.total_protocol_allocation()?)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        Ok(gross.saturating_sub(self.total_unclaimed_fees()?))
+    }
+
+    /// 按当前 NAV 将代币数量换算为份额（向下取整，有利于金库）
+    pub fn convert_to_shares(&self, amount: u64, total_assets: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            // 首次存款，1:1 比例
+            return Ok(amount);
+        }
+
+        // 已有份额但资产归零时无法定价，拒绝存款以免稀释
+        require!(total_assets > 0, crate::error::CustomError::InvalidVaultState);
+
+        (amount as u128)
+            .checked_mul(self.total_shares as u128)
+            .and_then(|v| v.checked_div(total_assets as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 按当前 NAV 将份额换算为代币数量（向下取整，有利于金库）
+    pub fn convert_to_assets(&self, shares: u64, total_assets: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(0);
+        }
+
+        (shares as u128)
+            .checked_mul(total_assets as u128)
+            .and_then(|v| v.checked_div(self.total_shares as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 紧急模式下按份额比例分配 Treasury 中的可用资金（不含未认领费用，向下取整）
+    ///
+    /// 仍在协议中的持仓不参与分配，由剩余持有人在资金撤回后继续享有
+    pub fn emergency_redeem_amount(&self, shares: u64, treasury_balance: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(0);
+        }
+
+        let available = treasury_balance.saturating_sub(self.total_unclaimed_fees()?);
+        (shares as u128)
+            .checked_mul(available as u128)
+            .and_then(|v| v.checked_div(self.total_shares as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 计算存款费用
+    pub fn deposit_fee_for(&self, amount: u64) -> Result<u64> {
+        (amount as u128)
+            .checked_mul(self.fee_config.deposit_fee_bps as u128)
+            .and_then(|v| v.checked_div(10_000))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 计算提款费用
+    pub fn withdraw_fee_for(&self, amount: u64) -> Result<u64> {
+        (amount as u128)
+            .checked_mul(self.fee_config.withdraw_fee_bps as u128)
+            .and_then(|v| v.checked_div(10_000))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(crate::error::CustomError::MathOverflow))
+    }
+
+    /// 检查存款限额：单笔最小 / 最大金额、金库 TVL 上限和用户持仓上限
+    ///
+    /// `net_amount` 为扣除存款费后的金额，`user_shares` 为用户存款前持有的份额
+    pub fn check_deposit_limits(
+        &self,
+        amount: u64,
+        net_amount: u64,
+        user_shares: u64,
+        total_assets: u64,
+    ) -> Result<()> {
+        require!(
+            self.min_deposit_amount == 0 || amount >= self.min_deposit_amount,
+            crate::error::CustomError::DepositBelowMinimum
+        );
+        require!(
+            self.max_deposit_amount == 0 || amount <= self.max_deposit_amount,
+            crate::error::CustomError::DepositAboveMaximum
+        );
+
+        if self.max_total_deposits > 0 {
+            let total_after = total_assets
+                .checked_add(net_amount)
+                .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+            require!(
+                total_after <= self.max_total_deposits,
+                crate::error::CustomError::VaultDepositCapExceeded
+            );
+        }
+
+        if self.max_user_deposit > 0 {
+            let user_after = self
+                .convert_to_assets(user_shares, total_assets)?
+                .checked_add(net_amount)
+                .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+            require!(
+                user_after <= self.max_user_deposit,
+                crate::error::CustomError::UserDepositCapExceeded
+            );
         }
+
+        Ok(())
+    }
+
+    /// 记录一笔存款：更新总量和存款费用统计
+    pub fn record_deposit(&mut self, net_amount: u64, shares: u64, fee: u64) -> Result<()> {
+        self.total_deposits = self
+            .total_deposits
+            .checked_add(net_amount)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.total_shares = self
+            .total_shares
+            .checked_add(shares)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.unclaimed_deposit_fee = self
+            .unclaimed_deposit_fee
+            .checked_add(fee)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.total_deposit_fee_collected = self
+            .total_deposit_fee_collected
+            .checked_add(fee)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.last_updated = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// 记录一笔提款：gross_amount 为扣费前的赎回金额，费用留在 Treasury 中
+    pub fn record_withdraw(&mut self, gross_amount: u64, shares: u64, fee: u64) -> Result<()> {
+        // 收益部分会使提款超过本金，因此使用饱和减法
+        self.total_deposits = self.total_deposits.saturating_sub(gross_amount);
+        self.total_shares = self
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.unclaimed_withdraw_fee = self
+            .unclaimed_withdraw_fee
+            .checked_add(fee)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.total_withdraw_fee_collected = self
+            .total_withdraw_fee_collected
+            .checked_add(fee)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+        self.last_updated = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// 存款（以及其他增加份额的操作）仅在 Active 状态下允许
+    pub fn require_deposits_allowed(&self) -> Result<()> {
+        match self.status {
+            VaultStatus::Active => Ok(()),
+            VaultStatus::Paused => err!(crate::error::CustomError::VaultPaused),
+            VaultStatus::Emergency => err!(crate::error::CustomError::EmergencyMode),
+            VaultStatus::Closed => err!(crate::error::CustomError::VaultClosed),
+        }
+    }
+
+    /// 普通提款在 Active 和 Paused 状态下允许；Emergency 状态下只能走紧急提款
+    pub fn require_withdrawals_allowed(&self) -> Result<()> {
+        match self.status {
+            VaultStatus::Active | VaultStatus::Paused => Ok(()),
+            VaultStatus::Emergency => err!(crate::error::CustomError::EmergencyMode),
+            VaultStatus::Closed => err!(crate::error::CustomError::VaultClosed),
+        }
+    }
+
+    /// 关闭后的金库不接受任何用户操作
+    pub fn require_not_closed(&self) -> Result<()> {
+        require!(self.status != VaultStatus::Closed, crate::error::CustomError::VaultClosed);
+        Ok(())
+    }
+
+    /// 查找旧版用户存款记录
+    pub fn find_user_deposit(&self, user: &Pubkey) -> Option<&UserDeposit> {
+        self.user_deposits.iter().find(|entry| entry.user == *user).map(|entry| &entry.deposit)
     }
 
-    /// 删除用户存款记录
+    /// 删除旧版用户存款记录（迁移时使用）
     pub fn remove_user_deposit(&mut self, user: &Pubkey) -> Option<UserDeposit> {
         if let Some(pos) = self.user_deposits.iter().position(|entry| entry.user == *user) {
             Some(self.user_deposits.remove(pos).deposit)
@@ -284,12 +666,146 @@ pub struct ProtocolConfig {
 
     /// 协议特定参数
     pub params: Vec<u8>,
+
+    /// Vault 持有的收益凭证账户（Kamino 份额 / jlToken 账户）
+    pub receipt_account: Pubkey,
+
+    /// 收益凭证的汇率来源（Kamino Vault 状态 / Jupiter Lending 账户）
+    pub rate_source: Pubkey,
+
+    /// 最近一次根据收益凭证汇率变化计算的年化收益率（基点）
+    pub apy_bps: u64,
+
+    /// 最近一次记录的收益凭证汇率（EXCHANGE_RATE_PRECISION 精度）
+    pub last_price: u64,
+
+    /// 最近一次记录汇率的时间
+    pub last_price_time: i64,
+
+    /// 重新计价时确认的、尚未恢复或由保险金库补偿的持仓亏损
+    pub unrecovered_loss: u64,
 }
 
 impl ProtocolConfig {
+    /// 协议特定参数的最大长度
+    pub const MAX_PARAMS_LEN: usize = 32;
+
     pub fn space() -> usize {
-        1 + 32 + 1 + 2 + 8 + 2 + 4 + 32 // protocol_id + program_id + enabled + weights + allocation + params
+        1 + 32 + 1 + 2 + 8 + 2 + 4 + 32 + // protocol_id + program_id + enabled + weights + allocation + params
+        32 + 32 + // receipt_account + rate_source
+        8 + 8 + 8 + // apy_bps + last_price + last_price_time
+        8 // unrecovered_loss
+    }
+
+    /// 新注册的协议配置（默认启用，尚无持仓和价格记录）
+    pub fn new(
+        protocol_id: u8,
+        program_id: Pubkey,
+        target_allocation_bps: u16,
+        params: Vec<u8>,
+        receipt_account: Pubkey,
+        rate_source: Pubkey,
+    ) -> Self {
+        Self {
+            protocol_id,
+            program_id,
+            enabled: true,
+            allocation_weight_bps: target_allocation_bps,
+            current_allocation: 0,
+            target_allocation_bps,
+            params,
+            receipt_account,
+            rate_source,
+            apy_bps: 0,
+            last_price: 0,
+            last_price_time: 0,
+            unrecovered_loss: 0,
+        }
+    }
+
+    /// 按汇率重新计价持仓：价值下跌计入 unrecovered_loss，回升时先抵减未恢复的亏损
+    pub fn revalue(&mut self, value: u64) {
+        if value < self.current_allocation {
+            self.unrecovered_loss =
+                self.unrecovered_loss.saturating_add(self.current_allocation - value);
+        } else {
+            self.unrecovered_loss =
+                self.unrecovered_loss.saturating_sub(value - self.current_allocation);
+        }
+        self.current_allocation = value;
+    }
+
+    /// 保险金库补偿后减记未恢复的亏损
+    pub fn cover_loss(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, crate::error::CustomError::ZeroAmount);
+        require!(amount <= self.unrecovered_loss, crate::error::CustomError::CoverExceedsShortfall);
+        self.unrecovered_loss -= amount;
+        Ok(())
+    }
+
+    /// 已集成协议的程序 ID；Lido / Marinade / Jito 暂无内置 ID，由管理员指定
+    pub fn known_program_id(protocol_id: u8) -> Option<Pubkey> {
+        match protocol_id {
+            crate::constants::PROTOCOL_KAMINO => Some(crate::constants::kamino::PROGRAM_ID),
+            crate::constants::PROTOCOL_JUPITER_LEND => {
+                Some(crate::constants::jupiter::LEND_PROGRAM_ID)
+            }
+            _ => None,
+        }
     }
+
+    /// 校验协议 ID、程序 ID、目标比例、参数长度和持仓账户绑定
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (crate::constants::PROTOCOL_KAMINO..=crate::constants::PROTOCOL_JUPITER_LEND)
+                .contains(&self.protocol_id),
+            crate::error::CustomError::UnsupportedProtocol
+        );
+        match Self::known_program_id(self.protocol_id) {
+            Some(program_id) => {
+                require_keys_eq!(
+                    self.program_id,
+                    program_id,
+                    crate::error::CustomError::ProgramMismatch
+                );
+                // 已集成协议的持仓按汇率计价，必须绑定收益凭证账户和汇率来源
+                require!(
+                    self.receipt_account != Pubkey::default()
+                        && self.rate_source != Pubkey::default()
+                        && self.receipt_account != self.rate_source,
+                    crate::error::CustomError::InvalidProtocolConfig
+                );
+            }
+            None => {
+                require!(
+                    self.program_id != Pubkey::default(),
+                    crate::error::CustomError::ProgramMismatch
+                );
+                // 未集成协议无法计价，不允许绑定持仓账户
+                require!(
+                    self.receipt_account == Pubkey::default()
+                        && self.rate_source == Pubkey::default(),
+                    crate::error::CustomError::InvalidProtocolConfig
+                );
+            }
+        }
+        require!(
+            self.target_allocation_bps <= 10_000,
+            crate::error::CustomError::AllocationLimitExceeded
+        );
+        require!(
+            self.params.len() <= Self::MAX_PARAMS_LEN,
+            crate::error::CustomError::InvalidProtocolConfig
+        );
+        Ok(())
+    }
+}
+
+/// 单个协议的目标分配（用于批量设置）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ProtocolTargetAllocation {
+    pub protocol_id: u8,
+    pub target_allocation_bps: u16,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -344,6 +860,22 @@ impl FeeConfig {
     pub fn space() -> usize {
         2 + 2 + 2 + 2 + 32 // 4个u16 + 1个Pubkey
     }
+
+    /// 校验各项费率不超过协议上限，且费用接收地址有效
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.deposit_fee_bps <= crate::constants::MAX_DEPOSIT_FEE_BPS
+                && self.withdraw_fee_bps <= crate::constants::MAX_WITHDRAW_FEE_BPS
+                && self.management_fee_bps <= crate::constants::MAX_MANAGEMENT_FEE_BPS
+                && self.performance_fee_bps <= crate::constants::MAX_PERFORMANCE_FEE_BPS,
+            crate::error::CustomError::InvalidFeeConfig
+        );
+        require!(
+            self.fee_recipient != Pubkey::default(),
+            crate::error::CustomError::InvalidFeeConfig
+        );
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
@@ -354,5 +886,405 @@ pub enum VaultStatus {
     Emergency,
 }
 
+impl VaultStatus {
+    /// 状态机：
+    /// - Active → Paused / Emergency
+    /// - Paused → Active / Emergency / Closed
+    /// - Emergency → Active / Paused / Closed
+    /// - Closed 为终态
+    pub fn can_transition_to(&self, next: &VaultStatus) -> bool {
+        matches!(
+            (self, next),
+            (VaultStatus::Active, VaultStatus::Paused)
+                | (VaultStatus::Active, VaultStatus::Emergency)
+                | (VaultStatus::Paused, VaultStatus::Active)
+                | (VaultStatus::Paused, VaultStatus::Emergency)
+                | (VaultStatus::Paused, VaultStatus::Closed)
+                | (VaultStatus::Emergency, VaultStatus::Active)
+                | (VaultStatus::Emergency, VaultStatus::Paused)
+                | (VaultStatus::Emergency, VaultStatus::Closed)
+        )
+    }
+}
+
 // Note: GlobalState, FeeTier, InsuranceFeeTier, ChainFeeEntry are now defined in state.rs
 // to avoid duplicate definitions. Import them from parent module.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_vault(total_shares: u64, kamino_allocation: u64) -> VaultState {
+        VaultState {
+            vault_id: [0u8; 32],
+            admin: Pubkey::default(),
+            pending_admin: None,
+            base_token_mint: Pubkey::default(),
+            shares_mint: Pubkey::default(),
+            treasury: Pubkey::default(),
+            total_deposits: 0,
+            total_shares,
+            created_at: 0,
+            last_updated: 0,
+            status: VaultStatus::Active,
+            supported_protocols: vec![ProtocolConfig {
+                protocol_id: crate::constants::PROTOCOL_KAMINO,
+                program_id: Pubkey::default(),
+                enabled: true,
+                allocation_weight_bps: 10_000,
+                current_allocation: kamino_allocation,
+                target_allocation_bps: 10_000,
+                params: Vec::new(),
+                receipt_account: Pubkey::default(),
+                rate_source: Pubkey::default(),
+                apy_bps: 0,
+                last_price: 0,
+                last_price_time: 0,
+                unrecovered_loss: 0,
+            }],
+            user_deposits: Vec::new(),
+            rebalance_history: Vec::new(),
+            fee_config: FeeConfig {
+                deposit_fee_bps: 0,
+                withdraw_fee_bps: 0,
+                management_fee_bps: 0,
+                performance_fee_bps: 0,
+                fee_recipient: Pubkey::default(),
+            },
+            platform_fee_bps: 0,
+            max_slippage_bps: 0,
+            bump: 0,
+            unclaimed_deposit_fee: 0,
+            unclaimed_withdraw_fee: 0,
+            unclaimed_management_fee: 0,
+            unclaimed_performance_fee: 0,
+            total_deposit_fee_collected: 0,
+            total_withdraw_fee_collected: 0,
+            total_management_fee_collected: 0,
+            total_performance_fee_collected: 0,
+            total_rewards_claimed: 0,
+            total_platform_fee_collected: 0,
+            last_fee_accrual_time: 0,
+            high_water_mark: 0,
+            max_total_deposits: 0,
+            max_user_deposit: 0,
+            min_deposit_amount: 0,
+            max_deposit_amount: 0,
+        }
+    }
+
+    #[test]
+    fn test_total_assets_excludes_unclaimed_fees() {
+        let mut vault = test_vault(1_000_000, 900_000);
+        vault.unclaimed_withdraw_fee = 50_000;
+
+        // Treasury 150k + Kamino 900k - 未认领费用 50k
+        assert_eq!(vault.total_assets(150_000).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_share_conversion_follows_nav() {
+        // 首次存款 1:1
+        let empty = test_vault(0, 0);
+        assert_eq!(empty.convert_to_shares(1_000_000, 0).unwrap(), 1_000_000);
+
+        // 金库增值 50% 后，存款获得更少份额，赎回获得更多代币
+        let vault = test_vault(10_000_000, 15_000_000);
+        let total_assets = vault.total_assets(0).unwrap();
+        assert_eq!(vault.convert_to_shares(1_500_000, total_assets).unwrap(), 1_000_000);
+        assert_eq!(vault.convert_to_assets(1_000_000, total_assets).unwrap(), 1_500_000);
+    }
+
+    #[test]
+    fn test_min_amount_after_slippage() {
+        let mut vault = test_vault(0, 0);
+        vault.max_slippage_bps = 100;
+        assert_eq!(vault.min_amount_after_slippage(1_000_000).unwrap(), 990_000);
+
+        vault.max_slippage_bps = 0;
+        assert_eq!(vault.min_amount_after_slippage(1_000_000).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_check_slippage_against_user_expectation() {
+        let mut vault = test_vault(0, 0);
+        vault.max_slippage_bps = 100;
+
+        // 用户下限过松时以相对预期值的 max_slippage_bps 为准
+        assert!(vault.check_slippage(990_000, 1_000_000, 0).is_ok());
+        assert!(vault.check_slippage(989_999, 1_000_000, 0).is_err());
+
+        // 用户下限更严格时以用户为准
+        assert!(vault.check_slippage(994_999, 1_000_000, 995_000).is_err());
+        assert!(vault.check_slippage(995_000, 1_000_000, 995_000).is_ok());
+
+        // 未提交预期值时拒绝，避免绕过 max_slippage_bps
+        assert!(vault.check_slippage(995_000, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_emergency_redeem_amount_ignores_protocol_positions() {
+        // 100 万份额，Kamino 中 90 万，Treasury 中 10 万（含 1 万未认领费用）
+        let mut vault = test_vault(1_000_000, 900_000);
+        vault.unclaimed_withdraw_fee = 10_000;
+
+        // 10% 的份额只能分到 Treasury 可用资金的 10%
+        assert_eq!(vault.emergency_redeem_amount(100_000, 100_000).unwrap(), 9_000);
+        assert_eq!(vault.emergency_redeem_amount(1_000_000, 100_000).unwrap(), 90_000);
+
+        let empty = test_vault(0, 0);
+        assert_eq!(empty.emergency_redeem_amount(100, 100_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_protocol_registry() {
+        use crate::constants::{jupiter, kamino, PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO};
+
+        let mut vault = test_vault(0, 0);
+        vault.supported_protocols.clear();
+
+        let bound = |protocol_id, program_id, target_bps, params| {
+            ProtocolConfig::new(
+                protocol_id,
+                program_id,
+                target_bps,
+                params,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            )
+        };
+
+        // 程序 ID 必须与内置的一致
+        let wrong_program = bound(PROTOCOL_KAMINO, Pubkey::new_unique(), 0, vec![]);
+        assert!(vault.add_protocol(wrong_program).is_err());
+
+        // 已集成协议必须绑定收益凭证账户和汇率来源
+        let unbound = ProtocolConfig::new(
+            PROTOCOL_KAMINO,
+            kamino::PROGRAM_ID,
+            6_000,
+            vec![],
+            Pubkey::default(),
+            Pubkey::default(),
+        );
+        assert!(vault.add_protocol(unbound).is_err());
+
+        let kamino = bound(PROTOCOL_KAMINO, kamino::PROGRAM_ID, 6_000, vec![]);
+        vault.add_protocol(kamino.clone()).unwrap();
+        assert!(vault.add_protocol(kamino).is_err());
+
+        // 参数过长、目标之和超过 100% 都会被拒绝
+        let long_params = bound(PROTOCOL_JUPITER_LEND, jupiter::LEND_PROGRAM_ID, 0, vec![0; 33]);
+        assert!(vault.add_protocol(long_params).is_err());
+        let too_much = bound(PROTOCOL_JUPITER_LEND, jupiter::LEND_PROGRAM_ID, 5_000, vec![]);
+        assert!(vault.add_protocol(too_much).is_err());
+
+        let jupiter = bound(PROTOCOL_JUPITER_LEND, jupiter::LEND_PROGRAM_ID, 4_000, vec![]);
+        vault.add_protocol(jupiter).unwrap();
+        assert_eq!(vault.enabled_target_allocation_bps(), 10_000);
+
+        // 目标之和必须为 10000
+        let allocations = [
+            ProtocolTargetAllocation {
+                protocol_id: PROTOCOL_KAMINO,
+                target_allocation_bps: 5_000,
+            },
+            ProtocolTargetAllocation {
+                protocol_id: PROTOCOL_JUPITER_LEND,
+                target_allocation_bps: 4_000,
+            },
+        ];
+        assert!(vault.set_target_allocations(&allocations).is_err());
+        let allocations = [
+            ProtocolTargetAllocation {
+                protocol_id: PROTOCOL_KAMINO,
+                target_allocation_bps: 3_000,
+            },
+            ProtocolTargetAllocation {
+                protocol_id: PROTOCOL_JUPITER_LEND,
+                target_allocation_bps: 7_000,
+            },
+        ];
+        vault.set_target_allocations(&allocations).unwrap();
+        assert_eq!(vault.find_protocol(PROTOCOL_KAMINO).unwrap().target_allocation_bps, 3_000);
+
+        // 仍有持仓的协议不能移除
+        vault.find_protocol_mut(PROTOCOL_KAMINO).unwrap().current_allocation = 1;
+        assert!(vault.remove_protocol(PROTOCOL_KAMINO).is_err());
+        vault.find_protocol_mut(PROTOCOL_KAMINO).unwrap().current_allocation = 0;
+        vault.remove_protocol(PROTOCOL_KAMINO).unwrap();
+        assert!(vault.find_protocol(PROTOCOL_KAMINO).is_none());
+    }
+
+    #[test]
+    fn test_receipt_account_binding() {
+        use crate::constants::{PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO};
+
+        let mut vault = test_vault(0, 0);
+        let receipt = Pubkey::new_unique();
+        vault.supported_protocols[0].receipt_account = receipt;
+
+        assert!(vault.require_receipt_account(PROTOCOL_KAMINO, &receipt).is_ok());
+        assert!(vault.is_bound_receipt_account(PROTOCOL_KAMINO, &receipt));
+
+        // 同一 Vault 持有的其他账户不能代替绑定账户
+        let other = Pubkey::new_unique();
+        assert!(vault.require_receipt_account(PROTOCOL_KAMINO, &other).is_err());
+        assert!(!vault.is_bound_receipt_account(PROTOCOL_KAMINO, &other));
+
+        // 未注册的协议不会使用该账户
+        assert!(vault.require_receipt_account(PROTOCOL_JUPITER_LEND, &other).is_err());
+        assert!(vault.is_bound_receipt_account(PROTOCOL_JUPITER_LEND, &other));
+    }
+
+    #[test]
+    fn test_revalue_tracks_unrecovered_loss() {
+        let mut vault = test_vault(1_000, 1_000);
+        let protocol = &mut vault.supported_protocols[0];
+
+        // 下跌 100：亏损已计入 NAV，同时记为未恢复亏损
+        protocol.revalue(900);
+        assert_eq!(protocol.current_allocation, 900);
+        assert_eq!(protocol.unrecovered_loss, 100);
+
+        // 再次计价不会重复记亏损，回升时先抵减
+        protocol.revalue(900);
+        assert_eq!(protocol.unrecovered_loss, 100);
+        protocol.revalue(930);
+        assert_eq!(protocol.unrecovered_loss, 70);
+
+        // 补偿不能超过未恢复的亏损
+        assert!(protocol.cover_loss(71).is_err());
+        protocol.cover_loss(70).unwrap();
+        assert_eq!(protocol.unrecovered_loss, 0);
+        protocol.revalue(1_000);
+        assert_eq!(protocol.unrecovered_loss, 0);
+    }
+
+    #[test]
+    fn test_fee_config_caps() {
+        let mut config = FeeConfig {
+            deposit_fee_bps: crate::constants::DEFAULT_DEPOSIT_FEE_BPS,
+            withdraw_fee_bps: crate::constants::DEFAULT_WITHDRAW_FEE_BPS,
+            management_fee_bps: crate::constants::DEFAULT_MANAGEMENT_FEE_BPS,
+            performance_fee_bps: crate::constants::DEFAULT_PERFORMANCE_FEE_BPS,
+            fee_recipient: Pubkey::new_unique(),
+        };
+        assert!(config.validate().is_ok());
+
+        config.performance_fee_bps = crate::constants::MAX_PERFORMANCE_FEE_BPS + 1;
+        assert!(config.validate().is_err());
+
+        config.performance_fee_bps = crate::constants::MAX_PERFORMANCE_FEE_BPS;
+        config.fee_recipient = Pubkey::default();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use VaultStatus::*;
+
+        assert!(Active.can_transition_to(&Paused));
+        assert!(Active.can_transition_to(&Emergency));
+        assert!(!Active.can_transition_to(&Closed));
+        assert!(!Active.can_transition_to(&Active));
+
+        assert!(Paused.can_transition_to(&Active));
+        assert!(Paused.can_transition_to(&Closed));
+        assert!(Emergency.can_transition_to(&Active));
+        assert!(Emergency.can_transition_to(&Closed));
+
+        for next in [Active, Paused, Emergency, Closed] {
+            assert!(!Closed.can_transition_to(&next));
+        }
+    }
+
+    #[test]
+    fn test_status_gates_user_operations() {
+        let mut vault = test_vault(1_000, 0);
+        assert!(vault.require_deposits_allowed().is_ok());
+        assert!(vault.require_withdrawals_allowed().is_ok());
+
+        vault.status = VaultStatus::Paused;
+        assert!(vault.require_deposits_allowed().is_err());
+        assert!(vault.require_withdrawals_allowed().is_ok());
+
+        vault.status = VaultStatus::Emergency;
+        assert!(vault.require_deposits_allowed().is_err());
+        assert!(vault.require_withdrawals_allowed().is_err());
+        assert!(vault.require_not_closed().is_ok());
+
+        vault.status = VaultStatus::Closed;
+        assert!(vault.require_deposits_allowed().is_err());
+        assert!(vault.require_withdrawals_allowed().is_err());
+        assert!(vault.require_not_closed().is_err());
+    }
+
+    #[test]
+    fn test_deposit_limits() {
+        // 100 万份额对应 100 万资产
+        let mut vault = test_vault(1_000_000, 1_000_000);
+        assert!(vault.check_deposit_limits(1, 1, 0, 1_000_000).is_ok());
+
+        vault.min_deposit_amount = 100;
+        vault.max_deposit_amount = 500_000;
+        assert!(vault.check_deposit_limits(99, 99, 0, 1_000_000).is_err());
+        assert!(vault.check_deposit_limits(500_001, 500_001, 0, 1_000_000).is_err());
+        assert!(vault.check_deposit_limits(500_000, 500_000, 0, 1_000_000).is_ok());
+
+        // TVL 上限按扣费后的金额计算
+        vault.max_total_deposits = 1_200_000;
+        assert!(vault.check_deposit_limits(200_000, 200_000, 0, 1_000_000).is_ok());
+        assert!(vault.check_deposit_limits(200_001, 200_001, 0, 1_000_000).is_err());
+        assert!(vault.check_deposit_limits(200_001, 200_000, 0, 1_000_000).is_ok());
+
+        // 用户持仓上限包含已有份额的当前价值
+        vault.max_user_deposit = 150_000;
+        assert!(vault.check_deposit_limits(50_000, 50_000, 100_000, 1_000_000).is_ok());
+        assert!(vault.check_deposit_limits(50_001, 50_001, 100_000, 1_000_000).is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_request_settles_at_processing_price() {
+        use crate::state::{WithdrawalRequest, WithdrawalRequestStatus};
+
+        let mut vault = test_vault(1_000_000, 1_000_000);
+        vault.fee_config.withdraw_fee_bps = 50;
+
+        let mut request = WithdrawalRequest {
+            vault_id: [0u8; 32],
+            user: Pubkey::new_unique(),
+            shares: 100_000,
+            status: WithdrawalRequestStatus::Pending,
+            claimable_amount: 0,
+            withdraw_fee: 0,
+            requested_at: 0,
+            processed_at: 0,
+            bump: 255,
+        };
+
+        // 请求后份额价格上涨 20%，按处理时的价格结算：120k，扣 0.5% 提款费
+        let total_assets = vault.total_assets(200_000).unwrap();
+        assert_eq!(request.settle(&vault, total_assets, 42).unwrap(), (119_400, 600));
+        assert_eq!(request.status, WithdrawalRequestStatus::Processed);
+        assert_eq!(request.processed_at, 42);
+
+        // 已处理的请求不能再次结算
+        assert!(request.settle(&vault, total_assets, 43).is_err());
+    }
+
+    #[test]
+    fn test_space_grows_with_rebalance_history() {
+        let vault = test_vault(0, 0);
+        assert_eq!(
+            vault.space_with_new_rebalance_record(),
+            vault.current_space() + RebalanceRecord::space()
+        );
+    }
+
+    #[test]
+    fn test_share_conversion_rejects_worthless_vault() {
+        let vault = test_vault(1_000_000, 0);
+        assert!(vault.convert_to_shares(1_000_000, 0).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+/// 排队提款请求（PDA: [WITHDRAWAL_REQUEST_SEED, vault_id, user]）
+///
+/// 流动性不足时（Kamino farm 两阶段解除质押、借贷协议利用率过高），用户先将份额锁入托管账户，
+/// keeper 释放流动性后按处理时的份额价格结算，用户随后领取代币。处理前可以取消。
+#[account]
+pub struct WithdrawalRequest {
+    /// 所属 Vault 的 ID
+    pub vault_id: [u8; 32],
+
+    /// 请求用户
+    pub user: Pubkey,
+
+    /// 锁定的份额数量
+    pub shares: u64,
+
+    /// 请求状态
+    pub status: WithdrawalRequestStatus,
+
+    /// 处理时确定的可领取代币数量（已扣除提款费）
+    pub claimable_amount: u64,
+
+    /// 处理时扣除的提款费
+    pub withdraw_fee: u64,
+
+    /// 请求时间
+    pub requested_at: i64,
+
+    /// 处理时间（0 表示尚未处理）
+    pub processed_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WithdrawalRequestStatus {
+    /// 等待 keeper 处理，可以取消
+    Pending,
+    /// 已按固定价格结算，等待用户领取
+    Processed,
+}
+
+impl WithdrawalRequest {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // vault_id
+        32 + // user
+        8 + // shares
+        1 + // status
+        8 + // claimable_amount
+        8 + // withdraw_fee
+        8 + // requested_at
+        8 + // processed_at
+        1 // bump
+    }
+
+    /// 按处理时的 NAV 结算请求：返回（扣费后可领取金额，提款费）
+    pub fn settle(
+        &mut self,
+        vault_state: &super::VaultState,
+        total_assets: u64,
+        now: i64,
+    ) -> Result<(u64, u64)> {
+        require!(
+            self.status == WithdrawalRequestStatus::Pending,
+            crate::error::CustomError::InvalidWithdrawalRequestStatus
+        );
+
+        let gross_amount = vault_state.convert_to_assets(self.shares, total_assets)?;
+        let withdraw_fee = vault_state.withdraw_fee_for(gross_amount)?;
+        let claimable_amount = gross_amount
+            .checked_sub(withdraw_fee)
+            .ok_or(error!(crate::error::CustomError::MathOverflow))?;
+
+        self.status = WithdrawalRequestStatus::Processed;
+        self.claimable_amount = claimable_amount;
+        self.withdraw_fee = withdraw_fee;
+        self.processed_at = now;
+
+        Ok((claimable_amount, withdraw_fee))
+    }
+}
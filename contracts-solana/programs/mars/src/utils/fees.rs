@@ -0,0 +1,389 @@
+use crate::constants::{BASE_PERCENTAGE, SECONDS_PER_YEAR};
+use crate::error::CustomError;
+use crate::events::{ManagementFeeAccrued, PerformanceFeeCrystallized};
+use crate::state::VaultState;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, MintTo, TokenAccount};
+
+/// 费用引擎
+///
+/// 管理费按经过的秒数连续计提，通过向 `fee_config.fee_recipient` 增发份额收取，
+/// 而不是从 Treasury 中划走代币：份额总数增加、NAV 不变，全体持有人按比例被稀释。
+///
+/// 性能费只对份额价格超过高水位（high-water mark）的收益收取，计入 `unclaimed_performance_fee`。
+pub struct FeeEngine;
+
+/// 一次管理费结算的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ManagementFeeAccrual {
+    /// 增发给费用接收方的份额
+    pub fee_shares: u64,
+    /// 增发份额按当前 NAV 折算的代币价值
+    pub fee_amount: u64,
+    /// 本次结算覆盖的秒数
+    pub elapsed_seconds: i64,
+}
+
+/// 一次性能费结算的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerformanceFeeCrystallization {
+    /// 计入 unclaimed_performance_fee 的代币数量
+    pub fee_amount: u64,
+    /// 结算前的份额价格
+    pub share_price: u64,
+    pub previous_high_water_mark: u64,
+    pub new_high_water_mark: u64,
+}
+
+/// 一次完整费用结算（管理费 + 性能费）的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeSettlement {
+    pub management: ManagementFeeAccrual,
+    pub performance: PerformanceFeeCrystallization,
+}
+
+impl FeeEngine {
+    /// 份额价格精度（1 份 = 1 个基础代币单位时价格为 1e9）
+    pub const SHARE_PRICE_PRECISION: u64 = 1_000_000_000;
+
+    /// 计算应增发的管理费份额
+    ///
+    /// 费率 f = bps * elapsed / (10000 * 一年秒数)，增发 s 份使接收方持有增发后总份额的 f：
+    /// s = total_shares * f / (1 - f)
+    pub fn management_fee_shares(
+        total_shares: u64,
+        management_fee_bps: u16,
+        elapsed_seconds: i64,
+    ) -> Option<u64> {
+        if total_shares == 0 || management_fee_bps == 0 || elapsed_seconds <= 0 {
+            return Some(0);
+        }
+
+        let fee_numerator = (management_fee_bps as u128).checked_mul(elapsed_seconds as u128)?;
+        let denominator = (BASE_PERCENTAGE as u128).checked_mul(SECONDS_PER_YEAR as u128)?;
+        let remaining = denominator.checked_sub(fee_numerator).filter(|v| *v > 0)?;
+
+        (total_shares as u128)
+            .checked_mul(fee_numerator)?
+            .checked_div(remaining)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// 结算管理费并更新金库状态（不执行铸造）
+    ///
+    /// 空金库不计费，直接推进时间戳；计算结果不足 1 份时保留时间戳，
+    /// 让费用继续累积到下次结算，避免频繁操作把管理费舍入为 0。
+    pub fn accrue_management_fee(
+        vault_state: &mut VaultState,
+        total_assets: u64,
+        now: i64,
+    ) -> Result<ManagementFeeAccrual> {
+        let last_accrual = vault_state.last_fee_accrual_time;
+
+        // 升级前创建的金库从首次结算开始计费
+        if last_accrual == 0 || vault_state.total_shares == 0 {
+            vault_state.last_fee_accrual_time = now;
+            return Ok(ManagementFeeAccrual::default());
+        }
+
+        let elapsed_seconds = now.saturating_sub(last_accrual);
+        let fee_shares = Self::management_fee_shares(
+            vault_state.total_shares,
+            vault_state.fee_config.management_fee_bps,
+            elapsed_seconds,
+        )
+        .ok_or(CustomError::MathOverflow)?;
+
+        if fee_shares == 0 {
+            return Ok(ManagementFeeAccrual::default());
+        }
+
+        let new_total_shares =
+            vault_state.total_shares.checked_add(fee_shares).ok_or(CustomError::MathOverflow)?;
+        let fee_amount = (total_assets as u128)
+            .checked_mul(fee_shares as u128)
+            .and_then(|v| v.checked_div(new_total_shares as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(CustomError::MathOverflow)?;
+
+        vault_state.total_shares = new_total_shares;
+        vault_state.total_management_fee_collected = vault_state
+            .total_management_fee_collected
+            .checked_add(fee_amount)
+            .ok_or(CustomError::MathOverflow)?;
+        vault_state.last_fee_accrual_time = now;
+
+        Ok(ManagementFeeAccrual {
+            fee_shares,
+            fee_amount,
+            elapsed_seconds,
+        })
+    }
+
+    /// 计算份额价格；没有份额时返回 None
+    pub fn share_price(total_assets: u64, total_shares: u64) -> Option<u64> {
+        if total_shares == 0 {
+            return None;
+        }
+
+        (total_assets as u128)
+            .checked_mul(Self::SHARE_PRICE_PRECISION as u128)?
+            .checked_div(total_shares as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// 计算超过高水位部分收益对应的性能费
+    ///
+    /// 收益 = (当前价格 - 高水位) * 总份额，性能费 = 收益 * bps / 10000
+    pub fn performance_fee_for(
+        total_assets: u64,
+        total_shares: u64,
+        high_water_mark: u64,
+        performance_fee_bps: u16,
+    ) -> Option<u64> {
+        let share_price = match Self::share_price(total_assets, total_shares) {
+            Some(price) if price > high_water_mark => price,
+            _ => return Some(0),
+        };
+
+        ((share_price - high_water_mark) as u128)
+            .checked_mul(total_shares as u128)?
+            .checked_div(Self::SHARE_PRICE_PRECISION as u128)?
+            .checked_mul(performance_fee_bps as u128)?
+            .checked_div(BASE_PERCENTAGE as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// 结算性能费并推进高水位
+    ///
+    /// 价格未超过高水位时不收费、高水位不变；收费后高水位更新为扣费后的份额价格。
+    /// 费用舍入为 0 时保留高水位，让收益继续累积。
+    pub fn crystallize_performance_fee(
+        vault_state: &mut VaultState,
+        total_assets: u64,
+    ) -> Result<PerformanceFeeCrystallization> {
+        let previous_high_water_mark = vault_state.high_water_mark;
+        let share_price = match Self::share_price(total_assets, vault_state.total_shares) {
+            Some(price) => price,
+            None => return Ok(PerformanceFeeCrystallization::default()),
+        };
+
+        let mut result = PerformanceFeeCrystallization {
+            fee_amount: 0,
+            share_price,
+            previous_high_water_mark,
+            new_high_water_mark: previous_high_water_mark,
+        };
+
+        // 升级前创建的金库以当前价格作为初始高水位
+        if previous_high_water_mark == 0 {
+            vault_state.high_water_mark = share_price;
+            result.new_high_water_mark = share_price;
+            return Ok(result);
+        }
+
+        if share_price <= previous_high_water_mark {
+            return Ok(result);
+        }
+
+        let performance_fee_bps = vault_state.fee_config.performance_fee_bps;
+        let fee_amount = Self::performance_fee_for(
+            total_assets,
+            vault_state.total_shares,
+            previous_high_water_mark,
+            performance_fee_bps,
+        )
+        .ok_or(CustomError::MathOverflow)?;
+
+        if fee_amount == 0 && performance_fee_bps > 0 {
+            return Ok(result);
+        }
+
+        let assets_after_fee =
+            total_assets.checked_sub(fee_amount).ok_or(CustomError::MathOverflow)?;
+        let new_high_water_mark = Self::share_price(assets_after_fee, vault_state.total_shares)
+            .ok_or(CustomError::MathOverflow)?
+            .max(previous_high_water_mark);
+
+        vault_state.unclaimed_performance_fee = vault_state
+            .unclaimed_performance_fee
+            .checked_add(fee_amount)
+            .ok_or(CustomError::MathOverflow)?;
+        vault_state.total_performance_fee_collected = vault_state
+            .total_performance_fee_collected
+            .checked_add(fee_amount)
+            .ok_or(CustomError::MathOverflow)?;
+        vault_state.high_water_mark = new_high_water_mark;
+
+        result.fee_amount = fee_amount;
+        result.new_high_water_mark = new_high_water_mark;
+        Ok(result)
+    }
+
+    /// 结算管理费和性能费（vault_state PDA 签名铸造管理费份额）
+    ///
+    /// 需在计算本次操作的份额/资产换算之前调用。先按时间增发管理费份额，
+    /// 再以稀释后的份额价格与高水位比较结算性能费。
+    pub fn settle_fees<'info>(
+        vault_state: &mut Account<'info, VaultState>,
+        treasury_balance: u64,
+        shares_mint: AccountInfo<'info>,
+        fee_recipient_shares_account: &Account<'info, TokenAccount>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<FeeSettlement> {
+        let now = Clock::get()?.unix_timestamp;
+        let management = Self::settle_management_fee(
+            vault_state,
+            treasury_balance,
+            shares_mint,
+            fee_recipient_shares_account,
+            token_program,
+            now,
+        )?;
+
+        let total_assets = vault_state.total_assets(treasury_balance)?;
+        let performance = Self::crystallize_performance_fee(vault_state, total_assets)?;
+
+        if performance.fee_amount > 0 {
+            emit!(PerformanceFeeCrystallized {
+                vault_id: vault_state.vault_id,
+                fee_amount: performance.fee_amount,
+                share_price: performance.share_price,
+                previous_high_water_mark: performance.previous_high_water_mark,
+                new_high_water_mark: performance.new_high_water_mark,
+                timestamp: now,
+            });
+
+            msg!(
+                "🧾 Performance fee crystallized: fee={}, price={}, hwm={} -> {}",
+                performance.fee_amount,
+                performance.share_price,
+                performance.previous_high_water_mark,
+                performance.new_high_water_mark
+            );
+        }
+
+        Ok(FeeSettlement {
+            management,
+            performance,
+        })
+    }
+
+    /// 结算管理费，并将费用份额铸造到 fee_recipient 的份额账户
+    fn settle_management_fee<'info>(
+        vault_state: &mut Account<'info, VaultState>,
+        treasury_balance: u64,
+        shares_mint: AccountInfo<'info>,
+        fee_recipient_shares_account: &Account<'info, TokenAccount>,
+        token_program: AccountInfo<'info>,
+        now: i64,
+    ) -> Result<ManagementFeeAccrual> {
+        let total_assets = vault_state.total_assets(treasury_balance)?;
+        let accrual = Self::accrue_management_fee(vault_state, total_assets, now)?;
+
+        if accrual.fee_shares == 0 {
+            return Ok(accrual);
+        }
+
+        let vault_id = vault_state.vault_id;
+        let bump_seed = [vault_state.bump];
+        let seeds: &[&[u8]] = &[VaultState::SEED_PREFIX, vault_id.as_ref(), &bump_seed];
+        let signer_seeds = &[seeds];
+
+        let mint_ctx = CpiContext::new_with_signer(
+            token_program,
+            MintTo {
+                mint: shares_mint,
+                to: fee_recipient_shares_account.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(mint_ctx, accrual.fee_shares)?;
+
+        emit!(ManagementFeeAccrued {
+            vault_id,
+            fee_recipient: vault_state.fee_config.fee_recipient,
+            fee_shares: accrual.fee_shares,
+            fee_amount: accrual.fee_amount,
+            elapsed_seconds: accrual.elapsed_seconds,
+            timestamp: now,
+        });
+
+        msg!(
+            "🧾 Management fee accrued: shares={}, value={}, elapsed={}s",
+            accrual.fee_shares,
+            accrual.fee_amount,
+            accrual.elapsed_seconds
+        );
+
+        Ok(accrual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_management_fee_shares() {
+        let year = SECONDS_PER_YEAR as i64;
+
+        // 2% 年费，一整年：s = 1_000_000 * 0.02 / 0.98
+        assert_eq!(FeeEngine::management_fee_shares(1_000_000, 200, year), Some(20_408));
+
+        // 半年约为一年的一半
+        assert_eq!(FeeEngine::management_fee_shares(1_000_000, 200, year / 2), Some(10_101));
+
+        // 无份额、零费率、时间未前进均不计费
+        assert_eq!(FeeEngine::management_fee_shares(0, 200, year), Some(0));
+        assert_eq!(FeeEngine::management_fee_shares(1_000_000, 0, year), Some(0));
+        assert_eq!(FeeEngine::management_fee_shares(1_000_000, 200, 0), Some(0));
+
+        // 费率累积到 100% 时无法通过增发收取
+        assert_eq!(FeeEngine::management_fee_shares(1_000_000, 10_000, year), None);
+    }
+
+    #[test]
+    fn test_performance_fee_only_above_high_water_mark() {
+        let precision = FeeEngine::SHARE_PRICE_PRECISION;
+
+        // 价格 1.10，高水位 1.00，1000 份：收益 100，10% 性能费 = 10
+        assert_eq!(FeeEngine::performance_fee_for(1_100, 1_000, precision, 1_000), Some(10));
+
+        // 高水位 1.05 时只对 1.05 以上的收益收费
+        assert_eq!(
+            FeeEngine::performance_fee_for(1_100, 1_000, precision * 105 / 100, 1_000),
+            Some(5)
+        );
+
+        // 价格未超过高水位（包括亏损后回升但未创新高）不收费
+        assert_eq!(FeeEngine::performance_fee_for(1_000, 1_000, precision, 1_000), Some(0));
+        assert_eq!(
+            FeeEngine::performance_fee_for(1_100, 1_000, precision * 12 / 10, 1_000),
+            Some(0)
+        );
+
+        // 没有份额时不收费
+        assert_eq!(FeeEngine::performance_fee_for(1_100, 0, precision, 1_000), Some(0));
+    }
+
+    #[test]
+    fn test_share_price() {
+        let precision = FeeEngine::SHARE_PRICE_PRECISION;
+        assert_eq!(FeeEngine::share_price(1_000, 1_000), Some(precision));
+        assert_eq!(FeeEngine::share_price(1_500, 1_000), Some(precision * 3 / 2));
+        assert_eq!(FeeEngine::share_price(1_000, 0), None);
+    }
+
+    #[test]
+    fn test_fee_shares_dilute_to_fee_rate() {
+        let total_shares = 1_000_000_000u64;
+        let fee_shares =
+            FeeEngine::management_fee_shares(total_shares, 200, SECONDS_PER_YEAR as i64).unwrap();
+
+        // 增发后费用接收方持有约 2% 的份额（百万分比）
+        let recipient_ppm = fee_shares as u128 * 1_000_000 / (total_shares + fee_shares) as u128;
+        assert!((19_999..=20_000).contains(&recipient_ppm));
+    }
+}
@@ -1,6 +1,10 @@
 /// 工具函数模块 - 高级功能
+pub mod fees;
 pub mod oracle;
 pub mod rebalance;
+pub mod valuation;
 
+pub use fees::*;
 pub use oracle::*;
 pub use rebalance::*;
+pub use valuation::*;
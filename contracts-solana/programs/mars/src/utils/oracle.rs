@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
-// use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
-// use pythnet_sdk::messages::Message;
+use pyth_solana_receiver_sdk::price_update::{
+    get_feed_id_from_hex, FeedId, PriceUpdateV2, VerificationLevel,
+};
 
 /// Pyth 价格预言机工具模块
 ///
 /// 功能：
-/// - 从 Pyth Pull Oracle 获取实时价格
+/// - 解析 Pyth Pull Oracle 价格更新（PriceUpdateV2）
 /// - 价格验证和过期检查
 /// - 支持多种代币价格查询
 /// - 置信区间检查
@@ -35,9 +36,9 @@ impl PriceData {
         let price_i128 = self.price as i128;
         let exponent = self.exponent;
 
-        // 目标精度为 10^6（6位小数）
+        // 目标精度为 10^6（6位小数）：实际价格 * 10^6 = price * 10^(6 + exponent)
         let target_exponent = 6;
-        let exponent_diff = target_exponent - exponent;
+        let exponent_diff = target_exponent + exponent;
 
         if exponent_diff >= 0 {
             // 需要放大
@@ -80,23 +81,47 @@ impl PriceData {
 pub struct PythOracle;
 
 impl PythOracle {
-    /// 从 Pyth PriceUpdateV2 账户获取价格
-    ///
-    /// 注意：这是一个占位实现。实际使用时需要集成 Pyth SDK。
-    ///
-    /// 实际实现示例：
-    /// ```ignore
-    /// use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
-    /// let price_update = PriceUpdateV2::try_from_slice(&price_update_account.data.borrow())?;
-    /// ```
-    pub fn get_price_from_account(
-        _price_update_account: &AccountInfo,
-        _feed_id: &str,
-    ) -> Result<PriceData> {
-        // TODO: 实际项目中应该使用 Pyth SDK 解析价格数据
-        // 当前返回占位错误，提示需要完整实现
-        msg!("Warning: PythOracle::get_price_from_account is a placeholder. Implement with pyth-solana-receiver-sdk.");
-        Err(error!(crate::error::CustomError::PriceOracleError))
+    /// PriceUpdateV2 账户的 Anchor discriminator: sha256("account:PriceUpdateV2")[..8]
+    pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    /// 解析 PriceUpdateV2 账户数据（包含 8 字节 discriminator）
+    pub fn decode_price_update(data: &[u8]) -> Result<PriceUpdateV2> {
+        if data.len() < 8 || data[..8] != Self::PRICE_UPDATE_V2_DISCRIMINATOR {
+            msg!("❌ Invalid PriceUpdateV2 discriminator");
+            return Err(error!(crate::error::CustomError::PriceOracleError));
+        }
+
+        PriceUpdateV2::deserialize(&mut &data[8..])
+            .map_err(|_| error!(crate::error::CustomError::PriceOracleError))
+    }
+
+    /// 从已解析的 PriceUpdateV2 中提取指定 feed 的价格
+    pub fn price_from_update(price_update: &PriceUpdateV2, feed_id: &str) -> Result<PriceData> {
+        let expected_feed_id = get_feed_id_from_hex(feed_id)
+            .map_err(|_| error!(crate::error::CustomError::PriceOracleError))?;
+
+        if !feed_ids::is_supported(&expected_feed_id) {
+            msg!("❌ Unsupported price feed: {}", feed_id);
+            return Err(error!(crate::error::CustomError::PriceOracleError));
+        }
+
+        let message = &price_update.price_message;
+        if message.feed_id != expected_feed_id {
+            msg!("❌ Price feed mismatch: expected {}", feed_id);
+            return Err(error!(crate::error::CustomError::PriceOracleError));
+        }
+
+        if !price_update.verification_level.gte(VerificationLevel::Full) {
+            msg!("❌ Price update is only partially verified");
+            return Err(error!(crate::error::CustomError::PriceOracleError));
+        }
+
+        Ok(PriceData {
+            price: message.price,
+            confidence: message.conf,
+            exponent: message.exponent,
+            publish_time: message.publish_time,
+        })
     }
 
     /// 验证价格数据的有效性
@@ -190,6 +215,14 @@ pub mod feed_ids {
 
     /// ETH/USD
     pub const ETH_USD: &str = "0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace";
+
+    /// 所有支持的 Price Feed
+    pub const ALL: [&str; 6] = [USDC_USD, USDT_USD, SOL_USD, PYUSD_USD, BTC_USD, ETH_USD];
+
+    /// 检查 feed id 是否在支持列表中
+    pub fn is_supported(feed_id: &super::FeedId) -> bool {
+        ALL.iter().any(|hex| super::get_feed_id_from_hex(hex).is_ok_and(|id| id == *feed_id))
+    }
 }
 
 #[cfg(test)]
@@ -246,4 +279,69 @@ mod tests {
         let rate = PythOracle::calculate_exchange_rate(&sol_price, &usdc_price).unwrap();
         assert_eq!(rate, 100_000_000); // 100.0 (6位小数)
     }
+
+    fn price_update_data(feed_id: &str, verification_level: VerificationLevel) -> Vec<u8> {
+        use pythnet_sdk::messages::PriceFeedMessage;
+
+        let price_update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level,
+            price_message: PriceFeedMessage {
+                feed_id: get_feed_id_from_hex(feed_id).unwrap(),
+                price: 99_990_000,
+                conf: 10_000,
+                exponent: -8,
+                publish_time: 1_700_000_000,
+                prev_publish_time: 1_699_999_999,
+                ema_price: 99_990_000,
+                ema_conf: 10_000,
+            },
+            posted_slot: 1,
+        };
+
+        let mut data = PythOracle::PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        price_update.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_decode_price_update() {
+        let data = price_update_data(feed_ids::USDC_USD, VerificationLevel::Full);
+        let price_update = PythOracle::decode_price_update(&data).unwrap();
+        let price = PythOracle::price_from_update(&price_update, feed_ids::USDC_USD).unwrap();
+
+        assert_eq!(price.price, 99_990_000);
+        assert_eq!(price.exponent, -8);
+        assert_eq!(price.publish_time, 1_700_000_000);
+        assert_eq!(price.to_ui_price(), Some(999_900)); // $0.9999
+    }
+
+    #[test]
+    fn test_price_update_rejects_invalid_data() {
+        // 错误的 discriminator
+        let mut data = price_update_data(feed_ids::USDC_USD, VerificationLevel::Full);
+        data[0] ^= 0xff;
+        assert!(PythOracle::decode_price_update(&data).is_err());
+
+        // feed id 不匹配
+        let data = price_update_data(feed_ids::USDC_USD, VerificationLevel::Full);
+        let price_update = PythOracle::decode_price_update(&data).unwrap();
+        assert!(PythOracle::price_from_update(&price_update, feed_ids::SOL_USD).is_err());
+
+        // 不在支持列表中的 feed
+        let unknown = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let data = price_update_data(unknown, VerificationLevel::Full);
+        let price_update = PythOracle::decode_price_update(&data).unwrap();
+        assert!(PythOracle::price_from_update(&price_update, unknown).is_err());
+
+        // 仅部分验证
+        let data = price_update_data(
+            feed_ids::USDC_USD,
+            VerificationLevel::Partial {
+                num_signatures: 5,
+            },
+        );
+        let price_update = PythOracle::decode_price_update(&data).unwrap();
+        assert!(PythOracle::price_from_update(&price_update, feed_ids::USDC_USD).is_err());
+    }
 }
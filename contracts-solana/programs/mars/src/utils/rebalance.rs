@@ -1,3 +1,4 @@
+pub use crate::state::RebalanceConfig;
 use crate::state::VaultState;
 use anchor_lang::prelude::*;
 
@@ -9,50 +10,6 @@ use anchor_lang::prelude::*;
 /// - 生成再平衡执行计划
 /// - 执行跨协议资产转移
 
-/// 再平衡阈值配置
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct RebalanceConfig {
-    /// 触发再平衡的偏差阈值（基点）
-    /// 例如：500 = 5% 的偏差将触发再平衡
-    pub deviation_threshold_bps: u16,
-
-    /// 最小再平衡金额（避免频繁小额操作）
-    pub min_rebalance_amount: u64,
-
-    /// 最大单次再平衡金额（风险控制）
-    pub max_rebalance_amount: u64,
-
-    /// 再平衡冷却时间（秒）
-    pub cooldown_seconds: i64,
-
-    /// 最后一次再平衡时间
-    pub last_rebalance_time: i64,
-}
-
-impl RebalanceConfig {
-    pub const DEFAULT_DEVIATION_THRESHOLD_BPS: u16 = 500; // 5%
-    pub const DEFAULT_MIN_AMOUNT: u64 = 10_000_000; // $10 (6位小数)
-    pub const DEFAULT_MAX_AMOUNT: u64 = 1_000_000_000_000; // $1M
-    pub const DEFAULT_COOLDOWN_SECONDS: i64 = 3600; // 1小时
-
-    pub fn default() -> Self {
-        Self {
-            deviation_threshold_bps: Self::DEFAULT_DEVIATION_THRESHOLD_BPS,
-            min_rebalance_amount: Self::DEFAULT_MIN_AMOUNT,
-            max_rebalance_amount: Self::DEFAULT_MAX_AMOUNT,
-            cooldown_seconds: Self::DEFAULT_COOLDOWN_SECONDS,
-            last_rebalance_time: 0,
-        }
-    }
-
-    /// 检查是否在冷却期
-    pub fn is_in_cooldown(&self) -> Result<bool> {
-        let current_time = Clock::get()?.unix_timestamp;
-        let time_since_last = current_time.saturating_sub(self.last_rebalance_time);
-        Ok(time_since_last < self.cooldown_seconds)
-    }
-}
-
 /// 再平衡动作
 #[derive(Debug, Clone)]
 pub struct RebalanceAction {
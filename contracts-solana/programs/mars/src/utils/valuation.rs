@@ -0,0 +1,231 @@
+use crate::constants::{PROTOCOL_JUPITER_LEND, PROTOCOL_KAMINO, SECONDS_PER_YEAR};
+use crate::error::CustomError;
+use anchor_lang::prelude::*;
+
+/// 汇率精度：1 个收益凭证对应的基础代币数量 × 1e12
+pub const EXCHANGE_RATE_PRECISION: u128 = 1_000_000_000_000;
+
+/// Kamino Vault (kvault) VaultState 账户布局（含 8 字节 discriminator）
+mod kamino_layout {
+    pub const TOKEN_MINT: usize = 80;
+    pub const SHARES_MINT: usize = 184;
+    pub const SHARES_ISSUED: usize = 232;
+    pub const PREV_AUM_SF: usize = 280;
+    pub const PENDING_FEES_SF: usize = 296;
+    pub const MIN_LEN: usize = 312;
+    /// prev_aum_sf / pending_fees_sf 为 60 位小数的定点数
+    pub const FRACTION_BITS: u32 = 60;
+}
+
+/// Jupiter Lend Lending 账户布局（含 8 字节 discriminator）
+mod jupiter_layout {
+    pub const MINT: usize = 8;
+    pub const F_TOKEN_MINT: usize = 40;
+    pub const TOKEN_EXCHANGE_PRICE: usize = 115;
+    pub const MIN_LEN: usize = 123;
+}
+
+/// 协议持仓估值工具
+///
+/// 从协议自身账户读取收益凭证汇率（Kamino Vault 份额价格、jlToken 兑换价格），
+/// 按汇率将 Vault 持有的收益凭证折算为基础代币价值。读取前校验账户 owner 和 mint，
+/// 布局不符时直接报错。
+pub struct PositionValuation;
+
+impl PositionValuation {
+    /// 读取协议的收益凭证汇率（EXCHANGE_RATE_PRECISION 精度）
+    ///
+    /// rate_source 为 Kamino Vault 状态或 Jupiter Lending 账户，必须由对应协议程序持有，
+    /// 且其底层代币和凭证 mint 必须与 Vault 一致
+    pub fn exchange_rate(
+        protocol_id: u8,
+        rate_source: &AccountInfo,
+        base_mint: &Pubkey,
+        receipt_mint: &Pubkey,
+    ) -> Result<u128> {
+        let expected_owner = match protocol_id {
+            PROTOCOL_KAMINO => crate::constants::kamino::PROGRAM_ID,
+            PROTOCOL_JUPITER_LEND => crate::constants::jupiter::LEND_PROGRAM_ID,
+            _ => return err!(CustomError::UnsupportedProtocol),
+        };
+        require_keys_eq!(*rate_source.owner, expected_owner, CustomError::InvalidRateSource);
+
+        let data = rate_source.try_borrow_data()?;
+        match protocol_id {
+            PROTOCOL_KAMINO => Self::kamino_exchange_rate(&data, base_mint, receipt_mint),
+            _ => Self::jupiter_exchange_rate(&data, base_mint, receipt_mint),
+        }
+    }
+
+    /// Kamino Vault 份额价格 = (prev_aum - pending_fees) / shares_issued
+    pub fn kamino_exchange_rate(
+        data: &[u8],
+        base_mint: &Pubkey,
+        receipt_mint: &Pubkey,
+    ) -> Result<u128> {
+        use kamino_layout::*;
+
+        require!(data.len() >= MIN_LEN, CustomError::InvalidRateSource);
+        require_keys_eq!(read_pubkey(data, TOKEN_MINT), *base_mint, CustomError::InvalidRateSource);
+        require_keys_eq!(
+            read_pubkey(data, SHARES_MINT),
+            *receipt_mint,
+            CustomError::InvalidRateSource
+        );
+
+        let shares_issued = read_u64(data, SHARES_ISSUED);
+        if shares_issued == 0 {
+            return Ok(EXCHANGE_RATE_PRECISION);
+        }
+
+        let aum = read_u128(data, PREV_AUM_SF).saturating_sub(read_u128(data, PENDING_FEES_SF))
+            >> FRACTION_BITS;
+        aum.checked_mul(EXCHANGE_RATE_PRECISION)
+            .and_then(|v| v.checked_div(shares_issued as u128))
+            .ok_or(error!(CustomError::MathOverflow))
+    }
+
+    /// jlToken 兑换价格（Jupiter Lend 的 token_exchange_price 本身即为 1e12 精度）
+    pub fn jupiter_exchange_rate(
+        data: &[u8],
+        base_mint: &Pubkey,
+        receipt_mint: &Pubkey,
+    ) -> Result<u128> {
+        use jupiter_layout::*;
+
+        require!(data.len() >= MIN_LEN, CustomError::InvalidRateSource);
+        require_keys_eq!(read_pubkey(data, MINT), *base_mint, CustomError::InvalidRateSource);
+        require_keys_eq!(
+            read_pubkey(data, F_TOKEN_MINT),
+            *receipt_mint,
+            CustomError::InvalidRateSource
+        );
+
+        let rate = read_u64(data, TOKEN_EXCHANGE_PRICE) as u128;
+        require!(rate > 0, CustomError::InvalidRateSource);
+        Ok(rate)
+    }
+
+    /// 按汇率折算收益凭证的基础代币价值（向下取整，有利于金库）
+    pub fn value_of(receipt_amount: u64, rate: u128) -> Result<u64> {
+        (receipt_amount as u128)
+            .checked_mul(rate)
+            .and_then(|v| v.checked_div(EXCHANGE_RATE_PRECISION))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(error!(CustomError::MathOverflow))
+    }
+
+    /// 根据收益凭证汇率的变化计算年化收益率（基点）
+    ///
+    /// 汇率下跌或时间未前进时返回 0
+    pub fn annualize_price_growth(
+        previous_price: u64,
+        previous_time: i64,
+        current_price: u64,
+        current_time: i64,
+    ) -> Option<u64> {
+        let elapsed = current_time.checked_sub(previous_time)?;
+        if previous_price == 0 || elapsed <= 0 || current_price <= previous_price {
+            return Some(0);
+        }
+
+        ((current_price - previous_price) as u128)
+            .checked_mul(10_000)?
+            .checked_mul(SECONDS_PER_YEAR as u128)?
+            .checked_div(previous_price as u128)?
+            .checked_div(elapsed as u128)
+            .map(|v| u64::try_from(v).unwrap_or(u64::MAX))
+    }
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kamino_vault_data(
+        token_mint: &Pubkey,
+        shares_mint: &Pubkey,
+        aum: u64,
+        shares: u64,
+    ) -> Vec<u8> {
+        let mut data = vec![0u8; kamino_layout::MIN_LEN];
+        data[80..112].copy_from_slice(token_mint.as_ref());
+        data[184..216].copy_from_slice(shares_mint.as_ref());
+        data[232..240].copy_from_slice(&shares.to_le_bytes());
+        let aum_sf = (aum as u128) << kamino_layout::FRACTION_BITS;
+        data[280..296].copy_from_slice(&aum_sf.to_le_bytes());
+        data
+    }
+
+    fn jupiter_lending_data(mint: &Pubkey, f_token_mint: &Pubkey, exchange_price: u64) -> Vec<u8> {
+        let mut data = vec![0u8; jupiter_layout::MIN_LEN];
+        data[8..40].copy_from_slice(mint.as_ref());
+        data[40..72].copy_from_slice(f_token_mint.as_ref());
+        data[115..123].copy_from_slice(&exchange_price.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_kamino_share_price_from_aum() {
+        let token_mint = Pubkey::new_unique();
+        let shares_mint = Pubkey::new_unique();
+        // AUM 增值 5%：1_050_000 代币 / 1_000_000 份额
+        let data = kamino_vault_data(&token_mint, &shares_mint, 1_050_000, 1_000_000);
+
+        let rate =
+            PositionValuation::kamino_exchange_rate(&data, &token_mint, &shares_mint).unwrap();
+        assert_eq!(rate, EXCHANGE_RATE_PRECISION * 105 / 100);
+        assert_eq!(PositionValuation::value_of(200_000, rate).unwrap(), 210_000);
+
+        // mint 不匹配说明账户或布局错误
+        assert!(PositionValuation::kamino_exchange_rate(&data, &shares_mint, &shares_mint).is_err());
+        assert!(PositionValuation::kamino_exchange_rate(&data[..100], &token_mint, &shares_mint)
+            .is_err());
+    }
+
+    #[test]
+    fn test_jupiter_exchange_price() {
+        let mint = Pubkey::new_unique();
+        let f_token_mint = Pubkey::new_unique();
+        let data = jupiter_lending_data(&mint, &f_token_mint, 1_020_000_000_000);
+
+        let rate = PositionValuation::jupiter_exchange_rate(&data, &mint, &f_token_mint).unwrap();
+        assert_eq!(PositionValuation::value_of(1_000_000, rate).unwrap(), 1_020_000);
+
+        assert!(PositionValuation::jupiter_exchange_rate(&data, &mint, &mint).is_err());
+        let zero_price = jupiter_lending_data(&mint, &f_token_mint, 0);
+        assert!(
+            PositionValuation::jupiter_exchange_rate(&zero_price, &mint, &f_token_mint).is_err()
+        );
+    }
+
+    #[test]
+    fn test_annualize_price_growth() {
+        // 30天上涨 0.5% -> 约 6.08% 年化
+        let apy = PositionValuation::annualize_price_growth(1_000_000, 0, 1_005_000, 30 * 86_400)
+            .unwrap();
+        assert_eq!(apy, 608);
+
+        // 汇率下跌或时间未前进时 APY 为 0
+        assert_eq!(
+            PositionValuation::annualize_price_growth(1_000_000, 0, 990_000, 86_400),
+            Some(0)
+        );
+        assert_eq!(
+            PositionValuation::annualize_price_growth(1_000_000, 100, 1_100_000, 100),
+            Some(0)
+        );
+    }
+}